@@ -379,6 +379,11 @@ pub enum InternalState {
     Present(PresentSubstate),
 }
 
+/// Callback invoked with the previous and new [`InternalState`] whenever the fuel gauge changes state.
+///
+/// Runs synchronously inside the `on_*` transition methods, so it should return quickly, e.g. by signaling a task.
+pub type TransitionObserver = fn(from: InternalState, to: InternalState);
+
 /// Fuel gauge state, owned by the driver (OEM) and managed via the `on_*` transition methods.
 ///
 /// This holds both the fuel gauge state machine state and the cached static and
@@ -394,6 +399,7 @@ pub struct State<S: StaticBatteryData = StaticBatteryMsgs, D: DynamicBatteryData
     state: InternalState,
     static_cache: S,
     dynamic_cache: D,
+    observer: Option<TransitionObserver>,
 }
 
 impl<S: StaticBatteryData, D: DynamicBatteryData> State<S, D> {
@@ -402,6 +408,23 @@ impl<S: StaticBatteryData, D: DynamicBatteryData> State<S, D> {
         self.state
     }
 
+    /// Register a callback to observe state transitions, replacing any previous observer.
+    ///
+    /// Pass `None` to remove the observer.
+    pub fn set_transition_observer(&mut self, observer: Option<TransitionObserver>) {
+        self.observer = observer;
+    }
+
+    /// Move to `to`, notifying the observer if the state changed.
+    fn transition(&mut self, to: InternalState) {
+        let from = core::mem::replace(&mut self.state, to);
+        if from != to
+            && let Some(observer) = self.observer
+        {
+            observer(from, to);
+        }
+    }
+
     /// A reference to the cached static battery data.
     pub fn static_cache(&self) -> &S {
         &self.static_cache
@@ -445,7 +468,9 @@ impl<S: StaticBatteryData, D: DynamicBatteryData> State<S, D> {
     /// Transitions to `Present(Operational(Init))`. Should be called by the
     /// driver after hardware initialization succeeds.
     pub fn on_initialized(&mut self) {
-        self.state = InternalState::Present(PresentSubstate::Operational(OperationalSubstate::Init));
+        self.transition(InternalState::Present(PresentSubstate::Operational(
+            OperationalSubstate::Init,
+        )));
     }
 
     /// Update the cached static battery data in place.
@@ -458,7 +483,9 @@ impl<S: StaticBatteryData, D: DynamicBatteryData> State<S, D> {
     pub fn on_static_data(&mut self, update: impl FnOnce(&mut S)) {
         update(&mut self.static_cache);
         if self.is_operational() {
-            self.state = InternalState::Present(PresentSubstate::Operational(OperationalSubstate::Polling));
+            self.transition(InternalState::Present(PresentSubstate::Operational(
+                OperationalSubstate::Polling,
+            )));
         }
     }
 
@@ -479,7 +506,7 @@ impl<S: StaticBatteryData, D: DynamicBatteryData> State<S, D> {
     /// called by the driver when a communication timeout is detected.
     pub fn on_timeout(&mut self) {
         if self.is_present() {
-            self.state = InternalState::Present(PresentSubstate::NotOperational);
+            self.transition(InternalState::Present(PresentSubstate::NotOperational));
         }
     }
 
@@ -490,7 +517,9 @@ impl<S: StaticBatteryData, D: DynamicBatteryData> State<S, D> {
     /// successful ping while recovering.
     pub fn on_recovered(&mut self) {
         if matches!(self.state, InternalState::Present(PresentSubstate::NotOperational)) {
-            self.state = InternalState::Present(PresentSubstate::Operational(OperationalSubstate::Init));
+            self.transition(InternalState::Present(PresentSubstate::Operational(
+                OperationalSubstate::Init,
+            )));
         }
     }
 }
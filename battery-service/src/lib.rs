@@ -9,7 +9,7 @@ use embedded_services::info;
 use embedded_services::sync::Lockable;
 
 mod acpi;
-#[cfg(feature = "mock")]
+#[cfg(any(test, feature = "mock"))]
 pub mod mock;
 pub mod registration;
 
@@ -19,7 +19,7 @@ pub use registration::{ArrayRegistration, Registration};
 // implement and use the battery service without depending on the interface crate directly.
 pub use battery_service_interface::fuel_gauge::{
     DynamicBatteryData, DynamicBatteryMsgs, FuelGauge, FuelGaugeError, InternalState, OperationalSubstate,
-    PresentSubstate, State, StaticBatteryData, StaticBatteryMsgs,
+    PresentSubstate, State, StaticBatteryData, StaticBatteryMsgs, TransitionObserver,
 };
 pub use battery_service_interface::{BatteryService, DeviceId};
 
@@ -36,6 +36,10 @@ pub struct Service<'hw, Reg: Registration<'hw>> {
 
 impl<'hw, Reg: Registration<'hw>> Service<'hw, Reg> {
     /// Create a new battery service that owns the provided registration.
+    ///
+    /// The service has no dependency on the comms service or any task, so it can be
+    /// constructed and queried entirely in-process, for example in unit tests that
+    /// drive a fuel gauge directly and then check the ACPI responses.
     pub fn new(registration: Reg) -> Self {
         info!("Starting battery-service");
         Self {
@@ -139,3 +143,87 @@ impl<'hw, Reg: Registration<'hw>> battery_service_interface::BatteryService for
         self.device_status(&mut *self.fuel_gauge(battery_id)?.lock().await)
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::mock::{MockFuelGauge, init_state_machine, recover_state_machine};
+    use embassy_sync::mutex::Mutex;
+    use embedded_services::GlobalRawMutex;
+    use std::vec::Vec;
+
+    /// Drive a fuel gauge from bring-up to polling and query it through the service, without any comms endpoint.
+    #[test]
+    fn test_standalone_init_to_polling() {
+        embassy_futures::block_on(async {
+            let fuel_gauge: Mutex<GlobalRawMutex, MockFuelGauge> = Mutex::new(MockFuelGauge::new());
+            let service = Service::new(ArrayRegistration {
+                fuel_gauges: [&fuel_gauge],
+            });
+
+            assert_eq!(
+                fuel_gauge.lock().await.state().internal_state(),
+                InternalState::NotPresent
+            );
+
+            init_state_machine(&fuel_gauge).await.unwrap();
+            assert!(fuel_gauge.lock().await.state().is_polling());
+
+            // 3S pack, 3.7 V nominal per cell
+            let bix = BatteryService::battery_info(&service, DeviceId(0)).await.unwrap();
+            assert_eq!(bix.design_voltage, 11_100);
+            assert!(BatteryService::battery_status(&service, DeviceId(0)).await.is_ok());
+
+            // Only a single fuel gauge is registered
+            assert!(BatteryService::battery_info(&service, DeviceId(1)).await.is_err());
+        });
+    }
+
+    /// Transitions recorded by [`record_transition`]
+    static TRANSITIONS: std::sync::Mutex<Vec<(InternalState, InternalState)>> = std::sync::Mutex::new(Vec::new());
+
+    fn record_transition(from: InternalState, to: InternalState) {
+        TRANSITIONS.lock().unwrap().push((from, to));
+    }
+
+    /// Drive a fuel gauge through bring-up, a communication loss and recovery, observing every transition.
+    #[test]
+    fn test_transition_observer() {
+        const INIT: InternalState = InternalState::Present(PresentSubstate::Operational(OperationalSubstate::Init));
+        const POLLING: InternalState =
+            InternalState::Present(PresentSubstate::Operational(OperationalSubstate::Polling));
+        const NOT_OPERATIONAL: InternalState = InternalState::Present(PresentSubstate::NotOperational);
+
+        embassy_futures::block_on(async {
+            let fuel_gauge: Mutex<GlobalRawMutex, MockFuelGauge> = Mutex::new(MockFuelGauge::new());
+            fuel_gauge
+                .lock()
+                .await
+                .state_mut()
+                .set_transition_observer(Some(record_transition));
+
+            init_state_machine(&fuel_gauge).await.unwrap();
+
+            // Re-reading static data while polling isn't a transition
+            fuel_gauge.lock().await.update_static_data().await.unwrap();
+
+            fuel_gauge.lock().await.state_mut().on_timeout();
+            recover_state_machine(&fuel_gauge).await.unwrap();
+            fuel_gauge.lock().await.update_static_data().await.unwrap();
+
+            assert_eq!(
+                TRANSITIONS.lock().unwrap().as_slice(),
+                &[
+                    (InternalState::NotPresent, INIT),
+                    (INIT, POLLING),
+                    (POLLING, NOT_OPERATIONAL),
+                    (NOT_OPERATIONAL, INIT),
+                    (INIT, POLLING),
+                ]
+            );
+        });
+    }
+}
@@ -36,6 +36,8 @@ pub struct Updater<'a, Device: Lockable<Inner: FwUpdate>, Shared: Lockable<Inner
     customization: Cust,
     shared_state: &'a Shared,
     config: Config,
+    /// Size of the image written so far in the current update
+    image_size: usize,
 }
 
 impl<'a, Device: Lockable<Inner: FwUpdate>, Shared: Lockable<Inner = SharedState>, Cust: Customization>
@@ -55,6 +57,7 @@ impl<'a, Device: Lockable<Inner: FwUpdate>, Shared: Lockable<Inner = SharedState
             component_id,
             customization,
             config,
+            image_size: 0,
         }
     }
 
@@ -195,6 +198,7 @@ impl<'a, Device: Lockable<Inner: FwUpdate>, Shared: Lockable<Inner = SharedState
                 .lock()
                 .await
                 .enter_in_progress(self.config.recovery.tick_interval);
+            self.image_size = 0;
         }
 
         let result = self
@@ -206,6 +210,9 @@ impl<'a, Device: Lockable<Inner: FwUpdate>, Shared: Lockable<Inner = SharedState
         match result {
             Ok(_) => {
                 debug!("Block written successfully");
+                self.image_size = self
+                    .image_size
+                    .max(content.header.firmware_address as usize + data.len());
             }
             Err(e) => {
                 error!("Failed to write block: {:?}", e);
@@ -217,6 +224,17 @@ impl<'a, Device: Lockable<Inner: FwUpdate>, Shared: Lockable<Inner = SharedState
         }
 
         if content.header.flags & FW_UPDATE_FLAG_LAST_BLOCK != 0 {
+            let result = self.device.lock().await.validate_fw_image(self.image_size).await;
+            if let Err(e) = result {
+                error!("FW image failed validation: {:?}", e);
+                // Don't finalize a bad image, abort the update instead
+                self.process_abort_update().await;
+                return InternalResponseData::ContentResponse(FwUpdateContentResponse::new(
+                    content.header.sequence_num,
+                    CfuUpdateContentResponseStatus::ErrorInvalid,
+                ));
+            }
+
             let result = self.device.lock().await.finalize_fw_update().await;
             match result {
                 Ok(_) => {
@@ -207,11 +207,15 @@ impl Test for TestBasicFlow {
                 )))
             );
             assert_eq!(cfu_basic.update_state().await, FwUpdateState::Idle);
-            assert_eq!(device.lock().await.fn_calls.len(), 2);
+            assert_eq!(device.lock().await.fn_calls.len(), 3);
             assert_eq!(
                 device.lock().await.fn_calls.pop_front().unwrap(),
                 FwFnCall::WriteFwContents(0, vec![3; DEFAULT_DATA_LENGTH])
             );
+            assert_eq!(
+                device.lock().await.fn_calls.pop_front().unwrap(),
+                FwFnCall::ValidateFwImage(DEFAULT_DATA_LENGTH)
+            );
             assert_eq!(
                 device.lock().await.fn_calls.pop_front().unwrap(),
                 FwFnCall::FinalizeFwUpdate
@@ -323,6 +327,83 @@ impl Test for TestStartRecoveryFlow {
     }
 }
 
+/// Test that an image which fails validation is aborted instead of finalized.
+struct TestImageValidationFailed;
+
+impl Test for TestImageValidationFailed {
+    async fn run<'a>(&mut self, device: &'a DeviceType, cfu_basic: &'a mut UpdaterType<'a>) {
+        {
+            // Give first content block
+            let output = with_timeout(
+                PER_CALL_TIMEOUT,
+                cfu_basic.process_event(Event::Request(RequestData::GiveContent(FwUpdateContentCommand {
+                    header: FwUpdateContentHeader {
+                        flags: FW_UPDATE_FLAG_FIRST_BLOCK,
+                        data_length: DEFAULT_DATA_LENGTH as u8,
+                        sequence_num: 0,
+                        firmware_address: 0x0,
+                    },
+                    data: [1; DEFAULT_DATA_LENGTH],
+                }))),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                output,
+                Output::CfuResponse(InternalResponseData::ContentResponse(FwUpdateContentResponse::new(
+                    0,
+                    CfuUpdateContentResponseStatus::Success
+                )))
+            );
+            assert_eq!(cfu_basic.update_state().await, FwUpdateState::InProgress(0));
+            device.lock().await.fn_calls.clear();
+        }
+
+        {
+            // Give final content block with a bad image
+            device.lock().await.set_image_valid(false);
+            let output = with_timeout(
+                PER_CALL_TIMEOUT,
+                cfu_basic.process_event(Event::Request(RequestData::GiveContent(FwUpdateContentCommand {
+                    header: FwUpdateContentHeader {
+                        flags: FW_UPDATE_FLAG_LAST_BLOCK,
+                        data_length: DEFAULT_DATA_LENGTH as u8,
+                        sequence_num: 1,
+                        firmware_address: DEFAULT_DATA_LENGTH as u32,
+                    },
+                    data: [2; DEFAULT_DATA_LENGTH],
+                }))),
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(
+                output,
+                Output::CfuResponse(InternalResponseData::ContentResponse(FwUpdateContentResponse::new(
+                    1,
+                    CfuUpdateContentResponseStatus::ErrorInvalid
+                )))
+            );
+            // The update should be aborted, not finalized
+            assert_eq!(cfu_basic.update_state().await, FwUpdateState::Idle);
+            assert_eq!(device.lock().await.fn_calls.len(), 3);
+            assert_eq!(
+                device.lock().await.fn_calls.pop_front().unwrap(),
+                FwFnCall::WriteFwContents(DEFAULT_DATA_LENGTH, vec![2; DEFAULT_DATA_LENGTH])
+            );
+            assert_eq!(
+                device.lock().await.fn_calls.pop_front().unwrap(),
+                FwFnCall::ValidateFwImage(2 * DEFAULT_DATA_LENGTH)
+            );
+            assert_eq!(
+                device.lock().await.fn_calls.pop_front().unwrap(),
+                FwFnCall::AbortFwUpdate
+            );
+        }
+    }
+}
+
 #[tokio::test]
 async fn run_test_basic_flow() {
     run_test(DEFAULT_TIMEOUT, TestBasicFlow).await;
@@ -333,6 +414,11 @@ async fn run_test_start_recovery_flow() {
     run_test(DEFAULT_TIMEOUT, TestStartRecoveryFlow).await;
 }
 
+#[tokio::test]
+async fn run_test_image_validation_failed() {
+    run_test(DEFAULT_TIMEOUT, TestImageValidationFailed).await;
+}
+
 /// Trait for runnable tests.
 ///
 /// This exists because there are lifetime issues with being generic over FnOnce or FnMut.
@@ -108,6 +108,7 @@ pub const DEVICE_CHANNEL_SIZE: usize = 1;
 pub struct CfuDevice {
     node: intrusive_list::Node,
     component_id: ComponentId,
+    dependencies: &'static [ComponentId],
     state: Mutex<GlobalRawMutex, InternalState>,
     request: Channel<GlobalRawMutex, RequestData, DEVICE_CHANNEL_SIZE>,
     response: Channel<GlobalRawMutex, InternalResponseData, DEVICE_CHANNEL_SIZE>,
@@ -134,9 +135,19 @@ impl CfuDeviceContainer for CfuDevice {
 impl CfuDevice {
     /// Constructor for CfuDevice
     pub fn new(component_id: ComponentId) -> Self {
+        Self::new_inner(component_id, &[])
+    }
+
+    /// Constructor for a CfuDevice that must be updated after the components in `dependencies`
+    pub fn new_with_dependencies(component_id: ComponentId, dependencies: &'static [ComponentId]) -> Self {
+        Self::new_inner(component_id, dependencies)
+    }
+
+    fn new_inner(component_id: ComponentId, dependencies: &'static [ComponentId]) -> Self {
         Self {
             node: intrusive_list::Node::uninit(),
             component_id,
+            dependencies,
             state: Mutex::new(InternalState::default()),
             request: Channel::new(),
             response: Channel::new(),
@@ -146,12 +157,22 @@ impl CfuDevice {
     pub fn component_id(&self) -> ComponentId {
         self.component_id
     }
-    /// Setter for component state
+
+    /// Getter for the ids of the components that must be updated before this one
+    pub fn dependencies(&self) -> &'static [ComponentId] {
+        self.dependencies
+    }
+    /// Getter for component state
     /// Intended to be used to auto-block updates if one is in-progress
     pub async fn state(&self) -> InternalState {
         *self.state.lock().await
     }
 
+    /// Setter for component state
+    pub async fn set_state(&self, state: InternalState) {
+        *self.state.lock().await = state;
+    }
+
     /// Send a request to this device
     pub async fn send_request(&self, request: RequestData) {
         self.request.send(request).await;
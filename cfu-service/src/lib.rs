@@ -1,10 +1,12 @@
 #![no_std]
 
 use embassy_sync::channel::Channel;
+use embassy_sync::semaphore::{GreedySemaphore, Semaphore};
 use embedded_cfu_protocol::client::CfuReceiveContent;
 use embedded_cfu_protocol::components::CfuComponentTraits;
 use embedded_cfu_protocol::protocol_definitions::*;
 use embedded_services::{GlobalRawMutex, comms, error, info, intrusive_list, trace};
+use heapless::Vec;
 
 pub mod basic;
 pub mod buffer;
@@ -44,8 +46,16 @@ impl<T, C> CfuReceiveContent<T, C, ()> for CfuClient {
 impl CfuClient {
     /// Create a new Cfu Client
     pub async fn new(service_storage: &'static embassy_sync::once_lock::OnceLock<CfuClient>) -> &'static Self {
+        Self::new_with_config(service_storage, ClientConfig::default()).await
+    }
+
+    /// Create a new Cfu Client with the given config
+    pub async fn new_with_config(
+        service_storage: &'static embassy_sync::once_lock::OnceLock<CfuClient>,
+        config: ClientConfig,
+    ) -> &'static Self {
         let service_storage = service_storage.get_or_init(|| Self {
-            context: ClientContext::new(),
+            context: ClientContext::new_with_config(config),
             tp: comms::Endpoint::uninit(comms::EndpointID::Internal(comms::Internal::Nonvol)),
         });
 
@@ -139,6 +149,21 @@ impl CfuClient {
     ) -> Result<component::InternalResponseData, CfuError> {
         self.context.route_request(to, request).await
     }
+
+    /// Abort all in-progress component updates, see [`ClientContext::abort_all`]
+    pub async fn abort_all<const N: usize>(&self) -> Vec<ComponentId, N> {
+        self.context.abort_all().await
+    }
+
+    /// Get the order in which registered components should be updated, see [`ClientContext::update_order`]
+    pub fn update_order<const N: usize>(&self) -> Result<Vec<ComponentId, N>, CfuError> {
+        self.context.update_order()
+    }
+
+    /// Get the firmware version of every registered component, see [`ClientContext::all_fw_versions`]
+    pub async fn all_fw_versions<const N: usize>(&self) -> Vec<(ComponentId, FwVersion), N> {
+        self.context.all_fw_versions().await
+    }
 }
 
 impl comms::MailboxDelegate for CfuClient {}
@@ -155,6 +180,10 @@ pub enum CfuError {
     ComponentBusy,
     /// Component encountered a protocol error during execution
     ProtocolError(CfuProtocolError),
+    /// Component dependencies form a cycle
+    DependencyCycle,
+    /// More components are registered than fit in the provided list
+    TooManyComponents,
 }
 
 /// Request to the power policy service
@@ -167,6 +196,30 @@ pub struct Request {
     pub data: component::RequestData,
 }
 
+/// Cfu client config
+#[derive(Copy, Clone)]
+pub struct ClientConfig {
+    /// Maximum number of routed requests that may be in flight at once
+    max_concurrent_requests: usize,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_requests: usize::MAX,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// Create a new config limiting the number of concurrently routed requests, a limit of zero is treated as one
+    pub fn with_max_concurrent_requests(max_concurrent_requests: usize) -> Self {
+        Self {
+            max_concurrent_requests: max_concurrent_requests.max(1),
+        }
+    }
+}
+
 /// Cfu context
 pub struct ClientContext {
     /// Registered devices
@@ -175,6 +228,8 @@ pub struct ClientContext {
     request: Channel<GlobalRawMutex, Request, { component::DEVICE_CHANNEL_SIZE }>,
     /// Response from components
     response: Channel<GlobalRawMutex, component::InternalResponseData, { component::DEVICE_CHANNEL_SIZE }>,
+    /// Permits for routed requests, limits how many component updates proceed at once
+    route_permits: GreedySemaphore<GlobalRawMutex>,
 }
 
 impl Default for ClientContext {
@@ -185,10 +240,16 @@ impl Default for ClientContext {
 
 impl ClientContext {
     pub fn new() -> Self {
+        Self::new_with_config(ClientConfig::default())
+    }
+
+    /// Create a new context with the given config
+    pub fn new_with_config(config: ClientConfig) -> Self {
         Self {
             devices: embedded_services::intrusive_list::IntrusiveList::new(),
             request: Channel::new(),
             response: Channel::new(),
+            route_permits: GreedySemaphore::new(config.max_concurrent_requests),
         }
     }
 
@@ -227,12 +288,125 @@ impl ClientContext {
         request: component::RequestData,
     ) -> Result<component::InternalResponseData, CfuError> {
         let device = self.get_device(to)?;
+        // Requests beyond the configured limit wait here until an in-flight request completes
+        let Ok(_permit) = self.route_permits.acquire(1).await;
         device
             .execute_device_request(request)
             .await
             .map_err(CfuError::ProtocolError)
     }
 
+    /// Abort all in-progress component updates
+    ///
+    /// Sends [`component::RequestData::AbortUpdate`] to every registered device that is not idle, waits for its
+    /// response and returns it to the idle state. Returns the IDs of the components that were aborted, IDs beyond
+    /// the capacity `N` are aborted but not returned.
+    pub async fn abort_all<const N: usize>(&self) -> Vec<ComponentId, N> {
+        let mut aborted = Vec::new();
+        for device in &self.devices {
+            let Some(device) = device.data::<component::CfuDevice>() else {
+                error!("Non-device located in devices list");
+                continue;
+            };
+
+            if device.state().await.state == component::ComponentState::Idle {
+                continue;
+            }
+
+            let id = device.component_id();
+            info!("Aborting update for comp {}", id);
+            if let Err(e) = device.execute_device_request(component::RequestData::AbortUpdate).await {
+                error!("Failed to abort update for comp {}: {:?}", id, e);
+                continue;
+            }
+
+            device.set_state(component::InternalState::default()).await;
+            if aborted.push(id).is_err() {
+                error!("Aborted component list full, comp {} not reported", id);
+            }
+        }
+
+        aborted
+    }
+
+    /// Get the firmware version of every registered component
+    ///
+    /// Sends [`component::RequestData::FwVersionRequest`] to every registered device and collects the reported
+    /// versions. Components that fail to respond with a version are skipped, components beyond the capacity `N` are
+    /// queried but not returned.
+    pub async fn all_fw_versions<const N: usize>(&self) -> Vec<(ComponentId, FwVersion), N> {
+        let mut versions = Vec::new();
+        for device in &self.devices {
+            let Some(device) = device.data::<component::CfuDevice>() else {
+                error!("Non-device located in devices list");
+                continue;
+            };
+
+            let id = device.component_id();
+            let version = match device
+                .execute_device_request(component::RequestData::FwVersionRequest)
+                .await
+            {
+                Ok(component::InternalResponseData::FwVersionResponse(r)) => r.component_info[0].fw_version,
+                Ok(resp) => {
+                    error!("Invalid response to get fw version {:?} from comp {}", resp, id);
+                    continue;
+                }
+                Err(e) => {
+                    error!("Failed to get fw version for comp {}: {:?}", id, e);
+                    continue;
+                }
+            };
+
+            if versions.push((id, version)).is_err() {
+                error!("Fw version list full, comp {} not reported", id);
+            }
+        }
+
+        versions
+    }
+
+    /// Get the order in which registered components should be updated
+    ///
+    /// Returns the ids of all registered components, each after every registered component it depends on, see
+    /// [`component::CfuDevice::new_with_dependencies`]. Dependencies on unregistered components are ignored. Returns
+    /// [`CfuError::DependencyCycle`] if the dependencies can't be satisfied, or [`CfuError::TooManyComponents`] if
+    /// more than `N` components are registered.
+    pub fn update_order<const N: usize>(&self) -> Result<Vec<ComponentId, N>, CfuError> {
+        let mut order: Vec<ComponentId, N> = Vec::new();
+        loop {
+            let mut progressed = false;
+            let mut remaining = false;
+            for device in self.devices.iter_only::<component::CfuDevice>() {
+                let id = device.component_id();
+                if order.contains(&id) {
+                    continue;
+                }
+
+                let ready = device
+                    .dependencies()
+                    .iter()
+                    .all(|dependency| order.contains(dependency) || self.get_device(*dependency).is_err());
+                if ready {
+                    order.push(id).map_err(|_| CfuError::TooManyComponents)?;
+                    progressed = true;
+                } else {
+                    remaining = true;
+                }
+            }
+
+            if !remaining {
+                return Ok(order);
+            }
+
+            // Every remaining component waits on another remaining component
+            if !progressed {
+                error!("Cycle in component dependencies");
+                return Err(CfuError::DependencyCycle);
+            }
+        }
+    }
+
     /// Send a request to the specific CFU device, but don't wait for a response
     pub async fn send_device_request(&self, to: ComponentId, request: component::RequestData) -> Result<(), CfuError> {
         let device = self.get_device(to)?;
@@ -251,6 +425,19 @@ impl ClientContext {
         self.request.receive().await
     }
 
+    /// Discard all queued cfu requests without waiting, returning how many were dropped
+    ///
+    /// Intended for use when aborting an update so that stale requests aren't processed after the abort. Senders of
+    /// the dropped requests do not receive a response, so this should only be used when those senders are also being
+    /// cancelled.
+    pub fn drain_requests(&self) -> usize {
+        let mut dropped = 0;
+        while self.request.try_receive().is_ok() {
+            dropped += 1;
+        }
+        dropped
+    }
+
     /// Send a response to a cfu request
     pub async fn send_response(&self, response: component::InternalResponseData) {
         self.response.send(response).await
@@ -276,3 +463,236 @@ impl ClientContext {
         &self.devices
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use crate::component::{CfuDevice, ComponentState, InternalResponseData, InternalState, RequestData};
+    use embassy_futures::join::{join3, join4};
+    use embassy_time::{Duration, TimeoutError, with_timeout};
+    use static_cell::StaticCell;
+
+    const PER_CALL_TIMEOUT: Duration = Duration::from_millis(1000);
+
+    /// Respond to a single abort request
+    async fn respond_to_abort(device: &CfuDevice) {
+        assert_eq!(device.wait_request().await, RequestData::AbortUpdate);
+        device.send_response(InternalResponseData::ComponentPrepared).await;
+    }
+
+    /// Respond to a single fw version request
+    async fn respond_to_fw_version(device: &CfuDevice, response: InternalResponseData) {
+        assert_eq!(device.wait_request().await, RequestData::FwVersionRequest);
+        device.send_response(response).await;
+    }
+
+    fn fw_version_response(id: ComponentId, version: FwVersion) -> InternalResponseData {
+        InternalResponseData::FwVersionResponse(GetFwVersionResponse {
+            header: GetFwVersionResponseHeader::new(1, GetFwVerRespHeaderByte3::NoSpecialFlags),
+            component_info: [FwVerComponentInfo::new(version, id); MAX_CMPT_COUNT],
+        })
+    }
+
+    /// Test that draining discards every queued request and leaves nothing to wait on
+    #[tokio::test]
+    async fn test_drain_requests() {
+        let context = ClientContext::new();
+        assert_eq!(context.drain_requests(), 0);
+
+        // Fill the request channel
+        let mut queued = 0;
+        while context
+            .request
+            .try_send(Request {
+                id: queued,
+                data: RequestData::AbortUpdate,
+            })
+            .is_ok()
+        {
+            queued += 1;
+        }
+        assert_eq!(queued as usize, component::DEVICE_CHANNEL_SIZE);
+
+        assert_eq!(context.drain_requests(), component::DEVICE_CHANNEL_SIZE);
+        assert_eq!(
+            with_timeout(PER_CALL_TIMEOUT, context.wait_request()).await,
+            Err(TimeoutError)
+        );
+        assert_eq!(context.drain_requests(), 0);
+    }
+
+    /// Test that all components mid-update are aborted and idle components are left alone
+    #[tokio::test]
+    async fn test_abort_all() {
+        static DEVICE0: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE1: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE2: StaticCell<CfuDevice> = StaticCell::new();
+
+        let device0: &'static CfuDevice = DEVICE0.init(CfuDevice::new(0));
+        let device1: &'static CfuDevice = DEVICE1.init(CfuDevice::new(1));
+        let device2: &'static CfuDevice = DEVICE2.init(CfuDevice::new(2));
+
+        let context = ClientContext::new();
+        context.register_device(device0).unwrap();
+        context.register_device(device1).unwrap();
+        context.register_device(device2).unwrap();
+
+        // Devices 0 and 1 are mid-update, device 2 is idle
+        device0.set_state(InternalState::new(ComponentState::Busy)).await;
+        device1
+            .set_state(InternalState::new(ComponentState::FinalizingUpdate))
+            .await;
+
+        let (mut aborted, _, _) = with_timeout(
+            PER_CALL_TIMEOUT,
+            join3(
+                context.abort_all::<4>(),
+                respond_to_abort(device0),
+                respond_to_abort(device1),
+            ),
+        )
+        .await
+        .unwrap();
+
+        aborted.sort_unstable();
+        assert_eq!(aborted.as_slice(), &[0, 1]);
+
+        assert_eq!(device0.state().await.state, ComponentState::Idle);
+        assert_eq!(device1.state().await.state, ComponentState::Idle);
+        assert_eq!(device2.state().await.state, ComponentState::Idle);
+
+        // The idle device should not have received an abort
+        assert_eq!(
+            with_timeout(PER_CALL_TIMEOUT, device2.wait_request()).await,
+            Err(TimeoutError)
+        );
+    }
+
+    /// Test that the versions of all components are collected and components that fail to respond are skipped
+    #[tokio::test]
+    async fn test_all_fw_versions() {
+        static DEVICE0: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE1: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE2: StaticCell<CfuDevice> = StaticCell::new();
+
+        let device0: &'static CfuDevice = DEVICE0.init(CfuDevice::new(0));
+        let device1: &'static CfuDevice = DEVICE1.init(CfuDevice::new(1));
+        let device2: &'static CfuDevice = DEVICE2.init(CfuDevice::new(2));
+
+        let context = ClientContext::new();
+        context.register_device(device0).unwrap();
+        context.register_device(device1).unwrap();
+        context.register_device(device2).unwrap();
+
+        // Device 1 responds with something other than a version
+        let (mut versions, _, _, _) = with_timeout(
+            PER_CALL_TIMEOUT,
+            join4(
+                context.all_fw_versions::<4>(),
+                respond_to_fw_version(device0, fw_version_response(0, FwVersion::new(0x100))),
+                respond_to_fw_version(device1, InternalResponseData::ComponentPrepared),
+                respond_to_fw_version(device2, fw_version_response(2, FwVersion::new(0x211))),
+            ),
+        )
+        .await
+        .unwrap();
+
+        versions.sort_unstable_by_key(|(id, _)| *id);
+        assert_eq!(
+            versions.as_slice(),
+            &[(0, FwVersion::new(0x100)), (2, FwVersion::new(0x211))]
+        );
+    }
+
+    /// Test that components are ordered after their dependencies
+    #[test]
+    fn test_update_order() {
+        static DEVICE0: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE1: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE2: StaticCell<CfuDevice> = StaticCell::new();
+
+        // Chain 2 -> 0 -> 1, registered out of order, with a dependency on an unregistered component
+        let device0: &'static CfuDevice = DEVICE0.init(CfuDevice::new_with_dependencies(0, &[1]));
+        let device1: &'static CfuDevice = DEVICE1.init(CfuDevice::new(1));
+        let device2: &'static CfuDevice = DEVICE2.init(CfuDevice::new_with_dependencies(2, &[0, 5]));
+
+        let context = ClientContext::new();
+        context.register_device(device1).unwrap();
+        context.register_device(device0).unwrap();
+        context.register_device(device2).unwrap();
+
+        assert_eq!(context.update_order::<4>().unwrap().as_slice(), &[1, 0, 2]);
+        assert_eq!(context.update_order::<2>(), Err(CfuError::TooManyComponents));
+    }
+
+    /// Test that a dependency cycle is reported
+    #[test]
+    fn test_update_order_cycle() {
+        static DEVICE0: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE1: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE2: StaticCell<CfuDevice> = StaticCell::new();
+
+        // 0 is independent, 1 and 2 depend on each other
+        let device0: &'static CfuDevice = DEVICE0.init(CfuDevice::new(0));
+        let device1: &'static CfuDevice = DEVICE1.init(CfuDevice::new_with_dependencies(1, &[2]));
+        let device2: &'static CfuDevice = DEVICE2.init(CfuDevice::new_with_dependencies(2, &[0, 1]));
+
+        let context = ClientContext::new();
+        context.register_device(device0).unwrap();
+        context.register_device(device1).unwrap();
+        context.register_device(device2).unwrap();
+
+        assert_eq!(context.update_order::<4>(), Err(CfuError::DependencyCycle));
+    }
+
+    /// Test that no more than the configured number of routed requests are in flight at once
+    #[tokio::test]
+    async fn test_route_request_limit() {
+        static DEVICE0: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE1: StaticCell<CfuDevice> = StaticCell::new();
+        static DEVICE2: StaticCell<CfuDevice> = StaticCell::new();
+
+        let device0: &'static CfuDevice = DEVICE0.init(CfuDevice::new(0));
+        let device1: &'static CfuDevice = DEVICE1.init(CfuDevice::new(1));
+        let device2: &'static CfuDevice = DEVICE2.init(CfuDevice::new(2));
+
+        let context = ClientContext::new_with_config(ClientConfig::with_max_concurrent_requests(2));
+        context.register_device(device0).unwrap();
+        context.register_device(device1).unwrap();
+        context.register_device(device2).unwrap();
+
+        let devices = async {
+            assert_eq!(device0.wait_request().await, RequestData::AbortUpdate);
+            assert_eq!(device1.wait_request().await, RequestData::AbortUpdate);
+
+            // Both permits are held, the third request must wait
+            assert_eq!(
+                with_timeout(PER_CALL_TIMEOUT, device2.wait_request()).await,
+                Err(TimeoutError)
+            );
+
+            // Completing one request lets the third proceed
+            device0.send_response(InternalResponseData::ComponentPrepared).await;
+            assert_eq!(device2.wait_request().await, RequestData::AbortUpdate);
+            device1.send_response(InternalResponseData::ComponentPrepared).await;
+            device2.send_response(InternalResponseData::ComponentPrepared).await;
+        };
+
+        let (response0, response1, response2, _) = with_timeout(
+            PER_CALL_TIMEOUT * 4,
+            join4(
+                context.route_request(0, RequestData::AbortUpdate),
+                context.route_request(1, RequestData::AbortUpdate),
+                context.route_request(2, RequestData::AbortUpdate),
+                devices,
+            ),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response0, Ok(InternalResponseData::ComponentPrepared));
+        assert_eq!(response1, Ok(InternalResponseData::ComponentPrepared));
+        assert_eq!(response2, Ok(InternalResponseData::ComponentPrepared));
+    }
+}
@@ -9,6 +9,10 @@ use serde::{Deserialize, Serialize};
 use crate::IntrusiveList;
 use crate::SyncCell;
 use crate::intrusive_list::{self, Node, NodeContainer};
+use crate::warn;
+
+#[cfg(any(test, feature = "mock"))]
+pub mod loopback;
 
 /// key type for OEM Endpoint declarations
 pub type OemKey = isize;
@@ -53,6 +57,9 @@ pub enum Internal {
     /// Security service provider
     Security,
 
+    /// Time and alarm service provider
+    TimeAlarm,
+
     /// OEM defined receiver
     Oem(OemKey),
 }
@@ -116,12 +123,13 @@ impl<'a> Data<'a> {
     /// Ex:
     /// ```
     /// # use core::any::TypeId;
-    /// # use embedded_services::comms::{Data, Message, EndpointID, Internal};
+    /// # use embedded_services::comms::{Data, Message, EndpointID, Internal, Priority};
     /// struct MessageClassA;
     /// struct MessageClassB;
     /// let message = Message {
     ///     from: EndpointID::from(Internal::PlatformInfo),
     ///     to: EndpointID::from(Internal::PlatformInfo),
+    ///     priority: Priority::Normal,
     ///     data: Data::new(&MessageClassA),
     /// };
     /// if message.data.type_id() == TypeId::of::<MessageClassA>() {
@@ -145,6 +153,21 @@ impl<'a> Data<'a> {
     }
 }
 
+/// Delivery priority of a message
+///
+/// Messages are delivered to delegates as they are sent, delegates that queue messages may use this to service
+/// high priority messages (e.g. a critical thermal shutdown) ahead of normal ones (e.g. bulk firmware content).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Priority {
+    /// default priority
+    #[default]
+    Normal,
+
+    /// expedited, should be serviced before any normal priority messages
+    High,
+}
+
 /// Message to receive
 #[derive(Copy, Clone, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -155,16 +178,91 @@ pub struct Message<'a> {
     /// where this message is going
     pub to: EndpointID,
 
+    /// delivery priority
+    pub priority: Priority,
+
     /// message content
     pub data: Data<'a>,
 }
 
+/// Handler for a single message type, see [`Message::try_dispatch`]
+pub type MessageHandler<'h> = &'h dyn Fn(&Message) -> Result<(), MailboxDelegateError>;
+
+impl Message<'_> {
+    /// Fetch type ID for message contents, shorthand for [`Data::type_id`]
+    pub fn type_id(&self) -> TypeId {
+        self.data.type_id()
+    }
+
+    /// Dispatch this message to the handler registered for its contents type
+    ///
+    /// Allows a delegate that supports many message types to match once instead of attempting a downcast per type:
+    /// ```
+    /// # use core::any::TypeId;
+    /// # use embedded_services::comms::{Data, Message, EndpointID, Internal, Priority};
+    /// struct MessageClassA;
+    /// struct MessageClassB;
+    /// let message = Message {
+    ///     from: EndpointID::from(Internal::PlatformInfo),
+    ///     to: EndpointID::from(Internal::PlatformInfo),
+    ///     priority: Priority::Normal,
+    ///     data: Data::new(&MessageClassA),
+    /// };
+    /// let result = message.try_dispatch(&[
+    ///     (TypeId::of::<MessageClassA>(), &|_message| Ok(())),
+    ///     (TypeId::of::<MessageClassB>(), &|_message| Ok(())),
+    /// ]);
+    /// assert!(result.is_ok());
+    /// ```
+    ///
+    /// Returns [`MailboxDelegateError::MessageNotFound`] if no handler matches.
+    pub fn try_dispatch(&self, handlers: &[(TypeId, MessageHandler<'_>)]) -> Result<(), MailboxDelegateError> {
+        let type_id = self.type_id();
+        handlers
+            .iter()
+            .find(|(id, _)| *id == type_id)
+            .ok_or(MailboxDelegateError::MessageNotFound)
+            .and_then(|(_, handler)| handler(self))
+    }
+}
+
 /// Trait to receive messages
 pub trait MailboxDelegate {
     /// Receive a Message (typically, push contents to queue or queue some action)
     fn receive(&self, _message: &Message) -> Result<(), MailboxDelegateError> {
         Ok(())
     }
+
+    /// Maximum number of received messages this delegate queues for processing, `None` if it doesn't queue messages
+    ///
+    /// Once [`MailboxDelegate::queue_len`] reaches this depth, further messages are rejected with
+    /// [`MailboxDelegateError::BufferFull`] without being passed to [`MailboxDelegate::receive`].
+    fn queue_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of received messages currently queued for processing
+    fn queue_len(&self) -> usize {
+        0
+    }
+}
+
+/// Occupancy of a delegate's message queue, see [`Endpoint::queue_occupancy`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueueOccupancy {
+    /// number of messages queued
+    pub len: usize,
+
+    /// maximum number of messages that can be queued
+    pub depth: usize,
+}
+
+impl QueueOccupancy {
+    /// Returns true if no more messages can be queued
+    pub fn is_full(&self) -> bool {
+        self.len >= self.depth
+    }
 }
 
 /// Message transmission Error
@@ -225,6 +323,27 @@ impl Endpoint {
         send(self.id, to, data).await
     }
 
+    /// Send a generic message to an endpoint with the given priority
+    pub async fn send_with_priority(
+        &self,
+        to: EndpointID,
+        priority: Priority,
+        data: &(impl Any + Send + Sync),
+    ) -> Result<(), Infallible> {
+        send_with_priority(self.id, to, priority, data).await
+    }
+
+    /// Get the occupancy of the registered delegate's message queue, for diagnostics
+    ///
+    /// Returns `None` if no delegate is registered or the delegate doesn't declare a queue depth.
+    pub fn queue_occupancy(&self) -> Option<QueueOccupancy> {
+        let delegator = self.delegator.get()?;
+        Some(QueueOccupancy {
+            len: delegator.queue_len(),
+            depth: delegator.queue_depth()?,
+        })
+    }
+
     fn init(&self, rx: &'static dyn MailboxDelegate) {
         self.delegator.set(Some(rx));
     }
@@ -232,7 +351,11 @@ impl Endpoint {
     fn process(&self, message: &Message) {
         if let Some(delegator) = self.delegator.get() {
             // REVISIT: Continue to propagate error
-            let _res = delegator.receive(message);
+            let _res = if self.queue_occupancy().is_some_and(|occupancy| occupancy.is_full()) {
+                Err(MailboxDelegateError::BufferFull)
+            } else {
+                delegator.receive(message)
+            };
         }
     }
 }
@@ -246,6 +369,38 @@ pub async fn register_endpoint(
     get_list(node.id).get().await.push(node)
 }
 
+/// [`register_endpoint`] with a bounded retry, awaiting `delay` between attempts
+///
+/// Allows services to tolerate a transient registration failure, such as the node still being removed from a list
+/// during a restart. Registration is attempted at least once and at most `attempts` times, the last error is
+/// returned if every attempt fails.
+pub async fn register_endpoint_retry(
+    this: &'static impl MailboxDelegate,
+    node: &'static Endpoint,
+    attempts: usize,
+    mut delay: impl AsyncFnMut(),
+) -> Result<(), intrusive_list::Error> {
+    let mut attempt = 1;
+    loop {
+        match register_endpoint(this, node).await {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(_) => {
+                warn!("Failed to register endpoint, attempt {} of {}", attempt, attempts);
+                delay().await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// remove a receiver node registered with [`register_endpoint`], messages routed to its ID are no longer delivered to it
+pub async fn deregister_endpoint(node: &'static Endpoint) -> Result<(), intrusive_list::Error> {
+    get_list(node.id).get().await.remove(node)?;
+    node.delegator.set(None);
+    Ok(())
+}
+
 fn get_list(target: EndpointID) -> &'static OnceLock<IntrusiveList> {
     match target {
         EndpointID::External(ext_endpoint) => match ext_endpoint {
@@ -277,6 +432,7 @@ fn get_list(target: EndpointID) -> &'static OnceLock<IntrusiveList> {
             static INTERNAL_LIST_NONVOL: OnceLock<IntrusiveList> = OnceLock::new();
             static INTERNAL_LIST_DEBUG: OnceLock<IntrusiveList> = OnceLock::new();
             static INTERNAL_LIST_SECURITY: OnceLock<IntrusiveList> = OnceLock::new();
+            static INTERNAL_LIST_TIME_ALARM: OnceLock<IntrusiveList> = OnceLock::new();
             static INTERNAL_LIST_OEM: OnceLock<IntrusiveList> = OnceLock::new();
 
             match int_endpoint {
@@ -292,6 +448,7 @@ fn get_list(target: EndpointID) -> &'static OnceLock<IntrusiveList> {
                 Nonvol => &INTERNAL_LIST_NONVOL,
                 Debug => &INTERNAL_LIST_DEBUG,
                 Security => &INTERNAL_LIST_SECURITY,
+                TimeAlarm => &INTERNAL_LIST_TIME_ALARM,
                 Oem(_key) => &INTERNAL_LIST_OEM,
             }
         }
@@ -300,9 +457,20 @@ fn get_list(target: EndpointID) -> &'static OnceLock<IntrusiveList> {
 
 /// Send a generic message to an endpoint
 pub async fn send(from: EndpointID, to: EndpointID, data: &(impl Any + Send + Sync)) -> Result<(), Infallible> {
+    send_with_priority(from, to, Priority::Normal, data).await
+}
+
+/// Send a generic message to an endpoint with the given priority
+pub async fn send_with_priority(
+    from: EndpointID,
+    to: EndpointID,
+    priority: Priority,
+    data: &(impl Any + Send + Sync),
+) -> Result<(), Infallible> {
     route(Message {
         from,
         to,
+        priority,
         data: Data::new(data),
     })
     .await
@@ -337,6 +505,7 @@ pub(crate) fn init() {
     get_list(Internal::Nonvol.into()).get_or_init(IntrusiveList::new);
     get_list(Internal::Debug.into()).get_or_init(IntrusiveList::new);
     get_list(Internal::Security.into()).get_or_init(IntrusiveList::new);
+    get_list(Internal::TimeAlarm.into()).get_or_init(IntrusiveList::new);
     get_list(Internal::Oem(0).into()).get_or_init(IntrusiveList::new);
 
     // initialize external subscriber lists
@@ -344,3 +513,143 @@ pub(crate) fn init() {
     get_list(External::Host.into()).get_or_init(IntrusiveList::new);
     get_list(External::Oem(0).into()).get_or_init(IntrusiveList::new);
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use core::cell::Cell;
+
+    use embassy_sync::channel::Channel;
+
+    use super::*;
+    use crate::GlobalRawMutex;
+
+    struct MessageA(u8);
+    struct MessageB(u16);
+    struct MessageC;
+    struct MessageUnknown;
+
+    /// Delegate that records the last message received of each type
+    #[derive(Default)]
+    struct TestDelegate {
+        a: Cell<Option<u8>>,
+        b: Cell<Option<u16>>,
+        c: Cell<bool>,
+    }
+
+    impl MailboxDelegate for TestDelegate {
+        fn receive(&self, message: &Message) -> Result<(), MailboxDelegateError> {
+            message.try_dispatch(&[
+                (TypeId::of::<MessageA>(), &|message| {
+                    let data = message
+                        .data
+                        .get::<MessageA>()
+                        .ok_or(MailboxDelegateError::InvalidData)?;
+                    self.a.set(Some(data.0));
+                    Ok(())
+                }),
+                (TypeId::of::<MessageB>(), &|message| {
+                    let data = message
+                        .data
+                        .get::<MessageB>()
+                        .ok_or(MailboxDelegateError::InvalidData)?;
+                    self.b.set(Some(data.0));
+                    Ok(())
+                }),
+                (TypeId::of::<MessageC>(), &|_| {
+                    self.c.set(true);
+                    Ok(())
+                }),
+            ])
+        }
+    }
+
+    fn message<'a>(data: &'a (impl Any + Send + Sync)) -> Message<'a> {
+        Message {
+            from: Internal::PlatformInfo.into(),
+            to: Internal::PlatformInfo.into(),
+            priority: Priority::Normal,
+            data: Data::new(data),
+        }
+    }
+
+    #[test]
+    fn test_try_dispatch() {
+        let delegate = TestDelegate::default();
+
+        assert!(delegate.receive(&message(&MessageA(1))).is_ok());
+        assert_eq!(delegate.a.get(), Some(1));
+        assert_eq!(delegate.b.get(), None);
+        assert!(!delegate.c.get());
+
+        assert!(delegate.receive(&message(&MessageB(2))).is_ok());
+        assert_eq!(delegate.b.get(), Some(2));
+        assert!(!delegate.c.get());
+
+        assert!(delegate.receive(&message(&MessageC)).is_ok());
+        assert!(delegate.c.get());
+    }
+
+    #[test]
+    fn test_try_dispatch_unknown() {
+        let delegate = TestDelegate::default();
+
+        assert!(matches!(
+            delegate.receive(&message(&MessageUnknown)),
+            Err(MailboxDelegateError::MessageNotFound)
+        ));
+        assert_eq!(delegate.a.get(), None);
+        assert_eq!(delegate.b.get(), None);
+        assert!(!delegate.c.get());
+    }
+
+    /// Delegate declaring a shallower queue depth than its channel can hold
+    struct QueuedDelegate {
+        tp: Endpoint,
+        queue: Channel<GlobalRawMutex, u8, 4>,
+    }
+
+    impl MailboxDelegate for QueuedDelegate {
+        fn receive(&self, message: &Message) -> Result<(), MailboxDelegateError> {
+            let data = message.data.get::<u8>().ok_or(MailboxDelegateError::InvalidData)?;
+            self.queue.try_send(*data).map_err(|_| MailboxDelegateError::BufferFull)
+        }
+
+        fn queue_depth(&self) -> Option<usize> {
+            Some(2)
+        }
+
+        fn queue_len(&self) -> usize {
+            self.queue.len()
+        }
+    }
+
+    static QUEUED: QueuedDelegate = QueuedDelegate {
+        tp: Endpoint::uninit(EndpointID::Internal(Internal::Oem(6))),
+        queue: Channel::new(),
+    };
+
+    #[tokio::test]
+    async fn test_queue_depth() {
+        crate::init().await;
+        register_endpoint(&QUEUED, &QUEUED.tp).await.unwrap();
+        let id = QUEUED.tp.get_id();
+        assert_eq!(QUEUED.tp.queue_occupancy(), Some(QueueOccupancy { len: 0, depth: 2 }));
+
+        // The third message is rejected once the declared depth is reached, even though the channel has room
+        for data in 0u8..3 {
+            send(id, id, &data).await.unwrap();
+        }
+        let occupancy = QUEUED.tp.queue_occupancy().unwrap();
+        assert_eq!(occupancy, QueueOccupancy { len: 2, depth: 2 });
+        assert!(occupancy.is_full());
+
+        // Draining makes room again
+        assert_eq!(QUEUED.queue.try_receive().unwrap(), 0);
+        assert!(!QUEUED.tp.queue_occupancy().unwrap().is_full());
+        send(id, id, &3u8).await.unwrap();
+        assert_eq!(QUEUED.queue.try_receive().unwrap(), 1);
+        assert_eq!(QUEUED.queue.try_receive().unwrap(), 3);
+        assert_eq!(QUEUED.tp.queue_occupancy(), Some(QueueOccupancy { len: 0, depth: 2 }));
+    }
+}
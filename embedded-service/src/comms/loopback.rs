@@ -0,0 +1,241 @@
+//! Loopback endpoint that records received messages, intended for testing code built on the comms system
+use core::any::Any;
+
+use embassy_futures::select::{Either, select};
+use embassy_sync::channel::Channel;
+
+use super::{Endpoint, EndpointID, MailboxDelegate, MailboxDelegateError, Message, Priority};
+use crate::GlobalRawMutex;
+use crate::intrusive_list;
+
+/// Message recorded by a [`Loopback`]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Received<T> {
+    /// where this message came from
+    pub from: EndpointID,
+
+    /// where this message was going
+    pub to: EndpointID,
+
+    /// delivery priority
+    pub priority: Priority,
+
+    /// copy of the message contents
+    pub data: T,
+}
+
+/// Endpoint that queues a copy of every message of type `T` it receives
+///
+/// High and normal priority messages are queued separately, with queued high priority messages returned first.
+/// Messages of any other type are rejected with [`MailboxDelegateError::MessageNotFound`], messages received while
+/// the queue for their priority is full are rejected with [`MailboxDelegateError::BufferFull`].
+pub struct Loopback<T: Any + Clone + Send + Sync, const N: usize> {
+    /// comms endpoint
+    pub tp: Endpoint,
+    queue: Channel<GlobalRawMutex, Received<T>, N>,
+    high_priority_queue: Channel<GlobalRawMutex, Received<T>, N>,
+}
+
+impl<T: Any + Clone + Send + Sync, const N: usize> Loopback<T, N> {
+    /// Create a new loopback endpoint with the given ID
+    pub const fn new(id: EndpointID) -> Self {
+        Self {
+            tp: Endpoint::uninit(id),
+            queue: Channel::new(),
+            high_priority_queue: Channel::new(),
+        }
+    }
+
+    /// Register this loopback with the comms system
+    pub async fn register(&'static self) -> Result<(), intrusive_list::Error> {
+        super::register_endpoint(self, &self.tp).await
+    }
+
+    /// Wait for the next recorded message
+    pub async fn wait_message(&self) -> Received<T> {
+        // select polls in order, so a queued high priority message always wins
+        match select(self.high_priority_queue.receive(), self.queue.receive()).await {
+            Either::First(message) | Either::Second(message) => message,
+        }
+    }
+
+    /// Get the next recorded message if one is queued
+    pub fn try_message(&self) -> Option<Received<T>> {
+        self.high_priority_queue
+            .try_receive()
+            .or_else(|_| self.queue.try_receive())
+            .ok()
+    }
+}
+
+impl<T: Any + Clone + Send + Sync, const N: usize> MailboxDelegate for Loopback<T, N> {
+    fn receive(&self, message: &Message) -> Result<(), MailboxDelegateError> {
+        let data = message.data.get::<T>().ok_or(MailboxDelegateError::MessageNotFound)?;
+        let queue = match message.priority {
+            Priority::High => &self.high_priority_queue,
+            Priority::Normal => &self.queue,
+        };
+        queue
+            .try_send(Received {
+                from: message.from,
+                to: message.to,
+                priority: message.priority,
+                data: data.clone(),
+            })
+            .map_err(|_| MailboxDelegateError::BufferFull)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use embassy_futures::join::join;
+
+    use super::*;
+    use crate::comms::{self, Internal};
+    use crate::{AtomicUsize, Ordering};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Ping(u32);
+
+    /// Service that counts the messages it receives
+    struct CountingService {
+        tp: Endpoint,
+        count: AtomicUsize,
+    }
+
+    impl MailboxDelegate for CountingService {
+        fn receive(&self, _message: &Message) -> Result<(), MailboxDelegateError> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    static SERVICE_A: CountingService = CountingService {
+        tp: Endpoint::uninit(EndpointID::Internal(Internal::Oem(1))),
+        count: AtomicUsize::new(0),
+    };
+    static SERVICE_B: CountingService = CountingService {
+        tp: Endpoint::uninit(EndpointID::Internal(Internal::Oem(2))),
+        count: AtomicUsize::new(0),
+    };
+    static LOOPBACK: Loopback<Ping, 2> = Loopback::new(EndpointID::Internal(Internal::Oem(3)));
+    static DEREGISTERED: Loopback<Ping, 2> = Loopback::new(EndpointID::Internal(Internal::Oem(4)));
+    static PRIORITIZED: Loopback<Ping, 4> = Loopback::new(EndpointID::Internal(Internal::Oem(5)));
+    static RETRIED: Loopback<Ping, 2> = Loopback::new(EndpointID::Internal(Internal::Oem(7)));
+
+    #[tokio::test]
+    async fn test_loopback() {
+        crate::init().await;
+        comms::register_endpoint(&SERVICE_A, &SERVICE_A.tp).await.unwrap();
+        comms::register_endpoint(&SERVICE_B, &SERVICE_B.tp).await.unwrap();
+        LOOPBACK.register().await.unwrap();
+
+        // Messages routed to the loopback are recorded
+        SERVICE_A.tp.send(LOOPBACK.tp.get_id(), &Ping(7)).await.unwrap();
+        assert_eq!(
+            LOOPBACK.wait_message().await,
+            Received {
+                from: SERVICE_A.tp.get_id(),
+                to: LOOPBACK.tp.get_id(),
+                priority: Priority::Normal,
+                data: Ping(7),
+            }
+        );
+
+        // Messages routed elsewhere and messages of other types are not
+        SERVICE_A.tp.send(SERVICE_B.tp.get_id(), &Ping(8)).await.unwrap();
+        SERVICE_B.tp.send(LOOPBACK.tp.get_id(), &0u8).await.unwrap();
+        assert_eq!(SERVICE_B.count.load(Ordering::SeqCst), 1);
+        assert_eq!(SERVICE_A.count.load(Ordering::SeqCst), 0);
+        assert!(LOOPBACK.try_message().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_deregister() {
+        crate::init().await;
+        DEREGISTERED.register().await.unwrap();
+
+        let from = SERVICE_A.tp.get_id();
+        let to = DEREGISTERED.tp.get_id();
+        comms::send(from, to, &Ping(1)).await.unwrap();
+        assert_eq!(DEREGISTERED.wait_message().await.data, Ping(1));
+
+        // Messages are no longer delivered once deregistered
+        comms::deregister_endpoint(&DEREGISTERED.tp).await.unwrap();
+        comms::send(from, to, &Ping(2)).await.unwrap();
+        assert!(DEREGISTERED.try_message().is_none());
+        assert!(matches!(
+            comms::deregister_endpoint(&DEREGISTERED.tp).await,
+            Err(intrusive_list::Error::NodeNotInList)
+        ));
+
+        // And are delivered again after registering again
+        DEREGISTERED.register().await.unwrap();
+        comms::send(from, to, &Ping(3)).await.unwrap();
+        assert_eq!(DEREGISTERED.wait_message().await.data, Ping(3));
+    }
+
+    #[tokio::test]
+    async fn test_register_retry() {
+        crate::init().await;
+        RETRIED.register().await.unwrap();
+
+        // The first attempt fails since the node is still registered, the retry succeeds once it has been removed
+        let delays = AtomicUsize::new(0);
+        let delay = async || {
+            delays.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        };
+        let (registered, deregistered) = join(
+            comms::register_endpoint_retry(&RETRIED, &RETRIED.tp, 3, delay),
+            comms::deregister_endpoint(&RETRIED.tp),
+        )
+        .await;
+        deregistered.unwrap();
+        registered.unwrap();
+        assert_eq!(delays.load(Ordering::SeqCst), 1);
+
+        let id = RETRIED.tp.get_id();
+        comms::send(id, id, &Ping(1)).await.unwrap();
+        assert_eq!(RETRIED.wait_message().await.data, Ping(1));
+
+        // The last error is returned once every attempt has failed
+        delays.store(0, Ordering::SeqCst);
+        let delay = async || {
+            delays.fetch_add(1, Ordering::SeqCst);
+        };
+        assert!(matches!(
+            comms::register_endpoint_retry(&RETRIED, &RETRIED.tp, 2, delay).await,
+            Err(intrusive_list::Error::NodeAlreadyInList)
+        ));
+        assert_eq!(delays.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_priority() {
+        crate::init().await;
+        PRIORITIZED.register().await.unwrap();
+
+        let from = SERVICE_A.tp.get_id();
+        let to = PRIORITIZED.tp.get_id();
+        comms::send(from, to, &Ping(1)).await.unwrap();
+        comms::send(from, to, &Ping(2)).await.unwrap();
+        comms::send_with_priority(from, to, Priority::High, &Ping(3))
+            .await
+            .unwrap();
+        comms::send(from, to, &Ping(4)).await.unwrap();
+
+        // The high priority message jumps the queue, normal messages keep their order
+        let message = PRIORITIZED.wait_message().await;
+        assert_eq!(message.data, Ping(3));
+        assert_eq!(message.priority, Priority::High);
+        for expected in [1, 2, 4] {
+            let message = PRIORITIZED.wait_message().await;
+            assert_eq!(message.data, Ping(expected));
+            assert_eq!(message.priority, Priority::Normal);
+        }
+        assert!(PRIORITIZED.try_message().is_none());
+    }
+}
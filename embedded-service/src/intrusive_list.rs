@@ -1,4 +1,4 @@
-//! A static lifetime'd intrusive linked list, construction only (never remove/delete)
+//! A static lifetime'd intrusive linked list, nodes are never deallocated but may be removed and pushed again
 
 // Any type used for dynamic type coercion
 pub use core::any::Any;
@@ -10,27 +10,41 @@ use crate::SyncCell;
 pub enum Error {
     /// cannot push a node to any list if it's already in one
     NodeAlreadyInList,
+
+    /// cannot remove a node from a list it isn't in
+    NodeNotInList,
 }
 
 /// override Result type for shorthand `-> Result<T>`
 pub type Result<T> = core::result::Result<T, Error>;
 
 /// Embedded node that "intrudes" on a structure
-#[derive(Copy, Clone, Debug)]
+///
+/// References to nodes are handed out while they are in a list, so every field that changes on push or removal is
+/// a cell.
 pub struct IntrusiveNode {
     /// offset from &self to struct data. Typically := sizeof(IntrusiveNode)
-    address_of_data: &'static dyn Any,
+    address_of_data: SyncCell<&'static dyn Any>,
 
     /// unsafe iterator type
-    next: Option<&'static IntrusiveNode>,
+    next: SyncCell<Option<&'static IntrusiveNode>>,
 
     /// valid address flag: used to ensure proper initialization sequencing over address_of_data
-    valid: bool,
+    valid: SyncCell<bool>,
+}
+
+// Not derived: the cells don't implement Debug
+impl core::fmt::Debug for IntrusiveNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("IntrusiveNode")
+            .field("valid", &self.valid.get())
+            .finish_non_exhaustive()
+    }
 }
 
 /// node type for list allocation. Embed this in the "list wrapper" object, and init with Node::uninit()
 pub struct Node {
-    inner: SyncCell<IntrusiveNode>,
+    inner: IntrusiveNode,
 }
 
 struct Invalid {}
@@ -38,17 +52,14 @@ struct Invalid {}
 impl Node {
     const INVALID: Invalid = Invalid {};
 
-    /// shorthand constant for no elements in list
-    pub const EMPTY: IntrusiveNode = IntrusiveNode {
-        address_of_data: &Node::INVALID,
-        next: None,
-        valid: false,
-    };
-
     /// construct an uninitialized node in place
     pub const fn uninit() -> Node {
         Node {
-            inner: SyncCell::new(Node::EMPTY),
+            inner: IntrusiveNode {
+                address_of_data: SyncCell::new(&Node::INVALID),
+                next: SyncCell::new(None),
+                valid: SyncCell::new(false),
+            },
         }
     }
 }
@@ -66,19 +77,10 @@ pub struct IntrusiveList {
 }
 
 impl IntrusiveNode {
-    /// generate an empty node for use within whatever type T
-    fn new<T: NodeContainer>(this_ref: &'static T) -> IntrusiveNode {
-        IntrusiveNode {
-            address_of_data: this_ref,
-            next: None,
-            valid: true,
-        }
-    }
-
     /// retrieve the underlying dynamic type information (vtable)
     pub fn data<T: NodeContainer>(&self) -> Option<&T> {
-        if self.valid {
-            self.address_of_data.downcast_ref()
+        if self.valid.get() {
+            self.address_of_data.get().downcast_ref()
         } else {
             None
         }
@@ -99,37 +101,59 @@ impl IntrusiveList {
         }
     }
 
-    /// only allow pushing to the head of the list
-    fn push_front(&self, node: &'static mut IntrusiveNode) {
+    /// generic over T: NodeContainer for list.push() proper node construction
+    pub fn push<T: NodeContainer>(&self, object: &'static T) -> Result<()> {
+        let node = &object.get_node().inner;
+
         // critical section in case of multi-threaded implementation:
         critical_section::with(|_cs| {
-            if let Some(old_head) = self.head.get() {
-                node.next = Some(old_head);
+            // check if node is in the list already. Valid flag will only be set if
+            // the element has been constructed and inserted into a linked list, so
+            // this check covers both same list and other list conditions.
+            if node.valid.get() {
+                return Err(Error::NodeAlreadyInList);
             }
 
+            // since this API is private to this module, this is the only place where
+            // a node can be marked as valid.
+            node.address_of_data.set(object);
+            node.next.set(self.head.get());
+            node.valid.set(true);
             self.head.set(Some(node));
-        });
+            Ok(())
+        })
     }
 
-    /// generic over T: NodeContainer for list.push() proper node construction
-    pub fn push<T: NodeContainer>(&self, object: &'static T) -> Result<()> {
-        // check if node is in the list already. Valid flag will only be set if
-        // the element has been constructed and inserted into a linked list, so
-        // this check covers both same list and other list conditions.
-        if object.get_node().inner.get().valid {
-            return Err(Error::NodeAlreadyInList);
-        }
+    /// remove a node previously pushed to this list, after which it may be pushed again
+    ///
+    /// An iterator currently positioned on the removed node continues past it. If the node is pushed again before
+    /// the iterator advances, the iterator continues from the head of the list the node was pushed to.
+    pub fn remove<T: NodeContainer>(&self, object: &'static T) -> Result<()> {
+        let target = &object.get_node().inner;
 
-        // since this API is private to this module, this is the only place where
-        // a node can be marked as valid.
-        let node = IntrusiveNode::new(object);
-        object.get_node().inner.set(node);
+        // critical section in case of multi-threaded implementation:
+        critical_section::with(|_cs| {
+            let mut prev: Option<&'static IntrusiveNode> = None;
+            let mut current = self.head.get();
+
+            while let Some(node) = current {
+                if core::ptr::eq(node, target) {
+                    match prev {
+                        Some(prev) => prev.next.set(node.next.get()),
+                        None => self.head.set(node.next.get()),
+                    }
+
+                    // keep the link so an iterator currently positioned on this node continues past it
+                    target.valid.set(false);
+                    return Ok(());
+                }
+
+                prev = current;
+                current = node.next.get();
+            }
 
-        self.push_front(
-            // SAFETY: known safe operation due to valid flag and static lifetime
-            unsafe { &mut *object.get_node().inner.as_ptr() },
-        );
-        Ok(())
+            Err(Error::NodeNotInList)
+        })
     }
 
     /// Iterate over the list as if it were items of type `T`, skipping any nodes that are of a different type.
@@ -161,7 +185,7 @@ impl Iterator for IntrusiveIterator {
         let mut iter = None;
 
         if let Some(current) = self.current {
-            self.current = current.next;
+            self.current = current.next.get();
             iter = Some(current);
         }
 
@@ -361,8 +385,7 @@ mod test {
         let empty_node = EMPTY_NODE.get_or_init(|| RegistrationOnlyOneInstance {});
 
         // accessing private .inner. here just for test validation. Not a consumer facing scenario
-        // SAFETY: this is not safe. Don't do this. Only here for test completeness
-        let as_element: Option<&RegistrationA> = unsafe { &*empty_node.get_node().inner.as_ptr() }.data();
+        let as_element: Option<&RegistrationA> = empty_node.get_node().inner.data();
         assert!(as_element.is_none());
     }
 
@@ -407,6 +430,65 @@ mod test {
         assert!(list2.push(empty_node_unpushable).is_err());
     }
 
+    #[test]
+    fn test_remove() {
+        static A: [OnceLock<RegistrationA>; 3] = [const { OnceLock::new() }; 3];
+        let [first, second, third] = A.each_ref().map(|a| a.get_or_init(RegistrationA::new));
+        let list = IntrusiveList::new();
+        let list2 = IntrusiveList::new();
+
+        assert!(list.push(first).is_ok());
+        assert!(list.push(second).is_ok());
+        assert!(list.push(third).is_ok());
+
+        // remove from the middle, then the head
+        assert!(list.remove(second).is_ok());
+        assert_eq!(2, list.into_iter().count());
+        assert!(list.remove(third).is_ok());
+        assert_eq!(1, list.into_iter().count());
+        assert!(list.into_iter().all(|node| node.data::<RegistrationA>().is_some()));
+
+        // nodes not in the list can't be removed
+        assert!(matches!(list.remove(second), Err(Error::NodeNotInList)));
+        assert!(matches!(list2.remove(first), Err(Error::NodeNotInList)));
+
+        // removed nodes can be pushed again, to any list
+        assert!(list2.push(second).is_ok());
+        assert!(list.push(third).is_ok());
+        assert_eq!(2, list.into_iter().count());
+        assert_eq!(1, list2.into_iter().count());
+
+        // remove the last remaining node
+        assert!(list.remove(first).is_ok());
+        assert!(list.remove(third).is_ok());
+        assert_eq!(0, list.into_iter().count());
+    }
+
+    #[test]
+    fn test_remove_while_iterating() {
+        static A: [OnceLock<RegistrationA>; 3] = [const { OnceLock::new() }; 3];
+        let [first, second, third] = A.each_ref().map(|a| a.get_or_init(RegistrationA::new));
+        let list = IntrusiveList::new();
+
+        assert!(list.push(first).is_ok());
+        assert!(list.push(second).is_ok());
+        assert!(list.push(third).is_ok());
+
+        // remove the node the iterator is positioned on, it no longer has data and iteration continues past it
+        let mut iter = list.into_iter();
+        assert!(iter.next().is_some_and(|node| core::ptr::eq(node, &third.node.inner)));
+        assert!(list.remove(second).is_ok());
+        assert!(
+            iter.next()
+                .is_some_and(|node| core::ptr::eq(node, &second.node.inner) && node.data::<RegistrationA>().is_none())
+        );
+        assert!(iter.next().is_some_and(|node| core::ptr::eq(node, &first.node.inner)));
+        assert!(iter.next().is_none());
+
+        // new iterators skip the removed node
+        assert_eq!(2, list.into_iter().count());
+    }
+
     #[test]
     fn test_empty_list() {
         let list = IntrusiveList::new();
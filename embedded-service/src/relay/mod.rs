@@ -30,6 +30,50 @@ pub trait SerializableMessage: Sized {
     fn deserialize(discriminant: u16, buffer: &[u8]) -> Result<Self, MessageSerializationError>;
 }
 
+/// Reads `N` bytes from `buffer` starting at `offset`.
+///
+/// Intended for [`SerializableMessage::deserialize`] implementations so a short buffer produces
+/// [`MessageSerializationError::BufferTooSmall`] rather than a panic.
+pub fn read_bytes<const N: usize>(buffer: &[u8], offset: usize) -> Result<[u8; N], MessageSerializationError> {
+    offset
+        .checked_add(N)
+        .and_then(|end| buffer.get(offset..end))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(MessageSerializationError::BufferTooSmall)
+}
+
+/// Empty message, e.g. the success type of requests that don't produce any data
+impl SerializableMessage for () {
+    fn serialize(self, _buffer: &mut [u8]) -> Result<usize, MessageSerializationError> {
+        Ok(0)
+    }
+
+    fn discriminant(&self) -> u16 {
+        0
+    }
+
+    fn deserialize(_discriminant: u16, _buffer: &[u8]) -> Result<Self, MessageSerializationError> {
+        Ok(())
+    }
+}
+
+/// Message that can never be constructed, e.g. the error type of requests that cannot fail
+impl SerializableMessage for core::convert::Infallible {
+    fn serialize(self, _buffer: &mut [u8]) -> Result<usize, MessageSerializationError> {
+        match self {}
+    }
+
+    fn discriminant(&self) -> u16 {
+        match *self {}
+    }
+
+    fn deserialize(_discriminant: u16, _buffer: &[u8]) -> Result<Self, MessageSerializationError> {
+        Err(MessageSerializationError::InvalidPayload(
+            "Infallible message cannot be deserialized",
+        ))
+    }
+}
+
 // Prevent other types from implementing SerializableResult - they should instead use SerializableMessage on a Response type and an Error type
 #[doc(hidden)]
 mod private {
@@ -118,6 +162,12 @@ pub mod mctp {
         type ResultType: super::SerializableResult;
     }
 
+    /// Result type for one-way services whose requests never produce a meaningful result, e.g. a fire-and-forget log service.
+    ///
+    /// Use this as [`RelayServiceHandlerTypes::ResultType`] for such a service. The relay still acknowledges each request
+    /// with an empty, successful result.
+    pub type OneWayResult = Result<(), core::convert::Infallible>;
+
     /// Trait for a service that can be relayed over an external bus (e.g. battery service, thermal service, time-alarm service)
     ///
     pub trait RelayServiceHandler: RelayServiceHandlerTypes {
@@ -188,7 +238,7 @@ pub mod mctp {
     ///                         This can be arbitrary.
     ///   service_id:           A unique u8 that addresses that service on the EC.
     ///   service_handler_type: A type that implements the RelayServiceHandler trait, which will be used to process messages
-    ///                         for this service.
+    ///                         for this service. One-way services should use [`OneWayResult`] as their result type.
     ///
     /// Example usage:
     ///
@@ -0,0 +1,153 @@
+#![allow(clippy::unwrap_used)]
+//! Tests for relay message handling, e.g. wiring a one-way service handler into a relay alongside a request/response handler
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use embedded_services::_macro_internal::mctp_rs::MctpMessageTrait;
+use embedded_services::_macro_internal::mctp_rs::smbus_espi::SmbusEspiMedium;
+use embedded_services::relay::mctp::{
+    OneWayResult, RelayHandler, RelayResponse, RelayServiceHandler, RelayServiceHandlerTypes,
+    impl_odp_mctp_relay_handler,
+};
+use embedded_services::relay::{MessageSerializationError, SerializableMessage, read_bytes};
+
+/// Single byte message used by both test services
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ByteMessage(u8);
+
+impl SerializableMessage for ByteMessage {
+    fn serialize(self, buffer: &mut [u8]) -> Result<usize, MessageSerializationError> {
+        *buffer.first_mut().ok_or(MessageSerializationError::BufferTooSmall)? = self.0;
+        Ok(1)
+    }
+
+    fn discriminant(&self) -> u16 {
+        1
+    }
+
+    fn deserialize(discriminant: u16, buffer: &[u8]) -> Result<Self, MessageSerializationError> {
+        match discriminant {
+            1 => Ok(Self(
+                *buffer
+                    .first()
+                    .ok_or(MessageSerializationError::InvalidPayload("Missing byte"))?,
+            )),
+            other => Err(MessageSerializationError::UnknownMessageDiscriminant(other)),
+        }
+    }
+}
+
+/// Message with a fixed-size payload of two little-endian words
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WordsMessage(u16, u32);
+
+impl SerializableMessage for WordsMessage {
+    fn serialize(self, _buffer: &mut [u8]) -> Result<usize, MessageSerializationError> {
+        Err(MessageSerializationError::Other("Unimplemented"))
+    }
+
+    fn discriminant(&self) -> u16 {
+        1
+    }
+
+    fn deserialize(discriminant: u16, buffer: &[u8]) -> Result<Self, MessageSerializationError> {
+        match discriminant {
+            1 => Ok(Self(
+                u16::from_le_bytes(read_bytes(buffer, 0)?),
+                u32::from_le_bytes(read_bytes(buffer, 2)?),
+            )),
+            other => Err(MessageSerializationError::UnknownMessageDiscriminant(other)),
+        }
+    }
+}
+
+/// Request/response service that echoes the request back
+pub struct EchoHandler;
+
+impl RelayServiceHandlerTypes for EchoHandler {
+    type RequestType = ByteMessage;
+    type ResultType = Result<ByteMessage, ByteMessage>;
+}
+
+impl RelayServiceHandler for EchoHandler {
+    async fn process_request(&self, request: Self::RequestType) -> Self::ResultType {
+        Ok(request)
+    }
+}
+
+/// Last byte received by [`LogHandler`]
+static LOGGED: AtomicU8 = AtomicU8::new(0);
+
+/// One-way service that records the request and never produces a result
+pub struct LogHandler;
+
+impl RelayServiceHandlerTypes for LogHandler {
+    type RequestType = ByteMessage;
+    type ResultType = OneWayResult;
+}
+
+impl RelayServiceHandler for LogHandler {
+    async fn process_request(&self, request: Self::RequestType) -> Self::ResultType {
+        LOGGED.store(request.0, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl_odp_mctp_relay_handler!(
+    TestRelay;
+    Echo, 0x1, crate::EchoHandler;
+    Log,  0x2, crate::LogHandler;
+);
+
+use _odp_impl_test_relay::{HostRequest, HostResult, OdpMessageType, OdpService};
+
+#[tokio::test]
+async fn test_one_way_handler() {
+    let relay = TestRelay::new(EchoHandler, LogHandler);
+    let mut buffer = [0xff; 4];
+
+    // Request/response handler works as usual alongside the one-way handler
+    let result = relay.process_request(HostRequest::Echo(ByteMessage(7))).await;
+    let header = result.create_header(&OdpService::Echo);
+    assert!(header.message_type == OdpMessageType::Result { is_error: false });
+    assert_eq!(header.message_id, 1);
+    assert_eq!(
+        <HostResult as MctpMessageTrait<'_>>::serialize::<SmbusEspiMedium>(result, &mut buffer).unwrap(),
+        1
+    );
+    assert_eq!(buffer.first(), Some(&7));
+
+    // One-way request is processed and acknowledged with an empty successful result
+    let result = relay.process_request(HostRequest::Log(ByteMessage(5))).await;
+    assert_eq!(LOGGED.load(Ordering::Relaxed), 5);
+    assert!(matches!(result, HostResult::Log(Ok(()))));
+    let header = result.create_header(&OdpService::Log);
+    assert!(header.message_type == OdpMessageType::Result { is_error: false });
+    assert_eq!(header.message_id, 0);
+    assert_eq!(
+        <HostResult as MctpMessageTrait<'_>>::serialize::<SmbusEspiMedium>(result, &mut buffer).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn test_deserialize_truncated_buffer() {
+    let buffer = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    assert_eq!(
+        WordsMessage::deserialize(1, &buffer).unwrap(),
+        WordsMessage(0x0201, 0x0605_0403)
+    );
+
+    // Every truncation is reported as an error instead of panicking
+    for len in 0..buffer.len() {
+        assert!(matches!(
+            WordsMessage::deserialize(1, buffer.get(..len).unwrap()),
+            Err(MessageSerializationError::BufferTooSmall)
+        ));
+    }
+
+    // Offsets that overflow are also too small
+    assert!(matches!(
+        read_bytes::<4>(&buffer, usize::MAX),
+        Err(MessageSerializationError::BufferTooSmall)
+    ));
+}
@@ -6,7 +6,6 @@ use embassy_sync::channel::Channel;
 use embassy_sync::mutex::Mutex;
 use embedded_services::{GlobalRawMutex, error, info, trace};
 use mctp_rs::smbus_espi::SmbusEspiMedium;
-use mctp_rs::smbus_espi::SmbusEspiReplyContext;
 
 const HOST_TX_QUEUE_SIZE: usize = 5;
 
@@ -80,12 +79,22 @@ impl<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> Service<'h
 pub struct InitParams<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> {
     pub espi: espi::Espi<'hw>,
     pub relay_handler: RelayHandler,
+    /// Whether MCTP frames exchanged with the host carry a trailing SMBus PEC byte
+    pub pec_enabled: bool,
+    /// Whether the PEC of MCTP frames received from the host is checked, frames with a mismatched PEC are rejected
+    ///
+    /// Only used if `pec_enabled` is set.
+    pub verify_pec: bool,
+    /// MCTP endpoint ID assigned to the host, used as the destination of results sent to the host
+    pub host_endpoint_id: mctp_rs::EndpointId,
 }
 
 struct ServiceInner<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> {
     espi: Mutex<GlobalRawMutex, espi::Espi<'hw>>,
     host_tx_queue: Channel<GlobalRawMutex, HostResultMessage<RelayHandler>, HOST_TX_QUEUE_SIZE>,
     relay_handler: RelayHandler,
+    medium: SmbusEspiMedium,
+    host_endpoint_id: mctp_rs::EndpointId,
 }
 
 impl<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> ServiceInner<'hw, RelayHandler> {
@@ -96,6 +105,12 @@ impl<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> ServiceInn
             espi: Mutex::new(init_params.espi),
             host_tx_queue: Channel::new(),
             relay_handler: init_params.relay_handler,
+            medium: if init_params.pec_enabled {
+                SmbusEspiMedium::new().with_pec_verification(init_params.verify_pec)
+            } else {
+                SmbusEspiMedium::without_pec()
+            },
+            host_endpoint_id: init_params.host_endpoint_id,
         }
     }
 
@@ -167,10 +182,8 @@ impl<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> ServiceInn
                     embedded_services::debug!("OOB message: {:02X}", &src_slice[0..]);
 
                     let mut assembly_buf = [0u8; ASSEMBLY_BUF_SIZE];
-                    let mut mctp_ctx = mctp_rs::MctpPacketContext::<SmbusEspiMedium>::new(
-                        SmbusEspiMedium,
-                        assembly_buf.as_mut_slice(),
-                    );
+                    let mut mctp_ctx =
+                        mctp_rs::MctpPacketContext::<SmbusEspiMedium>::new(self.medium, assembly_buf.as_mut_slice());
 
                     match mctp_ctx.deserialize_packet(src_slice) {
                         Ok(Some(message)) => {
@@ -265,22 +278,9 @@ impl<'hw, RelayHandler: embedded_services::relay::mctp::RelayHandler> ServiceInn
     ) -> Result<(), Error> {
         use embedded_services::relay::mctp::RelayResponse;
         let mut assembly_buf = [0u8; ASSEMBLY_BUF_SIZE];
-        let mut mctp_ctx =
-            mctp_rs::MctpPacketContext::new(mctp_rs::smbus_espi::SmbusEspiMedium, assembly_buf.as_mut_slice());
-
-        let reply_context: mctp_rs::MctpReplyContext<SmbusEspiMedium> = mctp_rs::MctpReplyContext {
-            source_endpoint_id: mctp_rs::EndpointId::Id(0x80),
-            destination_endpoint_id: mctp_rs::EndpointId::Id(result.handler_service_id.into()), // TODO We're currently using this incorrectly - it should be the bus address of the host. Revisit once we have assigned a bus address to the host.
-            packet_sequence_number: mctp_rs::MctpSequenceNumber::new(0),
-            message_tag: mctp_rs::MctpMessageTag::try_from(3).map_err(|e| {
-                error!("serialize_packet_from_subsystem: {:?}", e);
-                Error::Serialize
-            })?,
-            medium_context: SmbusEspiReplyContext {
-                destination_slave_address: 1,
-                source_slave_address: 0,
-            }, // Medium-specific context
-        };
+        let mut mctp_ctx = mctp_rs::MctpPacketContext::new(self.medium, assembly_buf.as_mut_slice());
+
+        let reply_context = crate::reply::host_reply_context(self.host_endpoint_id).ok_or(Error::Serialize)?;
 
         let header = result.message.create_header(&result.handler_service_id);
         let mut packet_state = mctp_ctx
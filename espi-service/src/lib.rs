@@ -22,5 +22,8 @@
 #[cfg(not(test))]
 mod espi_service;
 
+// Doesn't depend on embassy-imxrt, so it stays available to tests
+mod reply;
+
 #[cfg(not(test))]
 pub use espi_service::*;
@@ -0,0 +1,55 @@
+//! Reply context construction for results sent to the host.
+//!
+//! Kept separate from the eSPI service so it can be tested without depending on embassy-imxrt.
+use embedded_services::error;
+use mctp_rs::smbus_espi::{SmbusEspiMedium, SmbusEspiReplyContext};
+
+/// MCTP endpoint ID of the EC, used as the source of every result sent to the host
+const EC_ENDPOINT_ID: u8 = 0x80;
+
+/// Message tag used for every result sent to the host
+const HOST_REPLY_MESSAGE_TAG: u8 = 3;
+
+/// Returns the context for a result sent to the host at `host_endpoint_id`, or `None` if it couldn't be created
+pub(crate) fn host_reply_context(
+    host_endpoint_id: mctp_rs::EndpointId,
+) -> Option<mctp_rs::MctpReplyContext<SmbusEspiMedium>> {
+    let message_tag = mctp_rs::MctpMessageTag::try_from(HOST_REPLY_MESSAGE_TAG)
+        .inspect_err(|e| error!("host_reply_context: {:?}", e))
+        .ok()?;
+
+    Some(mctp_rs::MctpReplyContext {
+        source_endpoint_id: mctp_rs::EndpointId::Id(EC_ENDPOINT_ID),
+        destination_endpoint_id: host_endpoint_id,
+        packet_sequence_number: mctp_rs::MctpSequenceNumber::new(0),
+        message_tag,
+        medium_context: SmbusEspiReplyContext {
+            destination_slave_address: 1,
+            source_slave_address: 0,
+        }, // Medium-specific context
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Results must be addressed to the configured host endpoint, not the ID of the service that produced them
+    #[test]
+    fn test_reply_to_host_endpoint_id() {
+        const HOST_ENDPOINT_ID: u8 = 0x08;
+        const HANDLER_SERVICE_ID: u8 = 0x01;
+
+        let context = host_reply_context(mctp_rs::EndpointId::Id(HOST_ENDPOINT_ID)).unwrap();
+
+        assert_eq!(
+            context.destination_endpoint_id,
+            mctp_rs::EndpointId::Id(HOST_ENDPOINT_ID)
+        );
+        assert_ne!(
+            context.destination_endpoint_id,
+            mctp_rs::EndpointId::Id(HANDLER_SERVICE_ID)
+        );
+        assert_eq!(context.source_endpoint_id, mctp_rs::EndpointId::Id(EC_ENDPOINT_ID));
+    }
+}
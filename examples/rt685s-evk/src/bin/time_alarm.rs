@@ -44,6 +44,11 @@ async fn main(spawner: embassy_executor::Spawner) {
     })
     .expect("Failed to spawn time alarm service");
 
+    time_service
+        .register_endpoint()
+        .await
+        .expect("Failed to register time alarm service endpoint");
+
     use embedded_services::relay::mctp::impl_odp_mctp_relay_handler;
     impl_odp_mctp_relay_handler!(
         EspiRelayHandler;
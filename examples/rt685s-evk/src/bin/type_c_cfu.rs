@@ -28,16 +28,18 @@ use power_policy_service::psu::PsuEventReceivers;
 use power_policy_service::service::registration::ArrayRegistration;
 use static_cell::StaticCell;
 use tps6699x::odp::driver as tps6699x_drv;
+use type_c_interface::controller::ControllerId;
 use type_c_interface::port::event::PortEventBitfield;
 use type_c_service::controller::Port;
 use type_c_service::controller::event_receiver::{
     EventReceiver as PortEventReceiver, InterruptReceiver as _, PortEventSplitter,
 };
 use type_c_service::controller::macros::PortComponents;
+use type_c_service::controller::recovery::ControllerRecovery;
 use type_c_service::controller::state::SharedState as PortSharedState;
 use type_c_service::define_controller_port_static_cell_channel;
 use type_c_service::service::Service;
-use type_c_service::service::registration::PortData;
+use type_c_service::service::registration::{ControllerData, PortData};
 
 extern crate rt685s_evk_example;
 
@@ -105,8 +107,14 @@ type TypeCServiceEventReceiverType = type_c_service::service::event_receiver::Ar
 >;
 
 type TypeCServiceSenderType = NoopSender;
-type TypeCRegistrationType =
-    type_c_service::service::registration::ArrayRegistration<'static, PortType, PORT_COUNT, TypeCServiceSenderType, 1>;
+type TypeCRegistrationType = type_c_service::service::registration::ArrayRegistration<
+    'static,
+    PortType,
+    PORT_COUNT,
+    1,
+    TypeCServiceSenderType,
+    1,
+>;
 type TypeCServiceType = type_c_service::service::Service<'static, TypeCRegistrationType>;
 type PortEventReceiverType = PortEventReceiver<
     'static,
@@ -123,11 +131,13 @@ const CONTROLLER0_CFU_ID: ComponentId = 0x12;
 
 #[embassy_executor::task(pool_size = 2)]
 async fn port_task(mut event_receiver: PortEventReceiverType, port: &'static PortType) {
-    port.lock().await.sync_state().await.unwrap();
+    if let Err(e) = port.lock().await.sync_state_with_recovery().await {
+        error!("Error syncing port state: {:?}", e);
+    }
 
     loop {
         let event = event_receiver.wait_event().await;
-        let output = port.lock().await.process_event(event).await;
+        let output = port.lock().await.process_event_with_recovery(event).await;
         if let Err(e) = output {
             error!("Error processing event: {:?}", e);
         }
@@ -335,7 +345,7 @@ async fn main(spawner: Spawner) {
     let PortComponents {
         port: port0,
         power_policy_receiver: policy_receiver0,
-        event_receiver: event_receiver0,
+        event_receiver: mut event_receiver0,
         interrupt_sender: port0_interrupt_sender,
         type_c_receiver: type_c_receiver0,
     } = port0::create("PD0", LocalPortId(0), Default::default(), controller_mutex);
@@ -344,11 +354,19 @@ async fn main(spawner: Spawner) {
     let PortComponents {
         port: port1,
         power_policy_receiver: policy_receiver1,
-        event_receiver: event_receiver1,
+        event_receiver: mut event_receiver1,
         interrupt_sender: port1_interrupt_sender,
         type_c_receiver: type_c_receiver1,
     } = port1::create("PD1", LocalPortId(1), Default::default(), controller_mutex);
 
+    // Both ports are on the same controller and are re-synchronized together after a controller reset
+    static CONTROLLER_RECOVERY: StaticCell<ControllerRecovery<PORT_COUNT>> = StaticCell::new();
+    let controller_recovery = CONTROLLER_RECOVERY.init(ControllerRecovery::new(Default::default()));
+    port0.lock().await.register_recovery(controller_recovery);
+    event_receiver0.register_recovery(controller_recovery);
+    port1.lock().await.register_recovery(controller_recovery);
+    event_receiver1.register_recovery(controller_recovery);
+
     let port_event_splitter = PortEventSplitter::new([port0_interrupt_sender, port1_interrupt_sender]);
 
     // Create power policy service
@@ -388,6 +406,10 @@ async fn main(spawner: Spawner) {
                     local_port: Some(LocalPortId(1)),
                 },
             ],
+            controllers: [ControllerData {
+                id: ControllerId(0),
+                port_count: PORT_COUNT,
+            }],
             service_senders: [NoopSender],
         },
     )));
@@ -56,6 +56,8 @@ async fn run(spawner: Spawner) {
             config: ts::mock::fan::MockFan::config(),
             sensor_service,
             event_senders: &mut [],
+            profile_storage: None,
+            aggregation: ts::fan::Aggregation::Single,
         },
     ))
     .expect("Failed to spawn fan service");
@@ -13,6 +13,7 @@ pub enum FnCall {
     AbortFwUpdate,
     FinalizeFwUpdate,
     WriteFwContents(usize, Vec<u8>),
+    ValidateFwImage(usize),
 }
 
 pub struct Mock {
@@ -22,6 +23,8 @@ pub struct Mock {
     next_error: Option<Error>,
     /// Mock current FW version
     current_fw_version: u32,
+    /// Whether the written image passes validation
+    image_valid: bool,
     /// Human-readable name of the mock
     name: &'static str,
 }
@@ -33,6 +36,7 @@ impl Mock {
             fn_calls: VecDeque::new(),
             next_error: None,
             current_fw_version,
+            image_valid: true,
         }
     }
 
@@ -44,6 +48,11 @@ impl Mock {
     pub fn set_next_error(&mut self, error: Option<Error>) {
         self.next_error = error;
     }
+
+    /// Set whether the written image passes validation
+    pub fn set_image_valid(&mut self, valid: bool) {
+        self.image_valid = valid;
+    }
 }
 
 impl FwUpdate for Mock {
@@ -87,6 +96,19 @@ impl FwUpdate for Mock {
         }
         Ok(())
     }
+
+    async fn validate_fw_image(&mut self, size: usize) -> Result<(), Error> {
+        self.record_fn_call(FnCall::ValidateFwImage(size));
+        if let Some(error) = self.next_error.take() {
+            return Err(error);
+        }
+
+        if self.image_valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidContent)
+        }
+    }
 }
 
 impl Named for Mock {
@@ -141,6 +163,16 @@ mod test {
         assert_eq!(mock.fn_calls.pop_front(), Some(FnCall::WriteFwContents(0, data)));
     }
 
+    #[tokio::test]
+    async fn test_validate_fw_image() {
+        let mut mock = super::Mock::new("test", 1);
+        assert_eq!(mock.validate_fw_image(4).await, Ok(()));
+        assert_eq!(mock.fn_calls.pop_front(), Some(FnCall::ValidateFwImage(4)));
+
+        mock.set_image_valid(false);
+        assert_eq!(mock.validate_fw_image(4).await, Err(Error::InvalidContent));
+    }
+
     #[tokio::test]
     async fn test_set_next_error() {
         let mut mock = super::Mock::new("test", 1);
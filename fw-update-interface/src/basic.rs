@@ -40,4 +40,12 @@ pub trait FwUpdate: Named {
     fn finalize_fw_update(&mut self) -> impl Future<Output = Result<(), Error>>;
     /// Write firmware update contents
     fn write_fw_contents(&mut self, offset: usize, data: &[u8]) -> impl Future<Output = Result<(), Error>>;
+    /// Validate the written firmware image before the update is finalized
+    ///
+    /// `size` is the total size of the written image in bytes. Implementations should read back
+    /// the written contents to perform any CRC or signature checks and return [`Error::InvalidContent`]
+    /// if the image is bad, in which case the update is not finalized.
+    fn validate_fw_image(&mut self, _size: usize) -> impl Future<Output = Result<(), Error>> {
+        async { Ok(()) }
+    }
 }
@@ -12,7 +12,59 @@ use crate::{
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct SmbusEspiMedium;
+pub struct SmbusEspiMedium {
+    /// Whether frames carry a trailing PEC byte
+    pec: bool,
+    /// Whether the PEC of received frames is checked
+    verify_pec: bool,
+}
+
+impl SmbusEspiMedium {
+    /// Create a medium whose frames carry a trailing PEC byte
+    pub const fn new() -> Self {
+        Self {
+            pec: true,
+            verify_pec: false,
+        }
+    }
+
+    /// Create a medium whose frames don't carry a trailing PEC byte
+    pub const fn without_pec() -> Self {
+        Self {
+            pec: false,
+            verify_pec: false,
+        }
+    }
+
+    /// Set whether the PEC of received frames is checked, frames with a mismatched PEC are rejected
+    ///
+    /// Has no effect on a medium without PEC.
+    pub const fn with_pec_verification(mut self, verify: bool) -> Self {
+        self.verify_pec = verify;
+        self
+    }
+
+    /// Returns true if frames carry a trailing PEC byte
+    pub const fn pec_enabled(&self) -> bool {
+        self.pec
+    }
+
+    /// Returns true if the PEC of received frames is checked
+    pub const fn pec_verification_enabled(&self) -> bool {
+        self.pec && self.verify_pec
+    }
+
+    /// Number of bytes used by the PEC at the end of a frame
+    const fn pec_len(&self) -> usize {
+        if self.pec { 1 } else { 0 }
+    }
+}
+
+impl Default for SmbusEspiMedium {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -41,17 +93,26 @@ impl MctpMedium for SmbusEspiMedium {
                 .try_into()
                 .map_err(|_| MctpPacketError::MediumError("Packet too short to parse smbus header"))?,
         );
+        let frame_bytes = packet;
         // strip off the smbus header
         let packet = &packet[4..];
         let header = SmbusEspiMediumHeader::try_from(header_value)
             .map_err(|_| MctpPacketError::MediumError("Invalid smbus header"))?;
-        if header.byte_count as usize + 1 > packet.len() {
-            return Err(MctpPacketError::MediumError(
-                "Packet too short to parse smbus body and PEC",
-            ));
+        if header.byte_count as usize + self.pec_len() > packet.len() {
+            return Err(MctpPacketError::MediumError(if self.pec {
+                "Packet too short to parse smbus body and PEC"
+            } else {
+                "Packet too short to parse smbus body"
+            }));
+        }
+        let pec = self.pec.then(|| packet[header.byte_count as usize]);
+        if self.verify_pec
+            && let Some(pec) = pec
+            && pec != smbus_pec::pec(&frame_bytes[..4 + header.byte_count as usize])
+        {
+            return Err(MctpPacketError::MediumError("PEC mismatch"));
         }
-        let pec = packet[header.byte_count as usize];
-        // strip off the PEC byte; the inner stuffed region is the body bytes
+        // strip off the PEC byte if present; the inner stuffed region is the body bytes
         let inner = &packet[..header.byte_count as usize];
         Ok((SmbusEspiMediumFrame { header, pec }, EncodingDecoder::new(inner)))
     }
@@ -65,16 +126,17 @@ impl MctpMedium for SmbusEspiMedium {
     where
         F: for<'a> FnOnce(&mut EncodingEncoder<'a, Self::Encoding>) -> MctpPacketResult<(), Self>,
     {
-        // Reserve space for header (4 bytes) and PEC (1 byte)
-        if buffer.len() < 5 {
+        // Reserve space for header (4 bytes) and PEC (1 byte, if enabled)
+        let pec_len = self.pec_len();
+        if buffer.len() < 4 + pec_len {
             return Err(MctpPacketError::MediumError("Buffer too small for smbus frame"));
         }
         let buffer_len = buffer.len();
 
         // Write the body first via an encoder over the body region (reserve
-        // 4 leading header bytes and 1 trailing PEC byte).
+        // 4 leading header bytes and the trailing PEC byte).
         let body_wire_len = {
-            let body_buf = &mut buffer[4..buffer_len - 1];
+            let body_buf = &mut buffer[4..buffer_len - pec_len];
             let mut encoder = EncodingEncoder::<Self::Encoding>::new(body_buf);
             message_writer(&mut encoder)?;
             encoder.wire_position()
@@ -95,11 +157,13 @@ impl MctpMedium for SmbusEspiMedium {
         buffer[0..4].copy_from_slice(&header_value.to_be_bytes());
 
         // with the header written, compute the PEC byte
-        let pec_value = smbus_pec::pec(&buffer[0..4 + body_wire_len]);
-        buffer[4 + body_wire_len] = pec_value;
+        if self.pec {
+            let pec_value = smbus_pec::pec(&buffer[0..4 + body_wire_len]);
+            buffer[4 + body_wire_len] = pec_value;
+        }
 
-        // add 4 for frame header, add 1 for PEC byte
-        Ok(&buffer[0..4 + body_wire_len + 1])
+        // add 4 for frame header, add PEC byte if enabled
+        Ok(&buffer[0..4 + body_wire_len + pec_len])
     }
 
     // TODO - this is a guess, need to find the actual value from spec
@@ -109,13 +173,13 @@ impl MctpMedium for SmbusEspiMedium {
 
     fn frame_complete(&self, buf: &[u8]) -> MctpPacketResult<Option<usize>, Self> {
         // SmbusEspi framing: [dst_addr | src_addr | byte_count | cmd_code]
-        //                    [ body bytes (byte_count) ] [ PEC byte ]
-        // Total: 4 + byte_count + 1 = 5 + byte_count
+        //                    [ body bytes (byte_count) ] [ PEC byte (optional) ]
+        // Total: 4 + byte_count + 1 = 5 + byte_count, or 4 + byte_count without PEC
         if buf.len() < 4 {
             return Ok(None);
         }
         let byte_count = buf[2] as usize;
-        let total = 4 + byte_count + 1;
+        let total = 4 + byte_count + self.pec_len();
         if buf.len() < total { Ok(None) } else { Ok(Some(total)) }
     }
 }
@@ -165,7 +229,7 @@ bit_register! {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SmbusEspiMediumFrame {
     header: SmbusEspiMediumHeader,
-    pec: u8,
+    pec: Option<u8>,
 }
 
 impl SmbusEspiReplyContext {
@@ -207,7 +271,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_valid_packet() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Create a valid SMBus packet with little-endian header
         // destination_slave_address: 0x20, source_slave_address: 0x10, command: 0x0F, byte_count: 4
@@ -240,13 +304,51 @@ mod tests {
         assert_eq!(frame.header.source_slave_address, 0x10);
         assert_eq!(frame.header.command_code, SmbusCommandCode::Mctp);
         assert_eq!(frame.header.byte_count, 4);
-        assert_eq!(frame.pec, pec);
+        assert_eq!(frame.pec, Some(pec));
         assert_eq!(body, payload);
     }
 
+    #[test]
+    fn test_deserialize_pec_verification() {
+        let medium = SmbusEspiMedium::new().with_pec_verification(true);
+        assert!(medium.pec_verification_enabled());
+
+        let header = SmbusEspiMediumHeader {
+            destination_slave_address: 0x20,
+            source_slave_address: 0x10,
+            command_code: SmbusCommandCode::Mctp,
+            byte_count: 4,
+            ..Default::default()
+        };
+        let header_value: u32 = header.try_into().unwrap();
+
+        let payload = [0xAA, 0xBB, 0xCC, 0xDD];
+        let mut packet = [0u8; 9];
+        packet[0..4].copy_from_slice(&header_value.to_be_bytes());
+        packet[4..8].copy_from_slice(&payload);
+        packet[8] = smbus_pec::pec(&packet[0..8]);
+
+        let (frame, mut decoder) = medium.deserialize(&packet).unwrap();
+        assert_eq!(frame.pec, Some(packet[8]));
+        assert_eq!(drain_to_vec(&mut decoder), payload);
+
+        // A corrupted PEC is rejected when verified, and accepted otherwise
+        packet[8] ^= 0xFF;
+        let err = medium.deserialize(&packet).err().unwrap();
+        assert_eq!(err, MctpPacketError::MediumError("PEC mismatch"));
+        assert!(SmbusEspiMedium::new().deserialize(&packet).is_ok());
+
+        // Verification has nothing to check without a PEC
+        assert!(
+            !SmbusEspiMedium::without_pec()
+                .with_pec_verification(true)
+                .pec_verification_enabled()
+        );
+    }
+
     #[test]
     fn test_deserialize_packet_too_short_header() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let short_packet = [0x01, 0x02]; // Only 2 bytes, need at least 4 for header
 
         let err = medium.deserialize(&short_packet).err().unwrap();
@@ -258,7 +360,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_packet_too_short_body() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Header indicates 10 bytes of data but we only provide 2
         let header_bytes = [
@@ -283,7 +385,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_invalid_header() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Create invalid header with command code that's not MCTP
         let invalid_header_bytes = [
@@ -307,7 +409,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_zero_byte_count() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         let header_bytes = [
             0x20, // destination_slave_address
@@ -326,13 +428,13 @@ mod tests {
         let (frame, mut decoder) = result;
 
         assert_eq!(frame.header.byte_count, 0);
-        assert_eq!(frame.pec, pec);
+        assert_eq!(frame.pec, Some(pec));
         assert_eq!(decoder.read().unwrap_err(), DecodeError::PrematureEnd);
     }
 
     #[test]
     fn test_serialize_valid_packet() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x20,
             source_slave_address: 0x10,
@@ -373,7 +475,7 @@ mod tests {
 
     #[test]
     fn test_serialize_buffer_too_small() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x20,
             source_slave_address: 0x10,
@@ -391,7 +493,7 @@ mod tests {
 
     #[test]
     fn test_serialize_minimal_buffer() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x20,
             source_slave_address: 0x10,
@@ -421,7 +523,7 @@ mod tests {
 
     #[test]
     fn test_serialize_max_payload() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x20,
             source_slave_address: 0x10,
@@ -456,7 +558,7 @@ mod tests {
 
     #[test]
     fn test_serialize_message_writer_error() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x20,
             source_slave_address: 0x10,
@@ -473,7 +575,7 @@ mod tests {
 
     #[test]
     fn test_roundtrip_serialization_deserialization() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let original_context = SmbusEspiReplyContext {
             destination_slave_address: 0x42,
             source_slave_address: 0x24,
@@ -504,7 +606,38 @@ mod tests {
 
         // Verify PEC is correct
         let expected_pec = smbus_pec::pec(&serialized[0..serialized.len() - 1]);
-        assert_eq!(frame.pec, expected_pec);
+        assert_eq!(frame.pec, Some(expected_pec));
+    }
+
+    #[test]
+    fn test_roundtrip_without_pec() {
+        let medium = SmbusEspiMedium::without_pec();
+        let original_context = SmbusEspiReplyContext {
+            destination_slave_address: 0x42,
+            source_slave_address: 0x24,
+        };
+
+        let original_payload = [0x11, 0x22, 0x33, 0x44, 0x55];
+        let mut buffer = [0u8; 64];
+
+        let serialized = medium
+            .serialize(original_context, &mut buffer, |encoder| {
+                encoder
+                    .write_all(&original_payload)
+                    .map_err(|_| MctpPacketError::SerializeError("encode error"))
+            })
+            .unwrap();
+
+        // No trailing PEC byte
+        assert_eq!(serialized.len(), 4 + original_payload.len());
+        assert_eq!(medium.frame_complete(serialized).unwrap(), Some(serialized.len()));
+
+        let (frame, mut decoder) = medium.deserialize(serialized).unwrap();
+        let deserialized_payload = drain_to_vec(&mut decoder);
+
+        assert_eq!(deserialized_payload, original_payload);
+        assert_eq!(frame.header.byte_count, original_payload.len() as u8);
+        assert_eq!(frame.pec, None);
     }
 
     #[test]
@@ -514,7 +647,7 @@ mod tests {
                 byte_count: 42,
                 ..Default::default()
             },
-            pec: 0,
+            pec: Some(0),
         };
 
         assert_eq!(frame.packet_size(), 42);
@@ -528,7 +661,7 @@ mod tests {
                 source_slave_address: 0x40,
                 ..Default::default()
             },
-            pec: 0,
+            pec: Some(0),
         };
 
         let context = frame.reply_context();
@@ -577,7 +710,7 @@ mod tests {
 
     #[test]
     fn test_pec_calculation_accuracy() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x50,
             source_slave_address: 0x30,
@@ -605,7 +738,7 @@ mod tests {
 
     #[test]
     fn test_serialize_with_empty_payload() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x60,
             source_slave_address: 0x70,
@@ -637,7 +770,7 @@ mod tests {
 
     #[test]
     fn test_max_message_body_size() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         assert_eq!(medium.max_message_body_size(), 32);
     }
 
@@ -650,7 +783,7 @@ mod tests {
                 source_slave_address: 0x3B,      // Valid 6-bit address
                 ..Default::default()
             },
-            pec: 0,
+            pec: Some(0),
         };
 
         let reply_context = SmbusEspiReplyContext::new(original_frame);
@@ -658,7 +791,7 @@ mod tests {
         assert_eq!(reply_context.source_slave_address, 0x3B);
 
         // Now test that when we serialize with this context, addresses are swapped back
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let mut buffer = [0u8; 16];
 
         let result = medium.serialize(reply_context, &mut buffer, |_| Ok(())).unwrap();
@@ -673,7 +806,7 @@ mod tests {
 
     #[test]
     fn test_deserialize_with_different_byte_counts() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         for byte_count in [1, 16, 32, 64, 128, 255] {
             let header_bytes = [
@@ -703,13 +836,13 @@ mod tests {
 
             assert_eq!(frame.header.byte_count, byte_count);
             assert_eq!(body.len(), byte_count as usize);
-            assert_eq!(frame.pec, pec);
+            assert_eq!(frame.pec, Some(pec));
         }
     }
 
     #[test]
     fn test_smbus_buffer_overflow_protection() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Test packet with byte_count that would cause overflow
         let header_bytes = [
@@ -735,7 +868,7 @@ mod tests {
 
     #[test]
     fn test_smbus_serialize_buffer_underflow() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
         let reply_context = SmbusEspiReplyContext {
             destination_slave_address: 0x20,
             source_slave_address: 0x10,
@@ -756,7 +889,7 @@ mod tests {
 
     #[test]
     fn test_smbus_header_bounds_checking() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Test with packet shorter than header size (4 bytes)
         for packet_size in 0..4 {
@@ -771,7 +904,7 @@ mod tests {
 
     #[test]
     fn test_smbus_pec_bounds_checking() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Test with packet that has header but claims more data than available for PEC
         let header_bytes = [
@@ -796,7 +929,7 @@ mod tests {
 
     #[test]
     fn test_smbus_zero_byte_count_edge_case() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Test with zero byte count but packet shorter than header + PEC
         let header_bytes = [
@@ -819,7 +952,7 @@ mod tests {
 
     #[test]
     fn test_smbus_maximum_payload_boundary() {
-        let medium = SmbusEspiMedium;
+        let medium = SmbusEspiMedium::new();
 
         // Test serialization at the boundary of maximum payload (255 bytes)
         let reply_context = SmbusEspiReplyContext {
@@ -862,7 +995,7 @@ mod tests {
 
     #[test]
     fn frame_complete_empty_buf_returns_none() {
-        assert_eq!(SmbusEspiMedium.frame_complete(&[]).unwrap(), None);
+        assert_eq!(SmbusEspiMedium::new().frame_complete(&[]).unwrap(), None);
     }
 
     #[test]
@@ -872,7 +1005,7 @@ mod tests {
         for n in 1..4 {
             let buf: Vec<u8> = (0..n).map(|_| 0u8).collect();
             assert_eq!(
-                SmbusEspiMedium.frame_complete(&buf).unwrap(),
+                SmbusEspiMedium::new().frame_complete(&buf).unwrap(),
                 None,
                 "partial header ({} bytes) should be incomplete",
                 n
@@ -884,21 +1017,21 @@ mod tests {
     fn frame_complete_exact_frame_returns_total_len() {
         // header (4) + body (byte_count = 3) + PEC (1) = 8 bytes
         let buf: [u8; 8] = [0x20, 0x10, 0x03, 0x0F, 0xAA, 0xBB, 0xCC, 0xDD];
-        assert_eq!(SmbusEspiMedium.frame_complete(&buf).unwrap(), Some(8));
+        assert_eq!(SmbusEspiMedium::new().frame_complete(&buf).unwrap(), Some(8));
     }
 
     #[test]
     fn frame_complete_short_of_body_returns_none() {
         // byte_count says 5, but we only have 4 header + 3 body bytes (no PEC yet)
         let buf: [u8; 7] = [0x20, 0x10, 0x05, 0x0F, 0xAA, 0xBB, 0xCC];
-        assert_eq!(SmbusEspiMedium.frame_complete(&buf).unwrap(), None);
+        assert_eq!(SmbusEspiMedium::new().frame_complete(&buf).unwrap(), None);
     }
 
     #[test]
     fn frame_complete_short_of_pec_returns_none() {
         // byte_count = 2 → expects 4 + 2 + 1 = 7 bytes; we have 6
         let buf: [u8; 6] = [0x20, 0x10, 0x02, 0x0F, 0xAA, 0xBB];
-        assert_eq!(SmbusEspiMedium.frame_complete(&buf).unwrap(), None);
+        assert_eq!(SmbusEspiMedium::new().frame_complete(&buf).unwrap(), None);
     }
 
     #[test]
@@ -907,13 +1040,13 @@ mod tests {
         // frame_complete reports the length of the FIRST frame; trailing
         // bytes are the caller's problem (e.g., next iteration of the loop).
         let buf: [u8; 8] = [0x20, 0x10, 0x01, 0x0F, 0xAA, 0xBB, 0xCC, 0xDD];
-        assert_eq!(SmbusEspiMedium.frame_complete(&buf).unwrap(), Some(6));
+        assert_eq!(SmbusEspiMedium::new().frame_complete(&buf).unwrap(), Some(6));
     }
 
     #[test]
     fn frame_complete_zero_byte_count_returns_5_bytes() {
         // Edge case: byte_count = 0 means 4 header + 0 body + 1 PEC = 5 bytes
         let buf: [u8; 5] = [0x20, 0x10, 0x00, 0x0F, 0xCC];
-        assert_eq!(SmbusEspiMedium.frame_complete(&buf).unwrap(), Some(5));
+        assert_eq!(SmbusEspiMedium::new().frame_complete(&buf).unwrap(), Some(5));
     }
 }
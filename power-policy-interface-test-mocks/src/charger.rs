@@ -2,9 +2,8 @@
 
 use std::collections::VecDeque;
 
-use embassy_sync::mutex::Mutex;
 use embedded_batteries_async::charger::{MilliAmps, MilliVolts};
-use embedded_services::{GlobalRawMutex, event::NonBlockingSender};
+use embedded_services::event::NonBlockingSender;
 use power_policy_interface::{capability::ConsumerPowerCapability, charger};
 
 /// Contains a charger function call and its arguments
@@ -13,6 +12,9 @@ pub enum FnCall {
     InitCharger,
     AttachHandler(ConsumerPowerCapability),
     DetachHandler,
+    SetInputCurrentLimit(MilliAmps),
+    InputCurrent,
+    ChargingStatus,
     IsReady,
     ChargingCurrent(MilliAmps),
     ChargingVoltage(MilliVolts),
@@ -30,6 +32,12 @@ pub struct Mock<S: NonBlockingSender<charger::event::EventData>> {
     pub next_result_attach_handler: VecDeque<Result<(), core::convert::Infallible>>,
     /// Next results to return for [`charger::Charger::detach_handler`]
     pub next_result_detach_handler: VecDeque<Result<(), core::convert::Infallible>>,
+    /// Next results to return for [`charger::Charger::set_input_current_limit`]
+    pub next_result_set_input_current_limit: VecDeque<Result<(), core::convert::Infallible>>,
+    /// Next results to return for [`charger::Charger::input_current`]
+    pub next_result_input_current: VecDeque<Result<Option<MilliAmps>, core::convert::Infallible>>,
+    /// Next results to return for [`charger::Charger::charging_status`]
+    pub next_result_charging_status: VecDeque<Result<Option<charger::ChargingStatus>, core::convert::Infallible>>,
     /// Next results to return for [`charger::Charger::is_ready`]
     pub next_result_is_ready: VecDeque<Result<(), core::convert::Infallible>>,
     /// Next results to return for [`embedded_batteries_async::charger::Charger::charging_current`]
@@ -47,6 +55,9 @@ impl<S: NonBlockingSender<charger::event::EventData>> Mock<S> {
             next_result_init_charger: VecDeque::new(),
             next_result_attach_handler: VecDeque::new(),
             next_result_detach_handler: VecDeque::new(),
+            next_result_set_input_current_limit: VecDeque::new(),
+            next_result_input_current: VecDeque::new(),
+            next_result_charging_status: VecDeque::new(),
             next_result_is_ready: VecDeque::new(),
             next_result_charging_current: VecDeque::new(),
             next_result_charging_voltage: VecDeque::new(),
@@ -90,9 +101,14 @@ impl<S: NonBlockingSender<charger::event::EventData>> charger::Charger for Mock<
 
     async fn init_charger(&mut self) -> Result<charger::PsuState, Self::ChargerError> {
         self.fn_calls.push_back(FnCall::InitCharger);
-        self.next_result_init_charger
+        let result = self
+            .next_result_init_charger
             .pop_front()
-            .expect("next_result_init_charger not set")
+            .expect("next_result_init_charger not set");
+        if let Ok(psu_state) = result {
+            self.state.on_initialized(psu_state).unwrap();
+        }
+        result
     }
 
     fn attach_handler(
@@ -116,11 +132,37 @@ impl<S: NonBlockingSender<charger::event::EventData>> charger::Charger for Mock<
         async move { result }
     }
 
+    async fn set_input_current_limit(&mut self, current: MilliAmps) -> Result<(), Self::ChargerError> {
+        self.fn_calls.push_back(FnCall::SetInputCurrentLimit(current));
+        self.next_result_set_input_current_limit
+            .pop_front()
+            .expect("next_result_set_input_current_limit not set")
+    }
+
+    async fn input_current(&mut self) -> Result<Option<MilliAmps>, Self::ChargerError> {
+        self.fn_calls.push_back(FnCall::InputCurrent);
+        self.next_result_input_current
+            .pop_front()
+            .expect("next_result_input_current not set")
+    }
+
+    async fn charging_status(&mut self) -> Result<Option<charger::ChargingStatus>, Self::ChargerError> {
+        self.fn_calls.push_back(FnCall::ChargingStatus);
+        self.next_result_charging_status
+            .pop_front()
+            .expect("next_result_charging_status not set")
+    }
+
     async fn is_ready(&mut self) -> Result<(), Self::ChargerError> {
         self.fn_calls.push_back(FnCall::IsReady);
-        self.next_result_is_ready
+        let result = self
+            .next_result_is_ready
             .pop_front()
-            .expect("next_result_is_ready not set")
+            .expect("next_result_is_ready not set");
+        if result.is_ok() {
+            self.state.on_ready_success();
+        }
+        result
     }
 
     fn state(&self) -> &charger::State {
@@ -132,4 +174,4 @@ impl<S: NonBlockingSender<charger::event::EventData>> charger::Charger for Mock<
     }
 }
 
-pub type ChargerType<S> = Mutex<GlobalRawMutex, Mock<S>>;
+pub type ChargerType<S> = charger::Device<Mock<S>>;
@@ -5,6 +5,7 @@ use num_enum::{IntoPrimitive, TryFromPrimitive};
 /// Amount of power that a device can provider or consume
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PowerCapability {
     /// Available voltage in mV
     pub voltage_mv: u16,
@@ -13,9 +14,27 @@ pub struct PowerCapability {
 }
 
 impl PowerCapability {
+    /// Maximum voltage accepted by [`Self::new`], covers USB PD EPR
+    pub const MAX_VOLTAGE_MV: u16 = 48000;
+    /// Maximum current accepted by [`Self::new`], covers USB PD and high-current DC adapters
+    pub const MAX_CURRENT_MA: u16 = 10000;
+
+    /// Create a new power capability
+    ///
+    /// Returns `None` if the voltage is zero or either value is above [`Self::MAX_VOLTAGE_MV`] or
+    /// [`Self::MAX_CURRENT_MA`].
+    pub fn new(voltage_mv: u16, current_ma: u16) -> Option<Self> {
+        if voltage_mv == 0 || voltage_mv > Self::MAX_VOLTAGE_MV || current_ma > Self::MAX_CURRENT_MA {
+            return None;
+        }
+
+        Some(Self { voltage_mv, current_ma })
+    }
+
     /// Calculate maximum power
     pub fn max_power_mw(&self) -> u32 {
-        self.voltage_mv as u32 * self.current_ma as u32 / 1000
+        // The product of two u16 values always fits in a u32
+        u32::from(self.voltage_mv) * u32::from(self.current_ma) / 1000
     }
 }
 
@@ -34,6 +53,7 @@ impl Ord for PowerCapability {
 /// Power capability with consumer flags
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ConsumerPowerCapability {
     /// Power capability
     pub capability: PowerCapability,
@@ -113,6 +133,7 @@ bitfield! {
     /// Raw consumer flags bit field
     #[derive(Copy, Clone, PartialEq, Eq)]
     #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     struct ConsumerFlagsRaw(u32);
     impl Debug;
     /// Unconstrained power, indicates that we are drawing power from something like an outlet and not a limited source like a battery
@@ -124,6 +145,7 @@ bitfield! {
 /// Type safe wrapper for consumer flags
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ConsumerFlags(ConsumerFlagsRaw);
 
 impl ConsumerFlags {
@@ -217,6 +239,18 @@ bitfield! {
     ///
     /// When set this flag indicates that the service is switching to a different PSU.
     pub bool, switching, set_switching: 1;
+    /// Unplugged
+    ///
+    /// When set this flag indicates that the PSU was physically detached.
+    pub bool, unplugged, set_unplugged: 2;
+    /// Fault
+    ///
+    /// When set this flag indicates that the PSU stopped due to a fault.
+    pub bool, fault, set_fault: 3;
+    /// Shutdown
+    ///
+    /// When set this flag indicates that the service is shutting down.
+    pub bool, shutdown, set_shutdown: 4;
 }
 
 /// Type safe wrapper for consumer disconnect flags
@@ -261,6 +295,54 @@ impl ConsumerDisconnect {
     pub fn switching(&self) -> bool {
         self.0.switching()
     }
+
+    /// Builder method to set the unplugged flag
+    pub fn with_unplugged(mut self, value: bool) -> Self {
+        self.set_unplugged(value);
+        self
+    }
+
+    /// Set the value of the unplugged flag
+    pub fn set_unplugged(&mut self, value: bool) {
+        self.0.set_unplugged(value);
+    }
+
+    /// Get the value of the unplugged flag
+    pub fn unplugged(&self) -> bool {
+        self.0.unplugged()
+    }
+
+    /// Builder method to set the fault flag
+    pub fn with_fault(mut self, value: bool) -> Self {
+        self.set_fault(value);
+        self
+    }
+
+    /// Set the value of the fault flag
+    pub fn set_fault(&mut self, value: bool) {
+        self.0.set_fault(value);
+    }
+
+    /// Get the value of the fault flag
+    pub fn fault(&self) -> bool {
+        self.0.fault()
+    }
+
+    /// Builder method to set the shutdown flag
+    pub fn with_shutdown(mut self, value: bool) -> Self {
+        self.set_shutdown(value);
+        self
+    }
+
+    /// Set the value of the shutdown flag
+    pub fn set_shutdown(&mut self, value: bool) {
+        self.0.set_shutdown(value);
+    }
+
+    /// Get the value of the shutdown flag
+    pub fn shutdown(&self) -> bool {
+        self.0.shutdown()
+    }
 }
 
 impl Default for ConsumerDisconnect {
@@ -269,10 +351,132 @@ impl Default for ConsumerDisconnect {
     }
 }
 
+bitfield! {
+    /// Flags for provider disconnect events
+    #[derive(Copy, Clone, PartialEq, Eq)]
+    #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+    struct ProviderDisconnectRaw(u32);
+    impl Debug;
+    /// Unplugged
+    ///
+    /// When set this flag indicates that the PSU was physically detached.
+    pub bool, unplugged, set_unplugged: 0;
+    /// Fault
+    ///
+    /// When set this flag indicates that the PSU stopped due to a fault.
+    pub bool, fault, set_fault: 1;
+    /// Shutdown
+    ///
+    /// When set this flag indicates that the service is shutting down.
+    pub bool, shutdown, set_shutdown: 2;
+}
+
+/// Type safe wrapper for provider disconnect flags
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProviderDisconnect(ProviderDisconnectRaw);
+
+impl ProviderDisconnect {
+    /// Create new provider disconnect flags with no flags set
+    pub const fn none() -> Self {
+        Self(ProviderDisconnectRaw(0))
+    }
+
+    /// Builder method to set the unplugged flag
+    pub fn with_unplugged(mut self, value: bool) -> Self {
+        self.set_unplugged(value);
+        self
+    }
+
+    /// Set the value of the unplugged flag
+    pub fn set_unplugged(&mut self, value: bool) {
+        self.0.set_unplugged(value);
+    }
+
+    /// Get the value of the unplugged flag
+    pub fn unplugged(&self) -> bool {
+        self.0.unplugged()
+    }
+
+    /// Builder method to set the fault flag
+    pub fn with_fault(mut self, value: bool) -> Self {
+        self.set_fault(value);
+        self
+    }
+
+    /// Set the value of the fault flag
+    pub fn set_fault(&mut self, value: bool) {
+        self.0.set_fault(value);
+    }
+
+    /// Get the value of the fault flag
+    pub fn fault(&self) -> bool {
+        self.0.fault()
+    }
+
+    /// Builder method to set the shutdown flag
+    pub fn with_shutdown(mut self, value: bool) -> Self {
+        self.set_shutdown(value);
+        self
+    }
+
+    /// Set the value of the shutdown flag
+    pub fn set_shutdown(&mut self, value: bool) {
+        self.0.set_shutdown(value);
+    }
+
+    /// Get the value of the shutdown flag
+    pub fn shutdown(&self) -> bool {
+        self.0.shutdown()
+    }
+}
+
+impl Default for ProviderDisconnect {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl From<ConsumerDisconnect> for ProviderDisconnect {
+    /// Keep the flags that apply to providers, consumer specific flags are dropped
+    fn from(value: ConsumerDisconnect) -> Self {
+        Self::none()
+            .with_unplugged(value.unplugged())
+            .with_fault(value.fault())
+            .with_shutdown(value.shutdown())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_power_capability_new() {
+        assert_eq!(
+            PowerCapability::new(5000, 3000),
+            Some(PowerCapability {
+                voltage_mv: 5000,
+                current_ma: 3000,
+            })
+        );
+        assert!(PowerCapability::new(PowerCapability::MAX_VOLTAGE_MV, PowerCapability::MAX_CURRENT_MA).is_some());
+        assert!(PowerCapability::new(5000, 0).is_some());
+
+        assert_eq!(PowerCapability::new(0, 3000), None);
+        assert_eq!(PowerCapability::new(PowerCapability::MAX_VOLTAGE_MV + 1, 3000), None);
+        assert_eq!(PowerCapability::new(5000, PowerCapability::MAX_CURRENT_MA + 1), None);
+    }
+
+    #[test]
+    fn test_power_capability_max_power_overflow() {
+        let capability = PowerCapability {
+            voltage_mv: u16::MAX,
+            current_ma: u16::MAX,
+        };
+        assert_eq!(capability.max_power_mw(), 4_294_836);
+    }
+
     #[test]
     fn test_psu_type_conversion() {
         // Test valid conversions
@@ -352,4 +556,30 @@ mod tests {
         assert!(!disconnect.renegotiation());
         assert!(!disconnect.switching());
     }
+
+    #[test]
+    fn test_consumer_disconnect_reasons() {
+        let mut disconnect = ConsumerDisconnect::none()
+            .with_unplugged(true)
+            .with_fault(true)
+            .with_shutdown(true);
+        assert_eq!(disconnect.0.0, 0x1c);
+        assert!(disconnect.unplugged());
+        assert!(disconnect.fault());
+        assert!(disconnect.shutdown());
+        disconnect.set_fault(false);
+        assert_eq!(disconnect.0.0, 0x14);
+        assert!(!disconnect.fault());
+    }
+
+    #[test]
+    fn test_provider_disconnect_from_consumer() {
+        let consumer = ConsumerDisconnect::none().with_switching(true).with_fault(true);
+        let provider = ProviderDisconnect::from(consumer);
+        assert_eq!(provider.0.0, 0x2);
+        assert!(provider.fault());
+        assert!(!provider.unplugged());
+        assert!(!provider.shutdown());
+        assert_eq!(ProviderDisconnect::default().0.0, 0x0);
+    }
 }
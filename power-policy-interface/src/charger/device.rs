@@ -0,0 +1,120 @@
+//! Charger wrapper that allows waiting for the charger to become ready
+use core::cell::RefCell;
+use core::future::poll_fn;
+use core::ops::{Deref, DerefMut};
+use core::task::Poll;
+
+use embassy_sync::blocking_mutex;
+use embassy_sync::mutex::{Mutex, MutexGuard};
+use embassy_sync::waitqueue::MultiWakerRegistration;
+use embedded_services::{GlobalRawMutex, sync::Lockable};
+
+use super::Charger;
+
+/// Default number of tasks that can wait for a charger to become ready at the same time
+pub const DEFAULT_READY_WAITERS: usize = 4;
+
+/// Readiness of the charger, as of the last time it was unlocked
+struct Readiness<const N: usize> {
+    /// Whether the charger is initialized
+    ready: bool,
+    /// Tasks waiting for the charger to become ready
+    wakers: MultiWakerRegistration<N>,
+}
+
+/// Charger wrapper that tracks whether the charger is ready
+///
+/// The charger state can only change while the charger is locked, readiness is updated each time the charger is
+/// unlocked. `N` is the number of tasks that can wait in [`Self::wait_ready`] at the same time.
+pub struct Device<C: Charger, const N: usize = DEFAULT_READY_WAITERS> {
+    /// Wrapped charger
+    charger: Mutex<GlobalRawMutex, C>,
+    /// Readiness and waiters
+    readiness: blocking_mutex::Mutex<GlobalRawMutex, RefCell<Readiness<N>>>,
+}
+
+impl<C: Charger, const N: usize> Device<C, N> {
+    /// Create a new instance
+    pub fn new(charger: C) -> Self {
+        Self {
+            readiness: blocking_mutex::Mutex::new(RefCell::new(Readiness {
+                ready: charger.state().is_initialized(),
+                wakers: MultiWakerRegistration::new(),
+            })),
+            charger: Mutex::new(charger),
+        }
+    }
+
+    /// Wait until the charger is initialized
+    ///
+    /// Returns immediately if the charger is already initialized.
+    pub async fn wait_ready(&self) {
+        poll_fn(|cx| {
+            self.readiness.lock(|readiness| {
+                let mut readiness = readiness.borrow_mut();
+                if readiness.ready {
+                    Poll::Ready(())
+                } else {
+                    readiness.wakers.register(cx.waker());
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+
+    /// Wrap a guard so readiness is updated when it's dropped
+    fn guard<'a>(&'a self, guard: MutexGuard<'a, GlobalRawMutex, C>) -> DeviceGuard<'a, C, N> {
+        DeviceGuard {
+            guard,
+            readiness: &self.readiness,
+        }
+    }
+}
+
+impl<C: Charger, const N: usize> Lockable for Device<C, N> {
+    type Inner = C;
+
+    fn try_lock(&self) -> Option<impl DerefMut<Target = Self::Inner>> {
+        self.charger.try_lock().ok().map(|guard| self.guard(guard))
+    }
+
+    fn lock(&self) -> impl Future<Output = impl DerefMut<Target = Self::Inner>> {
+        async move { self.guard(self.charger.lock().await) }
+    }
+}
+
+/// Charger guard that updates the readiness of the charger when dropped
+struct DeviceGuard<'a, C: Charger, const N: usize> {
+    /// Charger guard
+    guard: MutexGuard<'a, GlobalRawMutex, C>,
+    /// Readiness and waiters
+    readiness: &'a blocking_mutex::Mutex<GlobalRawMutex, RefCell<Readiness<N>>>,
+}
+
+impl<C: Charger, const N: usize> Deref for DeviceGuard<'_, C, N> {
+    type Target = C;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<C: Charger, const N: usize> DerefMut for DeviceGuard<'_, C, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<C: Charger, const N: usize> Drop for DeviceGuard<'_, C, N> {
+    fn drop(&mut self) {
+        let ready = self.guard.state().is_initialized();
+        self.readiness.lock(|readiness| {
+            let mut readiness = readiness.borrow_mut();
+            readiness.ready = ready;
+            if ready {
+                readiness.wakers.wake();
+            }
+        });
+    }
+}
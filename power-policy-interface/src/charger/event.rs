@@ -1,5 +1,6 @@
 //! Events originating from a charger device
 
+use embedded_batteries_async::charger::MilliAmps;
 use embedded_services::sync::Lockable;
 
 /// PSU state as determined by charger device
@@ -28,6 +29,13 @@ impl From<bool> for PsuState {
 pub enum EventData {
     /// PSU state changed
     PsuStateChange(PsuState),
+    /// Measured input current is below the commanded input current limit by more than the allowed tolerance
+    InputUnderDelivery {
+        /// Commanded input current limit
+        expected_ma: MilliAmps,
+        /// Measured input current
+        measured_ma: MilliAmps,
+    },
 }
 
 /// Event broadcast from a charger.
@@ -2,13 +2,16 @@
 
 use crate::capability::ConsumerPowerCapability;
 use core::{convert::Infallible, future::Future};
+use embedded_batteries_async::charger::MilliAmps;
 
+pub mod device;
 pub mod event;
 /// Mock software representation of a charger
 pub mod mock;
 #[cfg(test)]
 mod tests;
 
+pub use device::Device;
 pub use event::{Event, EventData, PsuState};
 
 /// Charger Device ID new type
@@ -65,6 +68,21 @@ pub enum PoweredSubstate {
     PsuDetached,
 }
 
+/// Whether the charger is delivering charge current to the battery
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargingStatus {
+    /// No charge current is flowing
+    NotCharging,
+    /// Charge current is flowing
+    Charging {
+        /// Measured charge current
+        current_ma: MilliAmps,
+    },
+    /// The charger has stopped charging due to a fault
+    Fault,
+}
+
 /// Current state of the charger
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -73,6 +91,10 @@ pub struct State {
     state: InternalState,
     /// Current charger capability
     capability: Option<ConsumerPowerCapability>,
+    /// Current commanded input current limit
+    input_current_limit: Option<MilliAmps>,
+    /// Whether an input under-delivery has been reported for the current input current limit
+    input_under_delivery: bool,
 }
 
 impl Default for State {
@@ -80,6 +102,8 @@ impl Default for State {
         Self {
             state: InternalState::Unpowered,
             capability: None,
+            input_current_limit: None,
+            input_under_delivery: false,
         }
     }
 }
@@ -134,10 +158,16 @@ impl State {
         }
     }
 
+    /// Returns the current commanded input current limit, if any.
+    pub fn input_current_limit(&self) -> Option<MilliAmps> {
+        self.input_current_limit
+    }
+
     /// Handle a communication timeout. Transitions to `Unpowered` and clears the cached capability.
     pub fn on_timeout(&mut self) {
         self.state = InternalState::Unpowered;
         self.capability = None;
+        self.clear_input_current_limit();
     }
 
     /// Transition after a successful check-ready response.
@@ -171,12 +201,55 @@ impl State {
     /// Does not change the charger state.
     pub fn on_policy_detach(&mut self) {
         self.capability = None;
+        self.clear_input_current_limit();
+    }
+
+    /// Cache a new commanded input current limit.
+    /// Does not change the charger state.
+    pub fn on_input_current_limit(&mut self, current: MilliAmps) {
+        self.input_current_limit = Some(current);
+        self.input_under_delivery = false;
+    }
+
+    /// Compare a measured input current against the commanded input current limit.
+    ///
+    /// Returns [`EventData::InputUnderDelivery`] the first time the measured current falls short of the limit
+    /// by more than `tolerance`. No further events are returned until the measured current recovers or a new
+    /// limit is commanded.
+    pub fn on_input_current_measured(&mut self, measured: MilliAmps, tolerance: MilliAmps) -> Option<EventData> {
+        let expected = self.input_current_limit?;
+
+        if expected.saturating_sub(measured) <= tolerance {
+            self.input_under_delivery = false;
+            None
+        } else if self.input_under_delivery {
+            None
+        } else {
+            self.input_under_delivery = true;
+            Some(EventData::InputUnderDelivery {
+                expected_ma: expected,
+                measured_ma: measured,
+            })
+        }
+    }
+
+    fn clear_input_current_limit(&mut self) {
+        self.input_current_limit = None;
+        self.input_under_delivery = false;
     }
 
     /// Returns `true` if the charger is in the `Unpowered` state.
     pub fn is_unpowered(&self) -> bool {
         self.state == InternalState::Unpowered
     }
+
+    /// Returns `true` if the charger is in the `Powered(PsuAttached)` or `Powered(PsuDetached)` state.
+    pub fn is_initialized(&self) -> bool {
+        matches!(
+            self.state,
+            InternalState::Powered(PoweredSubstate::PsuAttached | PoweredSubstate::PsuDetached)
+        )
+    }
 }
 
 /// Charger controller trait that devices must implement to use the power policy service.
@@ -194,6 +267,23 @@ pub trait Charger: embedded_batteries_async::charger::Charger {
     /// Called after power policy detaches from a power port, either to switch consumers,
     /// or because PSU was disconnected.
     fn detach_handler(&mut self) -> impl Future<Output = Result<(), Self::ChargerError>>;
+    /// Called whenever the consumer power capability changes, including after [`Self::attach_handler`].
+    ///
+    /// When the current consumer renegotiates a new capability, this is called without a detach/attach
+    /// so the charger can re-limit its input current in place.
+    ///
+    /// The power policy service records the limit in [`State`] once this returns successfully.
+    fn set_input_current_limit(&mut self, _current: MilliAmps) -> impl Future<Output = Result<(), Self::ChargerError>> {
+        core::future::ready(Ok(()))
+    }
+    /// Measure the current drawn from the PSU, returns `None` if the charger can't measure it.
+    fn input_current(&mut self) -> impl Future<Output = Result<Option<MilliAmps>, Self::ChargerError>> {
+        core::future::ready(Ok(None))
+    }
+    /// Report whether charge current is flowing, returns `None` if the charger can't report it.
+    fn charging_status(&mut self) -> impl Future<Output = Result<Option<ChargingStatus>, Self::ChargerError>> {
+        core::future::ready(Ok(None))
+    }
     /// Upon successful return of this method, the charger is assumed to be powered and ready to communicate,
     /// transitioning state from unpowered to powered.
     fn is_ready(&mut self) -> impl Future<Output = Result<(), Self::ChargerError>> {
@@ -11,21 +11,21 @@ fn cap(voltage_mv: u16, current_ma: u16) -> ConsumerPowerCapability {
 fn state_init() -> State {
     State {
         state: InternalState::Powered(PoweredSubstate::Init),
-        capability: None,
+        ..State::default()
     }
 }
 
 fn state_psu_attached() -> State {
     State {
         state: InternalState::Powered(PoweredSubstate::PsuAttached),
-        capability: None,
+        ..State::default()
     }
 }
 
 fn state_psu_detached() -> State {
     State {
         state: InternalState::Powered(PoweredSubstate::PsuDetached),
-        capability: None,
+        ..State::default()
     }
 }
 
@@ -33,6 +33,16 @@ fn state_unpowered() -> State {
     State::default()
 }
 
+// is_initialized
+
+#[test]
+fn is_initialized() {
+    assert!(!state_unpowered().is_initialized());
+    assert!(!state_init().is_initialized());
+    assert!(state_psu_attached().is_initialized());
+    assert!(state_psu_detached().is_initialized());
+}
+
 // on_initialized
 
 #[test]
@@ -248,6 +258,50 @@ fn policy_detach_from_unpowered() {
     assert_eq!(s.state, InternalState::Unpowered);
 }
 
+// on_input_current_measured
+
+#[test]
+fn on_input_current_measured_without_limit() {
+    let mut s = state_psu_attached();
+    assert_eq!(s.on_input_current_measured(0, 100), None);
+}
+
+#[test]
+fn on_input_current_measured_within_tolerance() {
+    let mut s = state_psu_attached();
+    s.on_input_current_limit(1500);
+    assert_eq!(s.on_input_current_measured(1400, 100), None);
+    assert!(!s.input_under_delivery);
+}
+
+#[test]
+fn on_input_current_measured_reports_once() {
+    let mut s = state_psu_attached();
+    s.on_input_current_limit(1500);
+    assert_eq!(
+        s.on_input_current_measured(1000, 100),
+        Some(EventData::InputUnderDelivery {
+            expected_ma: 1500,
+            measured_ma: 1000,
+        })
+    );
+    assert_eq!(s.on_input_current_measured(900, 100), None);
+
+    // Recovering re-arms the event
+    assert_eq!(s.on_input_current_measured(1500, 100), None);
+    assert!(s.on_input_current_measured(1000, 100).is_some());
+}
+
+#[test]
+fn on_policy_detach_clears_input_current_limit() {
+    let mut s = state_psu_attached();
+    s.on_input_current_limit(1500);
+    assert!(s.on_input_current_measured(1000, 100).is_some());
+    s.on_policy_detach();
+    assert_eq!(s.input_current_limit(), None);
+    assert!(!s.input_under_delivery);
+}
+
 // Full transition sequence
 
 #[test]
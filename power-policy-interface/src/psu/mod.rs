@@ -1,7 +1,7 @@
 //! Device struct and methods
 use embedded_services::named::Named;
 
-use crate::capability::{ConsumerPowerCapability, PowerCapability, ProviderPowerCapability};
+use crate::capability::{ConsumerPowerCapability, ProviderPowerCapability};
 
 pub mod event;
 
@@ -12,9 +12,9 @@ pub enum Error {
     /// The requested device does not exist
     InvalidDevice,
     /// The provide request was denied, contains maximum available power
-    CannotProvide(Option<PowerCapability>),
+    CannotProvide(Option<ProviderPowerCapability>),
     /// The consume request was denied, contains maximum available power
-    CannotConsume(Option<PowerCapability>),
+    CannotConsume(Option<ConsumerPowerCapability>),
     /// The device is not in the correct state (expected, actual)
     InvalidState(&'static [StateKind], StateKind),
     /// Invalid response
@@ -194,19 +194,33 @@ impl State {
 
     /// Check if a request to connect as a consumer from the policy is valid given the current state
     /// Returns () or the error with information about why the request is invalid
-    pub fn can_connect_consumer(&self) -> Result<(), Error> {
+    ///
+    /// The request is denied with [`Error::CannotConsume`], carrying the available consumer capability, if it
+    /// exceeds that capability, e.g. if the capability dropped after the policy decided to connect.
+    pub fn can_connect_consumer(&self, capability: ConsumerPowerCapability) -> Result<(), Error> {
         match self.psu_state {
-            PsuState::Idle | PsuState::ConnectedConsumer(_) => Ok(()),
-            _ => Err(Error::InvalidState(
-                &[StateKind::Idle, StateKind::ConnectedConsumer],
-                self.psu_state.kind(),
-            )),
+            PsuState::Idle | PsuState::ConnectedConsumer(_) => {}
+            _ => {
+                return Err(Error::InvalidState(
+                    &[StateKind::Idle, StateKind::ConnectedConsumer],
+                    self.psu_state.kind(),
+                ));
+            }
+        }
+
+        if self
+            .consumer_capability
+            .is_some_and(|available| capability.capability <= available.capability)
+        {
+            Ok(())
+        } else {
+            Err(Error::CannotConsume(self.consumer_capability))
         }
     }
 
     /// Handle a request to connect as a consumer from the policy
     pub fn connect_consumer(&mut self, capability: ConsumerPowerCapability) -> Result<(), Error> {
-        self.can_connect_consumer()?;
+        self.can_connect_consumer(capability)?;
         self.psu_state = PsuState::ConnectedConsumer(capability);
         Ok(())
     }
@@ -252,3 +266,63 @@ pub trait Psu: Named {
     /// Return a mutable reference to the current PSU state
     fn state_mut(&mut self) -> &mut State;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capability::{ConsumerFlags, PowerCapability};
+
+    const LOW_POWER: PowerCapability = PowerCapability {
+        voltage_mv: 5000,
+        current_ma: 1500,
+    };
+
+    const HIGH_POWER: PowerCapability = PowerCapability {
+        voltage_mv: 5000,
+        current_ma: 3000,
+    };
+
+    fn attached_state(consumer_capability: Option<ConsumerPowerCapability>) -> State {
+        State {
+            psu_state: PsuState::Idle,
+            consumer_capability,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_connect_consumer() {
+        let available = ConsumerPowerCapability {
+            capability: HIGH_POWER,
+            flags: ConsumerFlags::none().with_unconstrained_power(),
+        };
+
+        let mut state = attached_state(Some(available));
+        assert_eq!(state.connect_consumer(LOW_POWER.into()), Ok(()));
+        assert_eq!(state.psu_state, PsuState::ConnectedConsumer(LOW_POWER.into()));
+    }
+
+    #[test]
+    fn test_connect_consumer_denied() {
+        let available = ConsumerPowerCapability {
+            capability: LOW_POWER,
+            flags: ConsumerFlags::none().with_unconstrained_power(),
+        };
+
+        // The denial carries the full available consumer capability, including its flags
+        let mut state = attached_state(Some(available));
+        assert_eq!(
+            state.connect_consumer(HIGH_POWER.into()),
+            Err(Error::CannotConsume(Some(available)))
+        );
+        assert_eq!(state.psu_state, PsuState::Idle);
+
+        // Nothing to consume from
+        let mut state = attached_state(None);
+        assert_eq!(
+            state.connect_consumer(LOW_POWER.into()),
+            Err(Error::CannotConsume(None))
+        );
+        assert_eq!(state.psu_state, PsuState::Idle);
+    }
+}
@@ -1,7 +1,9 @@
+use embedded_batteries_async::charger::MilliAmps;
 use embedded_services::sync::Lockable;
 
 use crate::{
-    capability::{ConsumerDisconnect, ConsumerPowerCapability, ProviderPowerCapability},
+    capability::{ConsumerDisconnect, ConsumerPowerCapability, ProviderDisconnect, ProviderPowerCapability},
+    charger::ChargerId,
     psu::Psu,
     service::UnconstrainedState,
 };
@@ -20,11 +22,23 @@ pub enum EventData {
     /// Consumer connected
     ConsumerConnected(ConsumerPowerCapability),
     /// Provider disconnected
-    ProviderDisconnected,
+    ProviderDisconnected(ProviderDisconnect),
     /// Provider connected
     ProviderConnected(ProviderPowerCapability),
+    /// Capability of an already connected provider changed
+    ProviderCapabilityChanged(ProviderPowerCapability),
     /// Unconstrained state changed
     Unconstrained(UnconstrainedState),
+    /// Measured input current of a charger is below its commanded input current limit by more than the
+    /// configured tolerance
+    ChargerInputUnderDelivery {
+        /// Charger that is under-delivering
+        charger: ChargerId,
+        /// Commanded input current limit
+        expected_ma: MilliAmps,
+        /// Measured input current
+        measured_ma: MilliAmps,
+    },
 }
 
 impl<'device, PSU: Lockable> From<Event<'device, PSU>> for EventData
@@ -35,9 +49,19 @@ where
         match value {
             Event::ConsumerDisconnected(_, flags) => EventData::ConsumerDisconnected(flags),
             Event::ConsumerConnected(_, capability) => EventData::ConsumerConnected(capability),
-            Event::ProviderDisconnected(_) => EventData::ProviderDisconnected,
+            Event::ProviderDisconnected(_, flags) => EventData::ProviderDisconnected(flags),
             Event::ProviderConnected(_, capability) => EventData::ProviderConnected(capability),
+            Event::ProviderCapabilityChanged(_, capability) => EventData::ProviderCapabilityChanged(capability),
             Event::Unconstrained(unconstrained) => EventData::Unconstrained(unconstrained),
+            Event::ChargerInputUnderDelivery {
+                charger,
+                expected_ma,
+                measured_ma,
+            } => EventData::ChargerInputUnderDelivery {
+                charger,
+                expected_ma,
+                measured_ma,
+            },
         }
     }
 }
@@ -55,11 +79,23 @@ where
     /// Consumer connected
     ConsumerConnected(&'device PSU, ConsumerPowerCapability),
     /// Provider disconnected
-    ProviderDisconnected(&'device PSU),
+    ProviderDisconnected(&'device PSU, ProviderDisconnect),
     /// Provider connected
     ProviderConnected(&'device PSU, ProviderPowerCapability),
+    /// Capability of an already connected provider changed
+    ProviderCapabilityChanged(&'device PSU, ProviderPowerCapability),
     /// Unconstrained state changed
     Unconstrained(UnconstrainedState),
+    /// Measured input current of a charger is below its commanded input current limit by more than the
+    /// configured tolerance
+    ChargerInputUnderDelivery {
+        /// Charger that is under-delivering
+        charger: ChargerId,
+        /// Commanded input current limit
+        expected_ma: MilliAmps,
+        /// Measured input current
+        measured_ma: MilliAmps,
+    },
 }
 
 impl<'device, PSU> Clone for Event<'device, PSU>
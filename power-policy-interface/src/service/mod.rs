@@ -3,6 +3,7 @@ pub mod event;
 /// Unconstrained state information
 #[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct UnconstrainedState {
     /// Unconstrained state
     pub unconstrained: bool,
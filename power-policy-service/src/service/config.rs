@@ -15,6 +15,18 @@ pub struct Config {
     ///
     /// If [`None`], the service will consume from providers, regardless of how much power they provide.
     pub min_consumer_threshold_mw: Option<u32>,
+    /// Maximum total power that can be supplied to all connected providers.
+    ///
+    /// If [`None`], the total provided power is only limited by the per-provider capabilities.
+    pub max_total_provider_mw: Option<u32>,
+    /// Grant a reduced capability instead of rejecting a provider request that exceeds
+    /// [`max_total_provider_mw`](Self::max_total_provider_mw).
+    ///
+    /// The reduced capability keeps the requested voltage and limits the current to the remaining budget.
+    pub limit_provider_to_budget: bool,
+    /// Allowed shortfall of a charger's measured input current below its commanded input current limit
+    /// before an under-delivery is reported.
+    pub input_current_tolerance_ma: u16,
 }
 
 impl Default for Config {
@@ -34,6 +46,12 @@ impl Default for Config {
             },
             // No minimum threshold
             min_consumer_threshold_mw: None,
+            // No total provider budget
+            max_total_provider_mw: None,
+            // Reject requests over budget
+            limit_provider_to_budget: false,
+            // Allow for measurement error
+            input_current_tolerance_ma: 100,
         }
     }
 }
@@ -31,6 +31,31 @@ impl<'device, Psu: Lockable<Inner: psu::Psu>> Clone for AvailableConsumer<'devic
 
 impl<'device, Psu: Lockable<Inner: psu::Psu>> Copy for AvailableConsumer<'device, Psu> {}
 
+/// Maximum number of candidates recorded in a [`PolicyDecision`]
+pub const MAX_POLICY_DECISION_CANDIDATES: usize = 8;
+
+/// Consumer considered in a [`PolicyDecision`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DecisionCandidate {
+    /// Index of the PSU in the service registration
+    pub psu: usize,
+    /// The power capability offered by the PSU
+    pub consumer_power_capability: ConsumerPowerCapability,
+}
+
+/// Record of a single consumer selection
+///
+/// Sent to the sender registered with [`Service::register_decision_sender`] each time the service selects a consumer.
+/// PSUs are identified by their index in the registration so records don't borrow the PSUs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyDecision {
+    /// Every PSU that offered a consumer capability when the decision was made
+    pub candidates: heapless::Vec<DecisionCandidate, MAX_POLICY_DECISION_CANDIDATES>,
+    /// The selected consumer, if any
+    pub chosen: Option<DecisionCandidate>,
+}
+
 /// Default function for comparing two consumer capabilities to determine which one is better
 ///
 /// This is not part of the `Ord` implementation for `ConsumerPowerCapability`, because it's specific to this implementation.
@@ -112,7 +137,7 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
     Service<'device, Reg, Customization>
 {
     /// Update unconstrained state and broadcast notifications if needed
-    async fn update_unconstrained_state(&mut self) -> Result<(), Error> {
+    pub(super) async fn update_unconstrained_state(&mut self) -> Result<(), Error> {
         // Count how many available unconstrained devices we have
         let mut unconstrained_new = UnconstrainedState::default();
         for psu in self.registration.psus() {
@@ -134,14 +159,62 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
             info!("Unconstrained state changed: {:?}", unconstrained_new);
             self.state.unconstrained = unconstrained_new;
             self.broadcast_event(ServiceEvent::Unconstrained(self.state.unconstrained));
+            if let Some(signal) = self.unconstrained_signal {
+                signal.0.signal(self.state.unconstrained);
+            }
         }
         Ok(())
     }
 
+    /// Send a [`PolicyDecision`] record if a decision sender is registered
+    async fn post_policy_decision(&self, chosen: Option<AvailableConsumer<'device, Reg::Psu>>) {
+        let Some(sender) = self.decision_sender.as_ref() else {
+            return;
+        };
+
+        let mut candidates = heapless::Vec::new();
+        for (index, psu) in self.registration.psus().iter().enumerate() {
+            let Some(capability) = psu.lock().await.state().consumer_capability else {
+                continue;
+            };
+
+            if candidates
+                .push(DecisionCandidate {
+                    psu: index,
+                    consumer_power_capability: capability,
+                })
+                .is_err()
+            {
+                warn!("Too many candidates for policy decision record, truncating");
+                break;
+            }
+        }
+
+        let chosen = chosen.and_then(|chosen| {
+            let index = self
+                .registration
+                .psus()
+                .iter()
+                .position(|psu| ptr::eq(*psu, chosen.psu))?;
+            Some(DecisionCandidate {
+                psu: index,
+                consumer_power_capability: chosen.consumer_power_capability,
+            })
+        });
+
+        if sender.try_send(PolicyDecision { candidates, chosen }).is_err() {
+            error!("Failed to send policy decision");
+        }
+    }
+
     /// Common logic to execute after a consumer is connected
+    ///
+    /// If `renegotiation` is true the chargers are still attached from the previous capability and only have
+    /// their input current limit updated.
     async fn post_consumer_connected(
         &mut self,
         connected_consumer: AvailableConsumer<'device, Reg::Psu>,
+        renegotiation: bool,
     ) -> Result<(), Error> {
         self.state.current_consumer_state = Some(connected_consumer);
         // todo: review the delay time
@@ -151,7 +224,8 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
         for node in self.registration.chargers() {
             let mut locked_charger = node.lock().await;
             // Chargers should be powered at this point, but in case they are not...
-            if locked_charger.state().is_unpowered() {
+            let was_unpowered = locked_charger.state().is_unpowered();
+            if was_unpowered {
                 // Force charger CheckReady and InitRequest to get it into an initialized state.
                 // This condition can get hit if we did not have a previous consumer and the charger is unpowered.
                 info!("Charger is unpowered, forcing charger CheckReady and Init sequence");
@@ -163,11 +237,24 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
                     .map_err(|e| Error::Charger(e.into()))?;
             }
 
-            // Attach and update state to new capability
-            locked_charger
-                .attach_handler(connected_consumer.consumer_power_capability)
-                .await
-                .map_err(|e| Error::Charger(e.into()))?;
+            // Attach and update state to new capability, chargers that were never detached only need to re-limit
+            let attach = !renegotiation || was_unpowered;
+            if attach {
+                locked_charger
+                    .attach_handler(connected_consumer.consumer_power_capability)
+                    .await
+                    .map_err(|e| Error::Charger(e.into()))?;
+            }
+
+            // A renegotiated capability may only change the voltage, don't re-limit if the current is unchanged
+            let current_ma = connected_consumer.consumer_power_capability.capability.current_ma;
+            if attach || locked_charger.state().input_current_limit() != Some(current_ma) {
+                locked_charger
+                    .set_input_current_limit(current_ma)
+                    .await
+                    .map_err(|e| Error::Charger(e.into()))?;
+                locked_charger.state_mut().on_input_current_limit(current_ma);
+            }
         }
         self.broadcast_event(ServiceEvent::ConsumerConnected(
             connected_consumer.psu,
@@ -194,6 +281,11 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
 
     /// Connect to a new consumer
     async fn connect_new_consumer(&mut self, new_consumer: AvailableConsumer<'device, Reg::Psu>) -> Result<(), Error> {
+        let renegotiation = self
+            .state
+            .current_consumer_state
+            .is_some_and(|current_consumer| ptr::eq(current_consumer.psu, new_consumer.psu));
+
         // Handle our current consumer
         if let Some(current_consumer) = self.state.current_consumer_state {
             if ptr::eq(current_consumer.psu, new_consumer.psu)
@@ -205,29 +297,36 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
             }
 
             self.state.current_consumer_state = None;
-            let mut current_psu = current_consumer.psu.lock().await;
-
-            if matches!(current_psu.state().psu_state, PsuState::ConnectedConsumer(_)) {
-                // Disconnect the current consumer if needed
-                info!("({}): Disconnecting current consumer", current_psu.name());
-                current_psu.disconnect().await?;
+            let mut result = Ok(());
+            {
+                let mut current_psu = current_consumer.psu.lock().await;
+                if matches!(current_psu.state().psu_state, PsuState::ConnectedConsumer(_)) {
+                    // Disconnect the current consumer if needed
+                    info!("({}): Disconnecting current consumer", current_psu.name());
+                    result = current_psu.disconnect().await;
+                }
             }
 
             // If no chargers are registered, they won't receive the new power capability.
             // Also, if chargers return UnpoweredAck, that means the charger isn't powered.
             // Further down this fn the power rails are enabled and thus the charger will get power,
             // so just continue execution.
-            self.disconnect_chargers().await?;
+            // Chargers stay attached when renegotiating and have their input current limit updated instead.
+            if result.is_ok() && !renegotiation {
+                result = self.disconnect_chargers().await;
+            }
 
             // Indicate why the current consumer is being disconnected. If we are reconnecting
             // the same device, it is renegotiating a new power capability. Otherwise, the service
-            // is switching to a different PSU.
-            let flags = if ptr::eq(current_consumer.psu, new_consumer.psu) {
+            // is switching to a different PSU. A failed disconnect is reported as a fault.
+            let flags = if renegotiation {
                 ConsumerDisconnect::none().with_renegotiation(true)
             } else {
                 ConsumerDisconnect::none().with_switching(true)
-            };
+            }
+            .with_fault(result.is_err());
             self.broadcast_event(ServiceEvent::ConsumerDisconnected(current_consumer.psu, flags));
+            result?;
 
             // Don't update the unconstrained here because this is a transitional state
         }
@@ -235,17 +334,35 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
         let mut psu = new_consumer.psu.lock().await;
         info!("({}): Connecting new consumer", psu.name());
 
-        if let e @ Err(_) = psu.state().can_connect_consumer() {
+        let result = if let e @ Err(_) = psu.state().can_connect_consumer(new_consumer.consumer_power_capability) {
             error!(
-                "({}): Not ready to connect consumer, state: {:#?}",
+                "({}): Cannot connect consumer, state: {:#?}",
                 psu.name(),
                 psu.state().psu_state
             );
             e
         } else {
-            psu.connect_consumer(new_consumer.consumer_power_capability).await?;
-            self.post_consumer_connected(new_consumer).await
+            psu.connect_consumer(new_consumer.consumer_power_capability).await
+        };
+
+        if let Err(e) = result {
+            // Chargers were left attached for the renegotiation, but there's no longer a consumer
+            if renegotiation {
+                self.disconnect_chargers().await?;
+            }
+            return Err(e);
         }
+
+        self.post_consumer_connected(new_consumer, renegotiation).await
+    }
+
+    /// Returns the consumer that [`Self::update_current_consumer`] would select given the current state
+    ///
+    /// This is a dry run: no PSU or charger is commanded and no events or policy decisions are sent.
+    pub async fn evaluate_best_consumer(&mut self) -> Result<Option<AvailableConsumer<'device, Reg::Psu>>, Error> {
+        self.customization
+            .find_best_consumer(&self.config, &self.state, &self.registration)
+            .await
     }
 
     /// Determines and connects the best external power
@@ -253,7 +370,8 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
     /// `disconnect_flags` describes the reason for a disconnect and is applied to the
     /// [`ServiceEvent::ConsumerDisconnected`] event when the current consumer is removed and not
     /// replaced by another one. When switching between consumers the flags are derived from the
-    /// switch itself (see [`Self::connect_new_consumer`]).
+    /// switch itself (see [`Self::connect_new_consumer`]). The `fault` flag is added if the
+    /// consumer couldn't be cleanly disconnected.
     pub(super) async fn update_current_consumer(&mut self, disconnect_flags: ConsumerDisconnect) -> Result<(), Error> {
         let current_consumer_name = if let Some(current_consumer) = self.state.current_consumer_state {
             current_consumer.psu.lock().await.name()
@@ -262,29 +380,26 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
         };
         info!("Selecting power port, current power: {:#?}", current_consumer_name);
 
-        let best_consumer = self
-            .customization
-            .find_best_consumer(&self.config, &self.state, &self.registration)
-            .await?;
+        let best_consumer = self.evaluate_best_consumer().await?;
         let best_consumer_name = if let Some(best_consumer) = best_consumer {
             best_consumer.psu.lock().await.name()
         } else {
             "None"
         };
         info!("Best consumer: {:#?}", best_consumer_name);
+        self.post_policy_decision(best_consumer).await;
         if let Some(best_consumer) = best_consumer {
             self.connect_new_consumer(best_consumer).await?;
         } else {
             // Notify disconnect if recently detached consumer was previously attached.
-            if let Some(current_consumer) = self.state.current_consumer_state {
-                self.disconnect_chargers().await?;
+            if let Some(current_consumer) = self.state.current_consumer_state.take() {
+                let result = self.disconnect_chargers().await;
                 self.broadcast_event(ServiceEvent::ConsumerDisconnected(
                     current_consumer.psu,
-                    disconnect_flags,
+                    disconnect_flags.with_fault(result.is_err()),
                 ));
+                result?;
             }
-            // No new consumer available
-            self.state.current_consumer_state = None;
         }
 
         self.update_unconstrained_state().await
@@ -6,15 +6,19 @@ pub mod consumer;
 pub mod customization;
 pub mod provider;
 pub mod registration;
+pub mod snapshot;
 pub mod task;
 
+use embassy_sync::channel::DynamicSender;
+use embassy_sync::signal::Signal;
+use embedded_services::GlobalRawMutex;
 use embedded_services::error;
 use embedded_services::named::Named;
-use embedded_services::{event::NonBlockingSender, info, sync::Lockable, trace};
+use embedded_services::{event::NonBlockingSender, info, sync::Lockable, trace, warn};
 
-use power_policy_interface::charger::{Charger, PsuState};
+use power_policy_interface::charger::{Charger, ChargerId, ChargingStatus, PsuState, State as ChargerState};
 use power_policy_interface::{
-    capability::{ConsumerDisconnect, ConsumerPowerCapability, ProviderPowerCapability},
+    capability::{ConsumerDisconnect, ConsumerPowerCapability, ProviderDisconnect, ProviderPowerCapability},
     charger::{Event as ChargerEvent, EventData as ChargerEventData},
     psu::{
         Error, Psu,
@@ -56,6 +60,30 @@ where
     }
 }
 
+/// Signal used to notify in-process listeners of [`UnconstrainedState`] changes
+///
+/// The service is typically accessed through a lock, this allows a listener to wait for
+/// changes without holding that lock.
+pub struct UnconstrainedSignal(Signal<GlobalRawMutex, UnconstrainedState>);
+
+impl UnconstrainedSignal {
+    /// Create a new unconstrained signal
+    pub const fn new() -> Self {
+        Self(Signal::new())
+    }
+
+    /// Wait for the next change in unconstrained state and return the new state
+    pub async fn wait_unconstrained_change(&self) -> UnconstrainedState {
+        self.0.wait().await
+    }
+}
+
+impl Default for UnconstrainedSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Power policy service
 pub struct Service<
     'device,
@@ -70,6 +98,10 @@ pub struct Service<
     config: config::Config,
     /// Customization
     customization: Customization,
+    /// Unconstrained state change signal
+    unconstrained_signal: Option<&'device UnconstrainedSignal>,
+    /// Policy decision record sender
+    decision_sender: Option<DynamicSender<'device, consumer::PolicyDecision>>,
 }
 
 impl<'device, Reg: Registration<'device>, Customization: customization::Customization + Default>
@@ -91,9 +123,30 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
             state: InternalState::default(),
             config,
             customization,
+            unconstrained_signal: None,
+            decision_sender: None,
         }
     }
 
+    /// Register a signal to be notified whenever the unconstrained state changes
+    pub fn register_unconstrained_signal(&mut self, signal: &'device UnconstrainedSignal) {
+        self.unconstrained_signal = Some(signal);
+    }
+
+    /// Register a sender to receive a [`consumer::PolicyDecision`] record each time a consumer is selected
+    ///
+    /// Records are only built while a sender is registered. They are dropped if the sender is full.
+    pub fn register_decision_sender(&mut self, sender: DynamicSender<'device, consumer::PolicyDecision>) {
+        self.decision_sender = Some(sender);
+    }
+
+    /// Returns true if an unconstrained consumer is currently connected
+    ///
+    /// Shorthand for [`UnconstrainedState::unconstrained`] when the full state isn't needed.
+    pub fn is_unconstrained(&self) -> bool {
+        self.state.unconstrained.unconstrained
+    }
+
     /// Returns the total amount of power that is being supplied to external devices
     pub async fn compute_total_provider_power_mw(&self) -> u32 {
         let mut total = 0;
@@ -116,8 +169,16 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
 
     async fn process_notify_detach(&mut self, device: &'device Reg::Psu) -> Result<(), Error> {
         info!("({}): Received notify detached", device.lock().await.name());
-        self.post_provider_removed(device).await;
-        self.update_current_consumer(ConsumerDisconnect::none()).await?;
+        self.post_provider_removed(device, ProviderDisconnect::none().with_unplugged(true))
+            .await;
+
+        // Only attribute a consumer disconnect to the detach if the detached device was the consumer
+        let was_consumer = self
+            .state
+            .current_consumer_state
+            .is_some_and(|current_consumer| ptr::eq(current_consumer.psu, device));
+        self.update_current_consumer(ConsumerDisconnect::none().with_unplugged(was_consumer))
+            .await?;
         Ok(())
     }
 
@@ -155,7 +216,7 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
         flags: ConsumerDisconnect,
     ) -> Result<(), Error> {
         info!("({}): Received notify disconnect", device.lock().await.name());
-        self.post_provider_removed(device).await;
+        self.post_provider_removed(device, flags.into()).await;
         self.update_current_consumer(flags).await?;
         Ok(())
     }
@@ -169,6 +230,78 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
         }
     }
 
+    /// Disconnect the current consumer and all connected providers
+    ///
+    /// Intended to be called before the system enters a low-power state. Every disconnect is attempted even if
+    /// an earlier one fails, the first error is returned. Internal state is reset so that later attach and
+    /// capability events are handled normally.
+    pub async fn shutdown(&mut self) -> Result<(), Error> {
+        info!("Shutting down power policy");
+        let mut result = Ok(());
+
+        if let Some(current_consumer) = self.state.current_consumer_state.take() {
+            {
+                let mut psu = current_consumer.psu.lock().await;
+                if matches!(
+                    psu.state().psu_state,
+                    power_policy_interface::psu::PsuState::ConnectedConsumer(_)
+                ) {
+                    info!("({}): Disconnecting consumer", psu.name());
+                    if let Err(e) = psu.disconnect().await {
+                        error!("({}): Failed to disconnect consumer: {:?}", psu.name(), e);
+                        result = result.and(Err(e));
+                    }
+                }
+            }
+
+            if let Err(e) = self.disconnect_chargers().await {
+                error!("Failed to disconnect chargers: {:?}", e);
+                result = result.and(Err(e));
+            }
+            self.broadcast_event(ServiceEvent::ConsumerDisconnected(
+                current_consumer.psu,
+                ConsumerDisconnect::none()
+                    .with_shutdown(true)
+                    .with_fault(result.is_err()),
+            ));
+        }
+
+        for i in 0..self.registration.psus().len() {
+            let Some(&psu) = self.registration.psus().get(i) else {
+                break;
+            };
+
+            if !self
+                .state
+                .connected_providers
+                .contains(&(psu as *const Reg::Psu as usize))
+            {
+                continue;
+            }
+
+            let disconnect_result = {
+                let mut locked_psu = psu.lock().await;
+                info!("({}): Disconnecting provider", locked_psu.name());
+                let disconnect_result = locked_psu.disconnect().await;
+                if let Err(e) = disconnect_result {
+                    error!("({}): Failed to disconnect provider: {:?}", locked_psu.name(), e);
+                    result = result.and(Err(e));
+                }
+                disconnect_result
+            };
+            self.post_provider_removed(
+                psu,
+                ProviderDisconnect::none()
+                    .with_shutdown(true)
+                    .with_fault(disconnect_result.is_err()),
+            )
+            .await;
+        }
+        self.state.connected_providers.clear();
+
+        result.and(self.update_unconstrained_state().await)
+    }
+
     pub async fn process_psu_event(&mut self, event: PsuEvent<'device, Reg::Psu>) -> Result<(), Error> {
         let device = event.psu;
         match event.event {
@@ -211,11 +344,103 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
         Ok(())
     }
 
+    /// Returns a snapshot of the state and cached capability of the charger registered at index `id`
+    ///
+    /// Returns `None` if no charger is registered with that ID.
+    pub async fn charger_state(&self, id: ChargerId) -> Option<ChargerState> {
+        let charger = self.registration.chargers().get(usize::from(id.0))?;
+        Some(*charger.lock().await.state())
+    }
+
+    /// Queries the charging status of the charger registered at index `id`
+    ///
+    /// Returns `Ok(None)` if the charger can't report its charging status.
+    pub async fn charging_status(&self, id: ChargerId) -> Result<Option<ChargingStatus>, Error> {
+        let charger = self
+            .registration
+            .chargers()
+            .get(usize::from(id.0))
+            .ok_or(Error::InvalidDevice)?;
+        charger
+            .lock()
+            .await
+            .charging_status()
+            .await
+            .map_err(|e| Error::Charger(e.into()))
+    }
+
+    /// Measures the input current of the charger registered at index `id` and compares it against its commanded
+    /// input current limit
+    ///
+    /// Broadcasts [`ServiceEvent::ChargerInputUnderDelivery`] the first time the measured current falls short of
+    /// the limit by more than [`input_current_tolerance_ma`](config::Config::input_current_tolerance_ma), see
+    /// [`ChargerState::on_input_current_measured`].
+    pub async fn check_charger_input_current(&mut self, id: ChargerId) -> Result<(), Error> {
+        let charger = self
+            .registration
+            .chargers()
+            .get(usize::from(id.0))
+            .ok_or(Error::InvalidDevice)?;
+        let mut locked_charger = charger.lock().await;
+        let Some(measured) = locked_charger
+            .input_current()
+            .await
+            .map_err(|e| Error::Charger(e.into()))?
+        else {
+            return Ok(());
+        };
+
+        let under_delivery = locked_charger
+            .state_mut()
+            .on_input_current_measured(measured, self.config.input_current_tolerance_ma);
+        drop(locked_charger);
+
+        if let Some(ChargerEventData::InputUnderDelivery {
+            expected_ma,
+            measured_ma,
+        }) = under_delivery
+        {
+            self.report_input_under_delivery(id, expected_ma, measured_ma);
+        }
+        Ok(())
+    }
+
+    /// Broadcast an input under-delivery of the charger registered at index `id`
+    fn report_input_under_delivery(&mut self, id: ChargerId, expected_ma: u16, measured_ma: u16) {
+        warn!(
+            "Charger {} input under-delivery, expected {} mA, measured {} mA",
+            id.0, expected_ma, measured_ma
+        );
+        self.broadcast_event(ServiceEvent::ChargerInputUnderDelivery {
+            charger: id,
+            expected_ma,
+            measured_ma,
+        });
+    }
+
+    /// Returns the ID of `charger` in the registration
+    fn charger_id(&self, charger: &Reg::Charger) -> Option<ChargerId> {
+        self.registration
+            .chargers()
+            .iter()
+            .position(|registered| ptr::eq(*registered, charger))
+            .and_then(|index| u8::try_from(index).ok())
+            .map(ChargerId)
+    }
+
     pub async fn process_charger_event(&mut self, event: ChargerEvent<'device, Reg::Charger>) -> Result<(), Error> {
         let charger = event.charger;
 
         match event.event {
             ChargerEventData::PsuStateChange(psu_state) => self.process_psu_state_change(charger, psu_state).await?,
+            ChargerEventData::InputUnderDelivery {
+                expected_ma,
+                measured_ma,
+            } => {
+                // Reported by a charger that checks its own input current
+                let id = self.charger_id(charger).ok_or(Error::InvalidDevice)?;
+                self.report_input_under_delivery(id, expected_ma, measured_ma);
+            }
             _ => {
                 return Err(Error::Charger(
                     power_policy_interface::charger::ChargerError::UnknownEvent,
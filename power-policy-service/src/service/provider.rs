@@ -2,18 +2,23 @@
 //! When total provided power is below [limited_power_threshold_mw](super::config::Config::limited_power_threshold_mw)
 //! the system is in unlimited power state. In this mode up to [provider_unlimited](super::config::Config::provider_unlimited)
 //! is provided to each device. Above this threshold, the system is in limited power state.
-//! In this mode [provider_limited](super::config::Config::provider_limited) is provided to each device
+//! In this mode [provider_limited](super::config::Config::provider_limited) is provided to each device.
+//! If [max_total_provider_mw](super::config::Config::max_total_provider_mw) is set, a request that would push the
+//! total provided power over this budget is rejected, or granted a reduced capability if
+//! [limit_provider_to_budget](super::config::Config::limit_provider_to_budget) is set.
 use core::ptr;
 
 use embedded_services::debug;
 use embedded_services::error;
 use embedded_services::named::Named;
+use power_policy_interface::capability::PowerCapability;
 
 use super::*;
 
 /// Current system provider power state
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum PowerState {
     /// System is capable of providing high power
     #[default]
@@ -29,6 +34,18 @@ pub struct State {
     state: PowerState,
 }
 
+impl State {
+    /// Create a new provider state
+    pub fn new(state: PowerState) -> Self {
+        Self { state }
+    }
+
+    /// Returns the current power state
+    pub fn power_state(&self) -> PowerState {
+        self.state
+    }
+}
+
 impl<'device, Reg: Registration<'device>, Customization: customization::Customization>
     Service<'device, Reg, Customization>
 {
@@ -49,6 +66,8 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
 
         // Determine total requested power draw
         let mut total_power_mw = 0;
+        // Power currently supplied to providers other than the requester
+        let mut other_providers_mw = 0;
         for psu in self.registration.psus() {
             let target_provider_cap = if ptr::eq(*psu, requester) {
                 // Use the requester's requested power capability
@@ -56,20 +75,21 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
                 Some(requested_power_capability)
             } else {
                 // Use the device's current working provider capability
-                psu.lock().await.state().connected_provider_capability()
+                let cap = psu.lock().await.state().connected_provider_capability();
+                other_providers_mw += cap.map_or(0, |cap| cap.capability.max_power_mw());
+                cap
             };
             total_power_mw += target_provider_cap.map_or(0, |cap| cap.capability.max_power_mw());
         }
 
-        if total_power_mw > self.config.limited_power_threshold_mw {
-            self.state.current_provider_state.state = PowerState::Limited;
+        // Only commit the new power state once the request has been accepted
+        let power_state = if total_power_mw > self.config.limited_power_threshold_mw {
+            PowerState::Limited
         } else {
-            self.state.current_provider_state.state = PowerState::Unlimited;
-        }
-
-        debug!("New power state: {:?}", self.state.current_provider_state.state);
+            PowerState::Unlimited
+        };
 
-        let target_power = match self.state.current_provider_state.state {
+        let mut target_power = match power_state {
             PowerState::Limited => ProviderPowerCapability {
                 capability: self.config.provider_limited,
                 flags: requested_power_capability.flags,
@@ -88,6 +108,40 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
             }
         };
 
+        if let Some(max_total_mw) = self.config.max_total_provider_mw {
+            let remaining_mw = max_total_mw.saturating_sub(other_providers_mw);
+            if target_power.capability.max_power_mw() > remaining_mw {
+                let voltage_mv = target_power.capability.voltage_mv;
+                let remaining = PowerCapability {
+                    voltage_mv,
+                    current_ma: remaining_mw
+                        .saturating_mul(1000)
+                        .checked_div(voltage_mv as u32)
+                        .unwrap_or(0)
+                        .try_into()
+                        .unwrap_or(u16::MAX),
+                };
+                if self.config.limit_provider_to_budget && remaining.current_ma > 0 {
+                    info!(
+                        "({}): Request exceeds total provider budget, limiting to: {:#?}",
+                        requester.lock().await.name(),
+                        remaining
+                    );
+                    target_power.capability = remaining;
+                } else {
+                    error!(
+                        "({}): Cannot provide, request exceeds total provider budget, remaining: {:#?}",
+                        requester.lock().await.name(),
+                        remaining
+                    );
+                    return Err(Error::CannotProvide(Some(ProviderPowerCapability {
+                        capability: remaining,
+                        flags: target_power.flags,
+                    })));
+                }
+            }
+        }
+
         let mut locked_requester = requester.lock().await;
         if let e @ Err(_) = locked_requester.state().can_connect_provider() {
             error!(
@@ -98,28 +152,50 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
             e
         } else {
             locked_requester.connect_provider(target_power).await?;
+            self.state.current_provider_state.state = power_state;
+            debug!("New power state: {:?}", power_state);
             self.post_provider_connected(requester, target_power);
             Ok(())
         }
     }
 
+    /// Returns the provider capability currently granted to `psu`, if it is connected as a provider
+    pub async fn provider_capability(&self, psu: &Reg::Psu) -> Option<ProviderPowerCapability> {
+        if !self
+            .state
+            .connected_providers
+            .contains(&(psu as *const Reg::Psu as usize))
+        {
+            return None;
+        }
+
+        psu.lock().await.state().connected_provider_capability()
+    }
+
     /// Common logic for after a provider has successfully connected
+    ///
+    /// Providers that were already connected are renegotiating, these broadcast a capability change instead of a new
+    /// connection.
     fn post_provider_connected(&mut self, requester: &'device Reg::Psu, target_power: ProviderPowerCapability) {
-        if self
+        match self
             .state
             .connected_providers
             .insert(requester as *const Reg::Psu as usize)
-            .is_err()
         {
-            error!("Tracked providers set is full");
+            Ok(true) => self.broadcast_event(ServiceEvent::ProviderConnected(requester, target_power)),
+            Ok(false) => self.broadcast_event(ServiceEvent::ProviderCapabilityChanged(requester, target_power)),
+            Err(_) => {
+                error!("Tracked providers set is full");
+                self.broadcast_event(ServiceEvent::ProviderConnected(requester, target_power));
+            }
         }
-        self.broadcast_event(ServiceEvent::ProviderConnected(requester, target_power));
     }
 
     /// Common logic for when a provider is removed
     ///
-    /// Returns true if the device was operating as a provider
-    pub(super) async fn post_provider_removed(&mut self, psu: &'device Reg::Psu) -> bool {
+    /// `flags` describes the reason for the disconnect and is applied to the [`ServiceEvent::ProviderDisconnected`]
+    /// event. Returns true if the device was operating as a provider
+    pub(super) async fn post_provider_removed(&mut self, psu: &'device Reg::Psu, flags: ProviderDisconnect) -> bool {
         if self
             .state
             .connected_providers
@@ -138,7 +214,7 @@ impl<'device, Reg: Registration<'device>, Customization: customization::Customiz
                 self.state.current_provider_state.state = PowerState::Unlimited;
             }
 
-            self.broadcast_event(ServiceEvent::ProviderDisconnected(psu));
+            self.broadcast_event(ServiceEvent::ProviderDisconnected(psu, flags));
             true
         } else {
             false
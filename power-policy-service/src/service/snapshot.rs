@@ -0,0 +1,96 @@
+//! Export and restore of the service's internal state
+//!
+//! Used to carry policy decisions across a warm reset where the devices keep their connections but the service is
+//! recreated. Devices are identified by their index in the [`Registration`], so a snapshot can only be restored into a
+//! service built over the same registration.
+use core::ptr;
+
+use embedded_services::error;
+
+use super::*;
+
+/// Snapshot of the power policy internal state
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StateSnapshot {
+    /// Index and capability of the current consumer, if any
+    pub current_consumer: Option<(usize, ConsumerPowerCapability)>,
+    /// Current provider power state
+    pub provider_state: provider::PowerState,
+    /// System unconstrained power
+    pub unconstrained: UnconstrainedState,
+    /// Indices of connected providers, in registration order
+    pub connected_providers: heapless::Vec<usize, MAX_CONNECTED_PROVIDERS>,
+}
+
+impl<'device, Reg: Registration<'device>, Customization: customization::Customization>
+    Service<'device, Reg, Customization>
+{
+    /// Returns the index of `psu` in the registration
+    fn psu_index(&self, psu: &Reg::Psu) -> Option<usize> {
+        self.registration
+            .psus()
+            .iter()
+            .position(|registered| ptr::eq(*registered, psu))
+    }
+
+    /// Export the current internal state
+    pub fn export_state(&self) -> StateSnapshot {
+        let mut snapshot = StateSnapshot {
+            current_consumer: self.state.current_consumer_state.and_then(|consumer| {
+                self.psu_index(consumer.psu)
+                    .map(|index| (index, consumer.consumer_power_capability))
+            }),
+            provider_state: self.state.current_provider_state.power_state(),
+            unconstrained: self.state.unconstrained,
+            connected_providers: heapless::Vec::new(),
+        };
+
+        for (index, psu) in self.registration.psus().iter().enumerate() {
+            if self
+                .state
+                .connected_providers
+                .contains(&(*psu as *const Reg::Psu as usize))
+                && snapshot.connected_providers.push(index).is_err()
+            {
+                error!("Connected providers snapshot is full");
+                break;
+            }
+        }
+
+        snapshot
+    }
+
+    /// Restore internal state from a snapshot produced by [`Self::export_state`]
+    ///
+    /// Devices are not contacted and no events are broadcast, the snapshot is assumed to describe the current state of
+    /// the devices. Returns [`Error::InvalidDevice`] without modifying the state if the snapshot refers to a device
+    /// that is not registered.
+    pub fn import_state(&mut self, snapshot: &StateSnapshot) -> Result<(), Error> {
+        let psus = self.registration.psus();
+
+        let current_consumer_state = match snapshot.current_consumer {
+            Some((index, consumer_power_capability)) => Some(consumer::AvailableConsumer {
+                psu: *psus.get(index).ok_or(Error::InvalidDevice)?,
+                consumer_power_capability,
+            }),
+            None => None,
+        };
+
+        let mut connected_providers = heapless::index_set::FnvIndexSet::new();
+        for index in &snapshot.connected_providers {
+            let psu = *psus.get(*index).ok_or(Error::InvalidDevice)?;
+            // Snapshot and set have the same capacity
+            let _ = connected_providers.insert(psu as *const Reg::Psu as usize);
+        }
+
+        self.state = InternalState {
+            current_consumer_state,
+            current_provider_state: provider::State::new(snapshot.provider_state),
+            unconstrained: snapshot.unconstrained,
+            connected_providers,
+        };
+        Ok(())
+    }
+}
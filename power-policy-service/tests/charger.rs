@@ -0,0 +1,389 @@
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+use std::pin::pin;
+
+use embassy_futures::poll_once;
+use embedded_services::sync::Lockable;
+use power_policy_interface::capability::{ConsumerDisconnect, ConsumerPowerCapability, PowerCapability};
+use power_policy_interface::charger::Charger as _;
+use power_policy_interface::charger::{ChargerId, ChargingStatus, InternalState, PoweredSubstate, PsuState};
+use power_policy_interface::service::event::Event as ServiceEvent;
+use power_policy_interface_test_mocks::charger::FnCall as ChargerFnCall;
+use power_policy_interface_test_mocks::psu::FnCall;
+use power_policy_service::service::config::Config;
+
+mod common;
+
+use common::{ChargerTest, ChargerTestContext, HIGH_POWER, LOW_POWER, run_charger_test};
+
+/// Allowed shortfall between the commanded and measured input current
+const INPUT_CURRENT_TOLERANCE_MA: u16 = 200;
+
+/// Test that a charger has its input current limit re-applied when the consumer renegotiates a lower capability,
+/// and left alone when the renegotiated capability has the same current.
+struct TestChargerInputCurrentLimit;
+
+impl ChargerTest for TestChargerInputCurrentLimit {
+    async fn run<'device, 'sender>(&mut self, context: ChargerTestContext<'device, 'sender>) {
+        let ChargerTestContext {
+            mut service,
+            service_receiver,
+            psu,
+            mut psu_receivers,
+            charger,
+            ..
+        } = context;
+
+        // Start with a powered charger so the service doesn't run the init sequence
+        charger.lock().await.state_mut().on_ready_success();
+
+        // Initial connection attaches the charger and applies the limit
+        {
+            psu.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            {
+                let mut charger = charger.lock().await;
+                charger.next_result_attach_handler.push_back(Ok(()));
+                charger.next_result_set_input_current_limit.push_back(Ok(()));
+            }
+            psu.lock().await.simulate_consumer_connection(HIGH_POWER.into()).await;
+
+            // Attach and capability update events
+            for _ in 0..2 {
+                service
+                    .process_psu_event(psu_receivers.wait_event().await)
+                    .await
+                    .unwrap();
+            }
+
+            let ServiceEvent::ConsumerConnected(_, capability) = service_receiver.receive().await else {
+                panic!("Expected ConsumerConnected event");
+            };
+            assert_eq!(capability, HIGH_POWER.into());
+
+            let mut charger = charger.lock().await;
+            assert_eq!(
+                charger.fn_calls.pop_front().unwrap(),
+                ChargerFnCall::AttachHandler(HIGH_POWER.into())
+            );
+            assert_eq!(
+                charger.fn_calls.pop_front().unwrap(),
+                ChargerFnCall::SetInputCurrentLimit(HIGH_POWER.current_ma)
+            );
+            assert!(charger.fn_calls.is_empty());
+        }
+
+        // Capability drops, the charger should be re-limited without a detach/attach
+        {
+            {
+                let mut psu = psu.lock().await;
+                psu.fn_calls.clear();
+                psu.next_result_disconnect.push_back(Ok(()));
+                psu.next_result_connect_consumer.push_back(Ok(()));
+            }
+            charger
+                .lock()
+                .await
+                .next_result_set_input_current_limit
+                .push_back(Ok(()));
+            psu.lock()
+                .await
+                .simulate_update_consumer_power_capability(Some(LOW_POWER.into()))
+                .await;
+
+            service
+                .process_psu_event(psu_receivers.wait_event().await)
+                .await
+                .unwrap();
+
+            let ServiceEvent::ConsumerDisconnected(_, flags) = service_receiver.receive().await else {
+                panic!("Expected ConsumerDisconnected event");
+            };
+            assert_eq!(flags, ConsumerDisconnect::none().with_renegotiation(true));
+            let ServiceEvent::ConsumerConnected(_, capability) = service_receiver.receive().await else {
+                panic!("Expected ConsumerConnected event");
+            };
+            assert_eq!(capability, ConsumerPowerCapability::from(LOW_POWER));
+
+            {
+                let mut psu = psu.lock().await;
+                assert_eq!(psu.fn_calls.pop_front().unwrap(), FnCall::Disconnect);
+                assert_eq!(
+                    psu.fn_calls.pop_front().unwrap(),
+                    FnCall::ConnectConsumer(LOW_POWER.into())
+                );
+                assert!(psu.fn_calls.is_empty());
+            }
+
+            let mut charger = charger.lock().await;
+            assert_eq!(
+                charger.fn_calls.pop_front().unwrap(),
+                ChargerFnCall::SetInputCurrentLimit(LOW_POWER.current_ma)
+            );
+            assert!(charger.fn_calls.is_empty());
+        }
+
+        // Only the voltage changes, the input current limit is already correct
+        {
+            let same_current = PowerCapability {
+                voltage_mv: 9000,
+                current_ma: LOW_POWER.current_ma,
+            };
+            {
+                let mut psu = psu.lock().await;
+                psu.fn_calls.clear();
+                psu.next_result_disconnect.push_back(Ok(()));
+                psu.next_result_connect_consumer.push_back(Ok(()));
+            }
+            psu.lock()
+                .await
+                .simulate_update_consumer_power_capability(Some(same_current.into()))
+                .await;
+
+            service
+                .process_psu_event(psu_receivers.wait_event().await)
+                .await
+                .unwrap();
+
+            let ServiceEvent::ConsumerDisconnected(..) = service_receiver.receive().await else {
+                panic!("Expected ConsumerDisconnected event");
+            };
+            let ServiceEvent::ConsumerConnected(_, capability) = service_receiver.receive().await else {
+                panic!("Expected ConsumerConnected event");
+            };
+            assert_eq!(capability, ConsumerPowerCapability::from(same_current));
+            assert!(charger.lock().await.fn_calls.is_empty());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_charger_input_current_limit() {
+    run_charger_test(TestChargerInputCurrentLimit, Config::default()).await;
+}
+
+/// Test that a charger reporting low input current results in a single under-delivery event.
+struct TestChargerInputUnderDelivery;
+
+impl ChargerTest for TestChargerInputUnderDelivery {
+    async fn run<'device, 'sender>(&mut self, context: ChargerTestContext<'device, 'sender>) {
+        let ChargerTestContext {
+            mut service,
+            service_receiver,
+            psu,
+            mut psu_receivers,
+            charger,
+            ..
+        } = context;
+
+        // Start with a powered charger so the service doesn't run the init sequence
+        charger.lock().await.state_mut().on_ready_success();
+
+        // Connect a consumer so the service commands an input current limit
+        psu.lock().await.next_result_connect_consumer.push_back(Ok(()));
+        {
+            let mut charger = charger.lock().await;
+            charger.next_result_attach_handler.push_back(Ok(()));
+            charger.next_result_set_input_current_limit.push_back(Ok(()));
+        }
+        psu.lock().await.simulate_consumer_connection(LOW_POWER.into()).await;
+        for _ in 0..2 {
+            service
+                .process_psu_event(psu_receivers.wait_event().await)
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            service_receiver.receive().await,
+            ServiceEvent::ConsumerConnected(_, _)
+        ));
+        assert_eq!(
+            charger.lock().await.state().input_current_limit(),
+            Some(LOW_POWER.current_ma)
+        );
+        psu.lock().await.fn_calls.clear();
+        charger.lock().await.fn_calls.clear();
+
+        // Within tolerance of the limit, no event
+        charger
+            .lock()
+            .await
+            .next_result_input_current
+            .push_back(Ok(Some(LOW_POWER.current_ma - INPUT_CURRENT_TOLERANCE_MA)));
+        service.check_charger_input_current(ChargerId(0)).await.unwrap();
+        assert!(service_receiver.try_receive().is_err());
+
+        // Report low input current twice, the event should only fire once
+        {
+            let mut charger = charger.lock().await;
+            charger.next_result_input_current.push_back(Ok(Some(1000)));
+            charger.next_result_input_current.push_back(Ok(Some(900)));
+        }
+        service.check_charger_input_current(ChargerId(0)).await.unwrap();
+        service.check_charger_input_current(ChargerId(0)).await.unwrap();
+
+        let ServiceEvent::ChargerInputUnderDelivery {
+            charger: id,
+            expected_ma,
+            measured_ma,
+        } = service_receiver.try_receive().unwrap()
+        else {
+            panic!("Expected ChargerInputUnderDelivery event");
+        };
+        assert_eq!(id, ChargerId(0));
+        assert_eq!(expected_ma, LOW_POWER.current_ma);
+        assert_eq!(measured_ma, 1000);
+        assert!(service_receiver.try_receive().is_err());
+
+        // The service should only measure, not act on the PSUs
+        assert_eq!(charger.lock().await.fn_calls.len(), 3);
+        assert!(psu.lock().await.fn_calls.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn test_charger_input_under_delivery() {
+    let mut config = Config::default();
+    config.input_current_tolerance_ma = INPUT_CURRENT_TOLERANCE_MA;
+    run_charger_test(TestChargerInputUnderDelivery, config).await;
+}
+
+/// Test that the charger state can be queried through the service as the charger moves through its states.
+struct TestChargerState;
+
+impl ChargerTest for TestChargerState {
+    async fn run<'device, 'sender>(&mut self, context: ChargerTestContext<'device, 'sender>) {
+        let ChargerTestContext { service, charger, .. } = context;
+
+        // Charger starts unpowered
+        let state = service.charger_state(ChargerId(0)).await.unwrap();
+        assert_eq!(*state.internal_state(), InternalState::Unpowered);
+        assert_eq!(*state.capability(), None);
+
+        charger.lock().await.state_mut().on_ready_success();
+        let state = service.charger_state(ChargerId(0)).await.unwrap();
+        assert_eq!(*state.internal_state(), InternalState::Powered(PoweredSubstate::Init));
+        assert_eq!(*state.capability(), None);
+
+        {
+            let mut charger = charger.lock().await;
+            charger.state_mut().on_initialized(PsuState::Attached).unwrap();
+            charger.state_mut().on_policy_attach(HIGH_POWER.into());
+        }
+        let state = service.charger_state(ChargerId(0)).await.unwrap();
+        assert_eq!(
+            *state.internal_state(),
+            InternalState::Powered(PoweredSubstate::PsuAttached)
+        );
+        assert_eq!(*state.capability(), Some(HIGH_POWER.into()));
+        assert_eq!(state, *charger.lock().await.state());
+
+        // No charger registered with this ID
+        assert!(service.charger_state(ChargerId(1)).await.is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_charger_state() {
+    run_charger_test(TestChargerState, Config::default()).await;
+}
+
+/// Test that the charging status reported by a charger is surfaced through the service.
+struct TestChargingStatus;
+
+impl ChargerTest for TestChargingStatus {
+    async fn run<'device, 'sender>(&mut self, context: ChargerTestContext<'device, 'sender>) {
+        let ChargerTestContext { service, charger, .. } = context;
+
+        {
+            let mut charger = charger.lock().await;
+            charger
+                .next_result_charging_status
+                .push_back(Ok(Some(ChargingStatus::Charging { current_ma: 1500 })));
+            charger
+                .next_result_charging_status
+                .push_back(Ok(Some(ChargingStatus::NotCharging)));
+            charger
+                .next_result_charging_status
+                .push_back(Ok(Some(ChargingStatus::Fault)));
+            charger.next_result_charging_status.push_back(Ok(None));
+        }
+
+        assert_eq!(
+            service.charging_status(ChargerId(0)).await,
+            Ok(Some(ChargingStatus::Charging { current_ma: 1500 }))
+        );
+        assert_eq!(
+            service.charging_status(ChargerId(0)).await,
+            Ok(Some(ChargingStatus::NotCharging))
+        );
+        assert_eq!(
+            service.charging_status(ChargerId(0)).await,
+            Ok(Some(ChargingStatus::Fault))
+        );
+        // Charger can't report its status
+        assert_eq!(service.charging_status(ChargerId(0)).await, Ok(None));
+
+        {
+            let mut charger = charger.lock().await;
+            for _ in 0..4 {
+                assert_eq!(charger.fn_calls.pop_front().unwrap(), ChargerFnCall::ChargingStatus);
+            }
+            assert!(charger.fn_calls.is_empty());
+        }
+
+        // No charger registered with this ID
+        assert_eq!(
+            service.charging_status(ChargerId(1)).await,
+            Err(power_policy_interface::psu::Error::InvalidDevice)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_charging_status() {
+    run_charger_test(TestChargingStatus, Config::default()).await;
+}
+
+/// Test that waiting for a charger to become ready only completes once the charger is initialized, and completes
+/// immediately for every waiter once it is.
+struct TestChargerWaitReady;
+
+impl ChargerTest for TestChargerWaitReady {
+    async fn run<'device, 'sender>(&mut self, context: ChargerTestContext<'device, 'sender>) {
+        let ChargerTestContext { charger, .. } = context;
+
+        // Charger starts unpowered
+        let mut wait_ready = pin!(charger.wait_ready());
+        assert!(charger.lock().await.state().is_unpowered());
+        assert!(poll_once(wait_ready.as_mut()).is_pending());
+
+        // CheckReady powers the charger, but it isn't initialized yet
+        {
+            let mut charger = charger.lock().await;
+            charger.next_result_is_ready.push_back(Ok(()));
+            charger.is_ready().await.unwrap();
+        }
+        assert!(poll_once(wait_ready.as_mut()).is_pending());
+
+        // Init completes the transition
+        {
+            let mut charger = charger.lock().await;
+            charger.next_result_init_charger.push_back(Ok(PsuState::Attached));
+            charger.init_charger().await.unwrap();
+            assert_eq!(
+                *charger.state().internal_state(),
+                InternalState::Powered(PoweredSubstate::PsuAttached)
+            );
+        }
+        assert!(poll_once(wait_ready.as_mut()).is_ready());
+
+        // Waiting on a charger that is already ready completes immediately
+        assert!(poll_once(charger.wait_ready()).is_ready());
+        assert!(poll_once(charger.wait_ready()).is_ready());
+    }
+}
+
+#[tokio::test]
+async fn test_charger_wait_ready() {
+    run_charger_test(TestChargerWaitReady, Config::default()).await;
+}
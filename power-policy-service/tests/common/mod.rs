@@ -14,13 +14,17 @@ use embassy_sync::{
 };
 use embassy_time::{Duration, with_timeout};
 use embedded_services::GlobalRawMutex;
+use power_policy_interface::charger::{Device, event::EventData as ChargerEventData};
 use power_policy_interface::psu::event::EventData;
 use power_policy_interface::{
-    capability::{ConsumerDisconnect, ConsumerPowerCapability, PowerCapability, ProviderPowerCapability},
+    capability::{
+        ConsumerDisconnect, ConsumerPowerCapability, PowerCapability, ProviderDisconnect, ProviderPowerCapability,
+    },
     service::{UnconstrainedState, event::Event as ServiceEvent},
 };
-use power_policy_interface_test_mocks::charger::ChargerType;
+use power_policy_interface_test_mocks::charger::{ChargerType, Mock as ChargerMock};
 use power_policy_interface_test_mocks::psu::Mock;
+use power_policy_service::charger::ChargerEventReceivers;
 use power_policy_service::service::{Service, config::Config, customization};
 use power_policy_service::{psu::PsuEventReceivers, service::registration::ArrayRegistration};
 
@@ -66,6 +70,23 @@ pub type ServiceType<'device, 'sender, Customization> = Service<
 pub type ServiceMutex<'device, 'sender, Customization> =
     Mutex<GlobalRawMutex, ServiceType<'device, 'sender, Customization>>;
 
+pub type ChargerDeviceType<'a> = ChargerType<DynamicSender<'a, ChargerEventData>>;
+
+/// Service with a single PSU and a single charger
+pub type ChargerServiceType<'device, 'sender> = Service<
+    'device,
+    ArrayRegistration<
+        'device,
+        DeviceType<'device>,
+        1,
+        DynamicSender<'sender, ServiceEvent<'device, DeviceType<'device>>>,
+        1,
+        ChargerDeviceType<'device>,
+        1,
+    >,
+    customization::DefaultCustomization,
+>;
+
 async fn power_policy_task<'device, 'sender, const N: usize, Customization: customization::Customization>(
     completion_signal: &'device embassy_sync::signal::Signal<GlobalRawMutex, ()>,
     power_policy: &ServiceMutex<'device, 'sender, Customization>,
@@ -151,6 +172,57 @@ pub async fn run_test<T: Test>(timeout: Duration, mut test: T, config: Config, c
     .unwrap();
 }
 
+/// Service, devices and event receivers for a test with a single PSU and a single charger.
+pub struct ChargerTestContext<'device, 'sender> {
+    pub service: ChargerServiceType<'device, 'sender>,
+    pub service_receiver: DynamicReceiver<'sender, ServiceEvent<'device, DeviceType<'device>>>,
+    pub psu: &'device DeviceType<'device>,
+    pub psu_receivers: PsuEventReceivers<'device, 1, DeviceType<'device>, DynamicReceiver<'device, EventData>>,
+    pub charger: &'device ChargerDeviceType<'device>,
+    pub charger_receivers:
+        ChargerEventReceivers<'device, 1, ChargerDeviceType<'device>, DynamicReceiver<'device, ChargerEventData>>,
+}
+
+/// Trait for tests with a single PSU and a single charger, see [`run_charger_test`].
+pub trait ChargerTest {
+    fn run<'device, 'sender>(&mut self, context: ChargerTestContext<'device, 'sender>) -> impl Future<Output = ()>;
+}
+
+/// Run `test` against a service with a single PSU and a single charger, the charger starts unpowered.
+pub async fn run_charger_test<T: ChargerTest>(mut test: T, config: Config) {
+    embedded_services::init().await;
+
+    let psu_event_channel: Channel<GlobalRawMutex, EventData, EVENT_CHANNEL_SIZE> = Channel::new();
+    let psu = Mutex::new(Mock::new("PSU0", psu_event_channel.dyn_sender()));
+
+    let charger_event_channel: Channel<GlobalRawMutex, ChargerEventData, EVENT_CHANNEL_SIZE> = Channel::new();
+    let charger: ChargerDeviceType<'_> = Device::new(ChargerMock::new(charger_event_channel.dyn_sender()));
+
+    // Declared after the devices since the events it holds reference them
+    let service_event_channel: Channel<GlobalRawMutex, ServiceEvent<'_, DeviceType<'_>>, EVENT_CHANNEL_SIZE> =
+        Channel::new();
+
+    let service = Service::new_with_customization(
+        ArrayRegistration {
+            psus: [&psu],
+            service_senders: [service_event_channel.dyn_sender()],
+            chargers: [&charger],
+        },
+        config,
+        customization::DefaultCustomization,
+    );
+
+    test.run(ChargerTestContext {
+        service,
+        service_receiver: service_event_channel.dyn_receiver(),
+        psu: &psu,
+        psu_receivers: PsuEventReceivers::new([&psu], [psu_event_channel.dyn_receiver()]),
+        charger: &charger,
+        charger_receivers: ChargerEventReceivers::new([&charger], [charger_event_channel.dyn_receiver()]),
+    })
+    .await;
+}
+
 pub async fn assert_consumer_disconnected<'a>(
     receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
     expected_device: &DeviceType<'a>,
@@ -189,12 +261,24 @@ pub async fn assert_provider_disconnected<'a>(
     receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
     expected_device: &DeviceType<'a>,
 ) {
-    let ServiceEvent::ProviderDisconnected(device) = receiver.receive().await else {
+    let ServiceEvent::ProviderDisconnected(device, _) = receiver.receive().await else {
         panic!("Expected ProviderDisconnected event");
     };
     assert_eq!(device as *const _, expected_device as *const _);
 }
 
+pub async fn assert_provider_disconnected_with_flags<'a>(
+    receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+    expected_device: &DeviceType<'a>,
+    expected_flags: ProviderDisconnect,
+) {
+    let ServiceEvent::ProviderDisconnected(device, flags) = receiver.receive().await else {
+        panic!("Expected ProviderDisconnected event");
+    };
+    assert_eq!(device as *const _, expected_device as *const _);
+    assert_eq!(flags, expected_flags);
+}
+
 pub async fn assert_provider_connected<'a>(
     receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
     expected_device: &DeviceType<'a>,
@@ -207,6 +291,18 @@ pub async fn assert_provider_connected<'a>(
     assert_eq!(capability, expected_capability);
 }
 
+pub async fn assert_provider_capability_changed<'a>(
+    receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+    expected_device: &DeviceType<'a>,
+    expected_capability: ProviderPowerCapability,
+) {
+    let ServiceEvent::ProviderCapabilityChanged(device, capability) = receiver.receive().await else {
+        panic!("Expected ProviderCapabilityChanged event");
+    };
+    assert_eq!(device as *const _, expected_device as *const _);
+    assert_eq!(capability, expected_capability);
+}
+
 pub async fn assert_unconstrained<'a>(
     receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
     expected_state: UnconstrainedState,
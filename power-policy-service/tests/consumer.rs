@@ -10,6 +10,10 @@ mod common;
 
 use common::{LOW_POWER, ServiceMutex};
 use power_policy_interface::psu::Psu;
+use power_policy_interface::psu::{
+    Error as PsuError,
+    event::{Event as PsuEvent, EventData as PsuEventData},
+};
 use power_policy_interface::service::event::Event as ServiceEvent;
 use power_policy_service::service::InternalState;
 use power_policy_service::service::config::Config;
@@ -557,7 +561,7 @@ impl Test for TestMinConsumerPower {
         service: &ServiceMutex<'a, 'a, Self::Customization>,
         service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
         device0: &DeviceType<'a>,
-        _device1: &DeviceType<'a>,
+        device1: &DeviceType<'a>,
     ) {
         info!("Running test_min_consumer_power");
         // Connect with power below the minimum threshold.
@@ -579,6 +583,37 @@ impl Test for TestMinConsumerPower {
 
         // Service shouldn't broadcast any events in this case.
         assert_no_event(service_receiver);
+
+        // Connect another consumer at the minimum threshold, it should be selected over the sub-threshold one.
+        {
+            device1.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device1
+                .lock()
+                .await
+                .simulate_consumer_connection(LOW_POWER.into())
+                .await;
+
+            assert_consumer_connected(
+                service_receiver,
+                device1,
+                ConsumerPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ConsumerFlags::none(),
+                },
+            )
+            .await;
+
+            assert_eq!(
+                device1.lock().await.fn_calls.pop_front().unwrap(),
+                FnCall::ConnectConsumer(ConsumerPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ConsumerFlags::none(),
+                })
+            );
+            assert!(device0.lock().await.fn_calls.is_empty());
+        }
+
+        assert_no_event(service_receiver);
     }
 }
 
@@ -945,6 +980,129 @@ impl Test for TestConsumerDisconnectRenegotiationFlag {
     }
 }
 
+/// Test that detaching the current consumer with no replacement sets the `unplugged` flag on the
+/// [`ServiceEvent::ConsumerDisconnected`] event.
+struct TestConsumerDisconnectUnpluggedFlag;
+
+impl Test for TestConsumerDisconnectUnpluggedFlag {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        _service: &ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &DeviceType<'a>,
+        _device1: &DeviceType<'a>,
+    ) {
+        info!("Running test_consumer_disconnect_unplugged_flag");
+        // Connect device0 at low power.
+        device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+        device0
+            .lock()
+            .await
+            .simulate_consumer_connection(LOW_POWER.into())
+            .await;
+        assert_consumer_connected(
+            service_receiver,
+            device0,
+            ConsumerPowerCapability {
+                capability: LOW_POWER,
+                flags: ConsumerFlags::none(),
+            },
+        )
+        .await;
+        device0.lock().await.fn_calls.clear();
+
+        // Unplug device0, the disconnect should be attributed to the detach rather than a policy switch.
+        device0.lock().await.simulate_detach().await;
+        assert_consumer_disconnected_with_flags(
+            service_receiver,
+            device0,
+            ConsumerDisconnect::none().with_unplugged(true),
+        )
+        .await;
+
+        // Power policy shouldn't call any functions on detach
+        assert!(device0.lock().await.fn_calls.is_empty());
+
+        assert_no_event(service_receiver);
+    }
+}
+
+/// Test that failing to disconnect the current consumer while switching to a better one sets the `fault` flag on the
+/// [`ServiceEvent::ConsumerDisconnected`] event.
+struct TestConsumerDisconnectFaultFlag;
+
+impl Test for TestConsumerDisconnectFaultFlag {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        service: &ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &DeviceType<'a>,
+        device1: &DeviceType<'a>,
+    ) {
+        info!("Running test_consumer_disconnect_fault_flag");
+        // Connect device0 at low power.
+        device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+        device0
+            .lock()
+            .await
+            .simulate_consumer_connection(LOW_POWER.into())
+            .await;
+        assert_consumer_connected(
+            service_receiver,
+            device0,
+            ConsumerPowerCapability {
+                capability: LOW_POWER,
+                flags: ConsumerFlags::none(),
+            },
+        )
+        .await;
+        device0.lock().await.fn_calls.clear();
+
+        // Device1 offers more power but device0 fails to disconnect. The event is processed directly instead of
+        // through the event loop so the error can be observed.
+        device0
+            .lock()
+            .await
+            .next_result_disconnect
+            .push_back(Err(PsuError::Failed));
+        {
+            let mut device1 = device1.lock().await;
+            device1.state.attach().unwrap();
+            device1
+                .state
+                .update_consumer_power_capability(Some(HIGH_POWER.into()))
+                .unwrap();
+        }
+        let result = service
+            .lock()
+            .await
+            .process_psu_event(PsuEvent {
+                psu: device1,
+                event: PsuEventData::UpdatedConsumerCapability(Some(HIGH_POWER.into())),
+            })
+            .await;
+        assert_eq!(result, Err(PsuError::Failed));
+
+        assert_consumer_disconnected_with_flags(
+            service_receiver,
+            device0,
+            ConsumerDisconnect::none().with_switching(true).with_fault(true),
+        )
+        .await;
+
+        // The service gave up on the switch, device1 isn't connected
+        assert_eq!(device0.lock().await.fn_calls.pop_front().unwrap(), FnCall::Disconnect);
+        assert!(device0.lock().await.fn_calls.is_empty());
+        assert!(device1.lock().await.fn_calls.is_empty());
+
+        assert_no_event(service_receiver);
+    }
+}
+
 #[tokio::test]
 async fn run_test_swap_higher() {
     run_test(
@@ -1039,3 +1197,25 @@ async fn run_test_consumer_disconnect_renegotiation_flag() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn run_test_consumer_disconnect_unplugged_flag() {
+    run_test(
+        DEFAULT_TIMEOUT,
+        TestConsumerDisconnectUnpluggedFlag,
+        Default::default(),
+        DefaultCustomization,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn run_test_consumer_disconnect_fault_flag() {
+    run_test(
+        DEFAULT_TIMEOUT,
+        TestConsumerDisconnectFaultFlag,
+        Default::default(),
+        DefaultCustomization,
+    )
+    .await;
+}
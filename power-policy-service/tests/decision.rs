@@ -0,0 +1,219 @@
+#![allow(clippy::unwrap_used)]
+use std::ptr;
+
+use embassy_sync::channel::{Channel, DynamicReceiver};
+use embassy_sync::mutex::Mutex;
+use embedded_services::GlobalRawMutex;
+use power_policy_interface::capability::ConsumerPowerCapability;
+use power_policy_interface::psu::event::EventData;
+use power_policy_interface::service::event::Event as ServiceEvent;
+use power_policy_interface_test_mocks::psu::Mock;
+use power_policy_service::psu::PsuEventReceivers;
+use power_policy_service::service::Service;
+use power_policy_service::service::config::Config;
+use power_policy_service::service::consumer::{DecisionCandidate, PolicyDecision};
+use power_policy_service::service::customization::DefaultCustomization;
+use power_policy_service::service::registration::ArrayRegistration;
+
+mod common;
+
+use common::{DeviceType, HIGH_POWER, LOW_POWER, ServiceType};
+
+const EVENT_CHANNEL_SIZE: usize = 4;
+
+/// Service, devices and receivers for a test with two PSUs and a registered decision sender
+struct DecisionTestContext<'device, 'sender> {
+    service: ServiceType<'device, 'sender, DefaultCustomization>,
+    service_receiver: DynamicReceiver<'sender, ServiceEvent<'device, DeviceType<'device>>>,
+    decision_receiver: DynamicReceiver<'device, PolicyDecision>,
+    psu_receivers: PsuEventReceivers<'device, 2, DeviceType<'device>, DynamicReceiver<'device, EventData>>,
+    device0: &'device DeviceType<'device>,
+    device1: &'device DeviceType<'device>,
+}
+
+/// Trait for tests run by [`run_decision_test`]
+trait DecisionTest {
+    fn run<'device, 'sender>(&mut self, context: DecisionTestContext<'device, 'sender>) -> impl Future<Output = ()>;
+}
+
+/// Run `test` against a service with two PSUs, events are processed by the test itself
+async fn run_decision_test<T: DecisionTest>(mut test: T) {
+    embedded_services::init().await;
+
+    let decision_channel: Channel<GlobalRawMutex, PolicyDecision, EVENT_CHANNEL_SIZE> = Channel::new();
+
+    let device0_event_channel: Channel<GlobalRawMutex, EventData, EVENT_CHANNEL_SIZE> = Channel::new();
+    let device0 = Mutex::new(Mock::new("PSU0", device0_event_channel.dyn_sender()));
+    let device1_event_channel: Channel<GlobalRawMutex, EventData, EVENT_CHANNEL_SIZE> = Channel::new();
+    let device1 = Mutex::new(Mock::new("PSU1", device1_event_channel.dyn_sender()));
+
+    // Declared after the devices since the events it holds reference them
+    let service_event_channel: Channel<GlobalRawMutex, ServiceEvent<'_, DeviceType<'_>>, EVENT_CHANNEL_SIZE> =
+        Channel::new();
+
+    let mut service = Service::new_with_customization(
+        ArrayRegistration {
+            psus: [&device0, &device1],
+            service_senders: [service_event_channel.dyn_sender()],
+            chargers: [],
+        },
+        Config::default(),
+        DefaultCustomization,
+    );
+    service.register_decision_sender(decision_channel.dyn_sender());
+
+    test.run(DecisionTestContext {
+        service,
+        service_receiver: service_event_channel.dyn_receiver(),
+        decision_receiver: decision_channel.dyn_receiver(),
+        psu_receivers: PsuEventReceivers::new(
+            [&device0, &device1],
+            [
+                device0_event_channel.dyn_receiver(),
+                device1_event_channel.dyn_receiver(),
+            ],
+        ),
+        device0: &device0,
+        device1: &device1,
+    })
+    .await;
+}
+
+fn assert_candidate(candidate: &DecisionCandidate, expected_psu: usize, expected_capability: ConsumerPowerCapability) {
+    assert_eq!(candidate.psu, expected_psu);
+    assert_eq!(candidate.consumer_power_capability, expected_capability);
+}
+
+/// Test that a policy decision record is sent for each consumer selection.
+struct TestPolicyDecision;
+
+impl DecisionTest for TestPolicyDecision {
+    async fn run<'device, 'sender>(&mut self, context: DecisionTestContext<'device, 'sender>) {
+        let DecisionTestContext {
+            mut service,
+            decision_receiver,
+            mut psu_receivers,
+            device0,
+            device1,
+            ..
+        } = context;
+
+        let low_power: ConsumerPowerCapability = LOW_POWER.into();
+        let high_power: ConsumerPowerCapability = HIGH_POWER.into();
+
+        // Device0 connects at low power and is the only candidate
+        {
+            device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device0.lock().await.simulate_consumer_connection(low_power).await;
+
+            // Attach and capability update events
+            for _ in 0..2 {
+                service
+                    .process_psu_event(psu_receivers.wait_event().await)
+                    .await
+                    .unwrap();
+            }
+
+            let decision = decision_receiver.try_receive().unwrap();
+            assert_eq!(decision.candidates.len(), 1);
+            assert_candidate(decision.candidates.first().unwrap(), 0, low_power);
+            assert_candidate(decision.chosen.as_ref().unwrap(), 0, low_power);
+            assert!(decision_receiver.try_receive().is_err());
+        }
+
+        // Device1 connects at high power, both devices are considered and device1 wins
+        {
+            device0.lock().await.next_result_disconnect.push_back(Ok(()));
+            device1.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device1.lock().await.simulate_consumer_connection(high_power).await;
+
+            for _ in 0..2 {
+                service
+                    .process_psu_event(psu_receivers.wait_event().await)
+                    .await
+                    .unwrap();
+            }
+
+            let decision = decision_receiver.try_receive().unwrap();
+            assert_eq!(decision.candidates.len(), 2);
+            assert_candidate(decision.candidates.first().unwrap(), 0, low_power);
+            assert_candidate(decision.candidates.get(1).unwrap(), 1, high_power);
+            assert_candidate(decision.chosen.as_ref().unwrap(), 1, high_power);
+            assert!(decision_receiver.try_receive().is_err());
+        }
+    }
+}
+
+/// Test that evaluating the best consumer has no side effects and matches the consumer actually selected.
+struct TestEvaluateBestConsumer;
+
+impl DecisionTest for TestEvaluateBestConsumer {
+    async fn run<'device, 'sender>(&mut self, context: DecisionTestContext<'device, 'sender>) {
+        let DecisionTestContext {
+            mut service,
+            service_receiver,
+            decision_receiver,
+            mut psu_receivers,
+            device0,
+            device1,
+        } = context;
+
+        let low_power: ConsumerPowerCapability = LOW_POWER.into();
+        let high_power: ConsumerPowerCapability = HIGH_POWER.into();
+
+        // Nothing attached
+        assert!(service.evaluate_best_consumer().await.unwrap().is_none());
+
+        // Device0 connects at low power, then device1 replaces it at high power
+        for (index, device, capability) in [(0, device0, low_power), (1, device1, high_power)] {
+            if index > 0 {
+                device0.lock().await.next_result_disconnect.push_back(Ok(()));
+            }
+            device.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device.lock().await.simulate_consumer_connection(capability).await;
+
+            // The dry run sees the new capability before the service has processed any events
+            let expected = service.evaluate_best_consumer().await.unwrap().unwrap();
+            assert!(ptr::eq(expected.psu, device));
+            assert_eq!(expected.consumer_power_capability, capability);
+
+            // And has no side effects
+            assert!(device0.lock().await.fn_calls.is_empty());
+            assert!(device1.lock().await.fn_calls.is_empty());
+            assert!(service_receiver.try_receive().is_err());
+            assert!(decision_receiver.try_receive().is_err());
+
+            // Attach and capability update events
+            for _ in 0..2 {
+                service
+                    .process_psu_event(psu_receivers.wait_event().await)
+                    .await
+                    .unwrap();
+            }
+
+            let connected = loop {
+                if let ServiceEvent::ConsumerConnected(psu, capability) = service_receiver.try_receive().unwrap() {
+                    break (psu, capability);
+                }
+            };
+            assert!(ptr::eq(connected.0, expected.psu));
+            assert_eq!(connected.1, expected.consumer_power_capability);
+            let decision = decision_receiver.try_receive().unwrap();
+            assert_candidate(decision.chosen.as_ref().unwrap(), index, capability);
+
+            device0.lock().await.fn_calls.clear();
+            device1.lock().await.fn_calls.clear();
+            while service_receiver.try_receive().is_ok() {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_policy_decision() {
+    run_decision_test(TestPolicyDecision).await;
+}
+
+#[tokio::test]
+async fn test_evaluate_best_consumer() {
+    run_decision_test(TestEvaluateBestConsumer).await;
+}
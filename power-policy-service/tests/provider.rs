@@ -1,20 +1,29 @@
 #![allow(clippy::unwrap_used)]
 use embassy_sync::channel::DynamicReceiver;
 use embedded_services::info;
+use power_policy_interface::capability::PowerCapability;
+use power_policy_interface::capability::ProviderDisconnect;
 use power_policy_interface::capability::ProviderFlags;
 use power_policy_interface::capability::ProviderPowerCapability;
+use power_policy_interface::psu::Error;
+use power_policy_interface::psu::event::{Event as PsuEvent, EventData};
 
 mod common;
 
 use common::{LOW_POWER, ServiceMutex};
 use power_policy_interface::service::event::Event as ServiceEvent;
+use power_policy_service::service::config::Config;
 use power_policy_service::service::customization::DefaultCustomization;
+use power_policy_service::service::provider::PowerState;
 
 use crate::common::DeviceType;
 use crate::common::HIGH_POWER;
 use crate::common::Test;
 use crate::common::assert_no_event;
-use crate::common::{DEFAULT_TIMEOUT, assert_provider_connected, assert_provider_disconnected, run_test};
+use crate::common::{
+    DEFAULT_TIMEOUT, assert_provider_capability_changed, assert_provider_connected, assert_provider_disconnected,
+    assert_provider_disconnected_with_flags, run_test,
+};
 use power_policy_interface_test_mocks::psu::FnCall;
 
 /// Test the basic provider flow with a single device.
@@ -25,7 +34,7 @@ impl Test for TestSingle {
 
     async fn run<'a>(
         &mut self,
-        _service: &ServiceMutex<'a, 'a, Self::Customization>,
+        service: &ServiceMutex<'a, 'a, Self::Customization>,
         service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
         device0: &DeviceType<'a>,
         _device1: &DeviceType<'a>,
@@ -45,6 +54,13 @@ impl Test for TestSingle {
                 },
             )
             .await;
+            assert_eq!(
+                service.lock().await.provider_capability(device0).await,
+                Some(ProviderPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ProviderFlags::none(),
+                })
+            );
 
             {
                 let mut device = device0.lock().await;
@@ -62,7 +78,13 @@ impl Test for TestSingle {
         {
             device0.lock().await.simulate_detach().await;
 
-            assert_provider_disconnected(service_receiver, device0).await;
+            assert_provider_disconnected_with_flags(
+                service_receiver,
+                device0,
+                ProviderDisconnect::none().with_unplugged(true),
+            )
+            .await;
+            assert_eq!(service.lock().await.provider_capability(device0).await, None);
 
             // Power policy shouldn't call any functions on detach
             assert!(device0.lock().await.fn_calls.is_empty());
@@ -156,7 +178,8 @@ impl Test for TestUpgrade {
                 .simulate_update_requested_provider_power_capability(Some(HIGH_POWER.into()))
                 .await;
 
-            assert_provider_connected(
+            // device1 is already connected, so this is reported as a capability change
+            assert_provider_capability_changed(
                 service_receiver,
                 device1,
                 ProviderPowerCapability {
@@ -202,7 +225,8 @@ impl Test for TestUpgrade {
                 .simulate_update_requested_provider_power_capability(Some(HIGH_POWER.into()))
                 .await;
 
-            assert_provider_connected(
+            // device1 is already connected, so this is reported as a capability change
+            assert_provider_capability_changed(
                 service_receiver,
                 device1,
                 ProviderPowerCapability {
@@ -290,6 +314,225 @@ impl Test for TestDisconnect {
     }
 }
 
+/// Total provider budget used by [`TestPowerBudget`].
+const MAX_TOTAL_PROVIDER_MW: u32 = 20000;
+
+/// Test that a provider request exceeding the total provider budget is rejected.
+struct TestPowerBudget;
+
+impl Test for TestPowerBudget {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        service: &'a ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &'a DeviceType<'a>,
+        device1: &'a DeviceType<'a>,
+    ) {
+        info!("Running test_power_budget");
+        {
+            // Connect device0 at high power, this is within budget
+            device0.lock().await.next_result_connect_provider.push_back(Ok(()));
+            device0.lock().await.simulate_provider_connection(HIGH_POWER).await;
+
+            assert_provider_connected(
+                service_receiver,
+                device0,
+                ProviderPowerCapability {
+                    capability: HIGH_POWER,
+                    flags: ProviderFlags::none(),
+                },
+            )
+            .await;
+
+            {
+                let mut device = device0.lock().await;
+                assert_eq!(
+                    device.fn_calls.pop_front().unwrap(),
+                    FnCall::ConnectProvider(ProviderPowerCapability {
+                        capability: HIGH_POWER,
+                        flags: ProviderFlags::none(),
+                    })
+                );
+                assert!(device.fn_calls.is_empty());
+            }
+
+            assert_eq!(service.lock().await.compute_total_provider_power_mw().await, 15000);
+        }
+
+        {
+            // Request low power on device1, this would exceed the budget and should be rejected
+            {
+                let mut device = device1.lock().await;
+                device.state.attach().unwrap();
+                device
+                    .state
+                    .update_requested_provider_power_capability(Some(LOW_POWER.into()))
+                    .unwrap();
+            }
+
+            let result = service
+                .lock()
+                .await
+                .process_psu_event(PsuEvent {
+                    psu: device1,
+                    event: EventData::RequestedProviderCapability(Some(LOW_POWER.into())),
+                })
+                .await;
+
+            // 5000 mW remaining at 5 V
+            assert_eq!(
+                result,
+                Err(Error::CannotProvide(Some(ProviderPowerCapability {
+                    capability: PowerCapability {
+                        voltage_mv: 5000,
+                        current_ma: 1000,
+                    },
+                    flags: ProviderFlags::none(),
+                })))
+            );
+
+            // Power policy shouldn't call any functions on rejection
+            assert!(device1.lock().await.fn_calls.is_empty());
+            assert_eq!(service.lock().await.compute_total_provider_power_mw().await, 15000);
+            // Rejected request shouldn't change the provider power state
+            assert_eq!(
+                service.lock().await.export_state().provider_state,
+                PowerState::Unlimited
+            );
+            assert_no_event(service_receiver);
+        }
+
+        {
+            // Disconnect device0 to free up the budget
+            device0.lock().await.simulate_disconnect().await;
+
+            assert_provider_disconnected(service_receiver, device0).await;
+            assert_eq!(service.lock().await.compute_total_provider_power_mw().await, 0);
+        }
+
+        {
+            // Retry the request on device1, this should now succeed
+            device1.lock().await.next_result_connect_provider.push_back(Ok(()));
+            service
+                .lock()
+                .await
+                .process_psu_event(PsuEvent {
+                    psu: device1,
+                    event: EventData::RequestedProviderCapability(Some(LOW_POWER.into())),
+                })
+                .await
+                .unwrap();
+
+            assert_provider_connected(
+                service_receiver,
+                device1,
+                ProviderPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ProviderFlags::none(),
+                },
+            )
+            .await;
+
+            {
+                let mut device = device1.lock().await;
+                assert_eq!(
+                    device.fn_calls.pop_front().unwrap(),
+                    FnCall::ConnectProvider(ProviderPowerCapability {
+                        capability: LOW_POWER,
+                        flags: ProviderFlags::none(),
+                    })
+                );
+                assert!(device.fn_calls.is_empty());
+            }
+
+            assert_eq!(service.lock().await.compute_total_provider_power_mw().await, 7500);
+        }
+
+        assert_no_event(service_receiver);
+    }
+}
+
+/// Test that a provider request exceeding the total provider budget is granted a reduced capability.
+struct TestPowerBudgetLimited;
+
+impl Test for TestPowerBudgetLimited {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        service: &'a ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &'a DeviceType<'a>,
+        device1: &'a DeviceType<'a>,
+    ) {
+        info!("Running test_power_budget_limited");
+        // 5000 mW remaining at 5 V
+        let limited = ProviderPowerCapability {
+            capability: PowerCapability {
+                voltage_mv: 5000,
+                current_ma: 1000,
+            },
+            flags: ProviderFlags::none(),
+        };
+
+        {
+            // Connect device0 at high power, this is within budget
+            device0.lock().await.next_result_connect_provider.push_back(Ok(()));
+            device0.lock().await.simulate_provider_connection(HIGH_POWER).await;
+
+            assert_provider_connected(
+                service_receiver,
+                device0,
+                ProviderPowerCapability {
+                    capability: HIGH_POWER,
+                    flags: ProviderFlags::none(),
+                },
+            )
+            .await;
+
+            let mut device = device0.lock().await;
+            assert_eq!(
+                device.fn_calls.pop_front().unwrap(),
+                FnCall::ConnectProvider(ProviderPowerCapability {
+                    capability: HIGH_POWER,
+                    flags: ProviderFlags::none(),
+                })
+            );
+            assert!(device.fn_calls.is_empty());
+        }
+
+        {
+            // Request low power on device1, this exceeds the budget and should be limited to the remaining power
+            device1.lock().await.next_result_connect_provider.push_back(Ok(()));
+            device1.lock().await.simulate_provider_connection(LOW_POWER).await;
+
+            assert_provider_connected(service_receiver, device1, limited).await;
+
+            let mut device = device1.lock().await;
+            assert_eq!(device.fn_calls.pop_front().unwrap(), FnCall::ConnectProvider(limited));
+            assert!(device.fn_calls.is_empty());
+
+            // The granted capability is recorded, not the requested one
+            assert_eq!(device.state.connected_provider_capability(), Some(limited));
+            assert_eq!(
+                device.state.requested_provider_capability,
+                Some(ProviderPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ProviderFlags::none(),
+                })
+            );
+        }
+
+        assert_eq!(
+            service.lock().await.compute_total_provider_power_mw().await,
+            MAX_TOTAL_PROVIDER_MW
+        );
+        assert_no_event(service_receiver);
+    }
+}
+
 #[tokio::test]
 async fn run_test_single() {
     run_test(DEFAULT_TIMEOUT, TestSingle, Default::default(), DefaultCustomization).await;
@@ -310,3 +553,20 @@ async fn run_test_disconnect() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn run_test_power_budget() {
+    let mut config = Config::default();
+    config.max_total_provider_mw = Some(MAX_TOTAL_PROVIDER_MW);
+
+    run_test(DEFAULT_TIMEOUT, TestPowerBudget, config, DefaultCustomization).await;
+}
+
+#[tokio::test]
+async fn run_test_power_budget_limited() {
+    let mut config = Config::default();
+    config.max_total_provider_mw = Some(MAX_TOTAL_PROVIDER_MW);
+    config.limit_provider_to_budget = true;
+
+    run_test(DEFAULT_TIMEOUT, TestPowerBudgetLimited, config, DefaultCustomization).await;
+}
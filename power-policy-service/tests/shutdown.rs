@@ -0,0 +1,115 @@
+#![allow(clippy::unwrap_used)]
+use embassy_sync::channel::DynamicReceiver;
+use embedded_services::info;
+use power_policy_interface::capability::{
+    ConsumerDisconnect, ProviderDisconnect, ProviderFlags, ProviderPowerCapability,
+};
+use power_policy_interface::psu::{Psu, PsuState};
+use power_policy_interface::service::event::Event as ServiceEvent;
+use power_policy_interface_test_mocks::psu::FnCall;
+use power_policy_service::service::customization::DefaultCustomization;
+
+mod common;
+
+use common::{
+    DEFAULT_TIMEOUT, DeviceType, HIGH_POWER, LOW_POWER, ServiceMutex, Test, assert_consumer_connected,
+    assert_consumer_disconnected_with_flags, assert_no_event, assert_provider_connected,
+    assert_provider_disconnected_with_flags, run_test,
+};
+
+/// Test that shutdown disconnects the consumer and all providers and that the service recovers afterwards.
+struct TestShutdown;
+
+impl Test for TestShutdown {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        service: &ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &DeviceType<'a>,
+        device1: &DeviceType<'a>,
+    ) {
+        info!("Running test_shutdown");
+        {
+            // Connect device0 as consumer and device1 as provider
+            device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device0
+                .lock()
+                .await
+                .simulate_consumer_connection(HIGH_POWER.into())
+                .await;
+            assert_consumer_connected(service_receiver, device0, HIGH_POWER.into()).await;
+
+            device1.lock().await.next_result_connect_provider.push_back(Ok(()));
+            device1.lock().await.simulate_provider_connection(LOW_POWER).await;
+            assert_provider_connected(
+                service_receiver,
+                device1,
+                ProviderPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ProviderFlags::none(),
+                },
+            )
+            .await;
+
+            device0.lock().await.fn_calls.clear();
+            device1.lock().await.fn_calls.clear();
+        }
+
+        {
+            // Shutdown should disconnect both devices and notify listeners
+            device0.lock().await.next_result_disconnect.push_back(Ok(()));
+            device1.lock().await.next_result_disconnect.push_back(Ok(()));
+            service.lock().await.shutdown().await.unwrap();
+
+            assert_consumer_disconnected_with_flags(
+                service_receiver,
+                device0,
+                ConsumerDisconnect::none().with_shutdown(true),
+            )
+            .await;
+            assert_provider_disconnected_with_flags(
+                service_receiver,
+                device1,
+                ProviderDisconnect::none().with_shutdown(true),
+            )
+            .await;
+            assert_no_event(service_receiver);
+
+            for device in [device0, device1] {
+                let mut device = device.lock().await;
+                assert_eq!(device.fn_calls.pop_front().unwrap(), FnCall::Disconnect);
+                assert!(device.fn_calls.is_empty());
+                assert_eq!(device.state().psu_state, PsuState::Idle);
+            }
+            assert_eq!(service.lock().await.compute_total_provider_power_mw().await, 0);
+        }
+
+        {
+            // A later attach should connect normally
+            device0.lock().await.simulate_detach().await;
+            device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device0
+                .lock()
+                .await
+                .simulate_consumer_connection(LOW_POWER.into())
+                .await;
+            assert_consumer_connected(service_receiver, device0, LOW_POWER.into()).await;
+
+            let mut device = device0.lock().await;
+            assert_eq!(
+                device.fn_calls.pop_front().unwrap(),
+                FnCall::ConnectConsumer(LOW_POWER.into())
+            );
+            assert!(device.fn_calls.is_empty());
+        }
+
+        assert_no_event(service_receiver);
+    }
+}
+
+#[tokio::test]
+async fn run_test_shutdown() {
+    run_test(DEFAULT_TIMEOUT, TestShutdown, Default::default(), DefaultCustomization).await;
+}
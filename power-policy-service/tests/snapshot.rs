@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used)]
+use embassy_sync::channel::{Channel, DynamicReceiver};
+use embedded_services::GlobalRawMutex;
+use embedded_services::info;
+use power_policy_interface::capability::{ProviderFlags, ProviderPowerCapability};
+use power_policy_interface::psu::Error;
+use power_policy_interface::service::event::Event as ServiceEvent;
+use power_policy_service::service::customization::DefaultCustomization;
+use power_policy_service::service::registration::ArrayRegistration;
+use power_policy_service::service::{Service, snapshot::StateSnapshot};
+
+mod common;
+
+use common::{
+    DEFAULT_TIMEOUT, DeviceType, HIGH_POWER, LOW_POWER, ServiceMutex, ServiceType, Test, assert_consumer_connected,
+    assert_provider_connected, run_test,
+};
+
+/// Test that state exported from one service can be imported into a new service over the same devices.
+struct TestSnapshot;
+
+impl Test for TestSnapshot {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        service: &'a ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &'a DeviceType<'a>,
+        device1: &'a DeviceType<'a>,
+    ) {
+        info!("Running test_snapshot");
+        let provider_capability = ProviderPowerCapability {
+            capability: LOW_POWER,
+            flags: ProviderFlags::none(),
+        };
+
+        {
+            // Connect device0 as consumer and device1 as provider
+            device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device0
+                .lock()
+                .await
+                .simulate_consumer_connection(HIGH_POWER.into())
+                .await;
+            assert_consumer_connected(service_receiver, device0, HIGH_POWER.into()).await;
+
+            device1.lock().await.next_result_connect_provider.push_back(Ok(()));
+            device1.lock().await.simulate_provider_connection(LOW_POWER).await;
+            assert_provider_connected(service_receiver, device1, provider_capability).await;
+        }
+
+        let snapshot = service.lock().await.export_state();
+        assert_eq!(snapshot.current_consumer, Some((0, HIGH_POWER.into())));
+        assert_eq!(snapshot.connected_providers.as_slice(), &[1]);
+
+        let fresh_channel: Channel<GlobalRawMutex, ServiceEvent<'a, DeviceType<'a>>, 1> = Channel::new();
+        let mut fresh: ServiceType<'a, '_, DefaultCustomization> = Service::new(
+            ArrayRegistration {
+                psus: [device0, device1],
+                service_senders: [fresh_channel.dyn_sender()],
+                chargers: [],
+            },
+            Default::default(),
+        );
+        assert_eq!(fresh.export_state(), StateSnapshot::default());
+
+        {
+            // Snapshots referring to unregistered devices are rejected without modifying the state
+            let mut invalid = snapshot.clone();
+            invalid.connected_providers.push(2).unwrap();
+            assert_eq!(fresh.import_state(&invalid), Err(Error::InvalidDevice));
+            assert_eq!(fresh.export_state(), StateSnapshot::default());
+        }
+
+        fresh.import_state(&snapshot).unwrap();
+        assert_eq!(fresh.export_state(), snapshot);
+        assert_eq!(fresh.provider_capability(device1).await, Some(provider_capability));
+        assert_eq!(fresh.provider_capability(device0).await, None);
+    }
+}
+
+#[tokio::test]
+async fn run_test_snapshot() {
+    run_test(DEFAULT_TIMEOUT, TestSnapshot, Default::default(), DefaultCustomization).await;
+}
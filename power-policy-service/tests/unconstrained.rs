@@ -8,6 +8,7 @@ mod common;
 use common::LOW_POWER;
 use power_policy_interface::service::UnconstrainedState;
 use power_policy_interface::service::event::Event as ServiceEvent;
+use power_policy_service::service::UnconstrainedSignal;
 use power_policy_service::service::customization::DefaultCustomization;
 
 use crate::common::HIGH_POWER;
@@ -26,12 +27,13 @@ impl Test for TestUnconstrained {
 
     async fn run<'a>(
         &mut self,
-        _service: &ServiceMutex<'a, 'a, Self::Customization>,
+        service: &ServiceMutex<'a, 'a, Self::Customization>,
         service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
         device0: &DeviceType<'a>,
         device1: &DeviceType<'a>,
     ) {
         info!("Running test_unconstrained");
+        assert!(!service.lock().await.is_unconstrained());
         {
             // Connect device0, without unconstrained,
             device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
@@ -63,6 +65,7 @@ impl Test for TestUnconstrained {
 
             // Should not have any unconstrained events
             assert!(service_receiver.try_receive().is_err());
+            assert!(!service.lock().await.is_unconstrained());
         }
 
         {
@@ -99,6 +102,7 @@ impl Test for TestUnconstrained {
                 },
             )
             .await;
+            assert!(service.lock().await.is_unconstrained());
 
             {
                 let mut device0 = device0.lock().await;
@@ -144,6 +148,7 @@ impl Test for TestUnconstrained {
                 },
             )
             .await;
+            assert!(!service.lock().await.is_unconstrained());
 
             // Power policy shouldn't call any functions on device1 for detach
             assert!(device1.lock().await.fn_calls.is_empty());
@@ -163,6 +168,107 @@ impl Test for TestUnconstrained {
     }
 }
 
+/// Signal used by [`TestUnconstrainedSignal`]
+static UNCONSTRAINED_SIGNAL: UnconstrainedSignal = UnconstrainedSignal::new();
+
+/// Test that unconstrained state changes are observable through [`UnconstrainedSignal`].
+struct TestUnconstrainedSignal;
+
+impl Test for TestUnconstrainedSignal {
+    type Customization = DefaultCustomization;
+
+    async fn run<'a>(
+        &mut self,
+        service: &'a ServiceMutex<'a, 'a, Self::Customization>,
+        service_receiver: DynamicReceiver<'a, ServiceEvent<'a, DeviceType<'a>>>,
+        device0: &'a DeviceType<'a>,
+        _device1: &'a DeviceType<'a>,
+    ) {
+        info!("Running test_unconstrained_signal");
+        service
+            .lock()
+            .await
+            .register_unconstrained_signal(&UNCONSTRAINED_SIGNAL);
+
+        {
+            // Connect device0 with unconstrained power
+            device0.lock().await.next_result_connect_consumer.push_back(Ok(()));
+            device0
+                .lock()
+                .await
+                .simulate_consumer_connection(ConsumerPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ConsumerFlags::none().with_unconstrained_power(),
+                })
+                .await;
+
+            assert_eq!(
+                UNCONSTRAINED_SIGNAL.wait_unconstrained_change().await,
+                UnconstrainedState {
+                    unconstrained: true,
+                    available: 1,
+                }
+            );
+
+            assert_consumer_connected(
+                service_receiver,
+                device0,
+                ConsumerPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ConsumerFlags::none().with_unconstrained_power(),
+                },
+            )
+            .await;
+            assert_unconstrained(
+                service_receiver,
+                UnconstrainedState {
+                    unconstrained: true,
+                    available: 1,
+                },
+            )
+            .await;
+
+            let mut device = device0.lock().await;
+            assert_eq!(
+                device.fn_calls.pop_front().unwrap(),
+                FnCall::ConnectConsumer(ConsumerPowerCapability {
+                    capability: LOW_POWER,
+                    flags: ConsumerFlags::none().with_unconstrained_power(),
+                })
+            );
+            assert!(device.fn_calls.is_empty());
+        }
+
+        {
+            // Detach device0, system should no longer be unconstrained
+            device0.lock().await.simulate_detach().await;
+
+            assert_eq!(
+                UNCONSTRAINED_SIGNAL.wait_unconstrained_change().await,
+                UnconstrainedState {
+                    unconstrained: false,
+                    available: 0,
+                }
+            );
+
+            assert_consumer_disconnected(service_receiver, device0).await;
+            assert_unconstrained(
+                service_receiver,
+                UnconstrainedState {
+                    unconstrained: false,
+                    available: 0,
+                },
+            )
+            .await;
+
+            // Power policy shouldn't call any functions on detach
+            assert!(device0.lock().await.fn_calls.is_empty());
+        }
+
+        assert_no_event(service_receiver);
+    }
+}
+
 #[tokio::test]
 async fn run_test_unconstrained() {
     run_test(
@@ -173,3 +279,14 @@ async fn run_test_unconstrained() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn run_test_unconstrained_signal() {
+    run_test(
+        DEFAULT_TIMEOUT,
+        TestUnconstrainedSignal,
+        Default::default(),
+        DefaultCustomization,
+    )
+    .await;
+}
@@ -13,6 +13,10 @@ pub trait Driver: Fan + RpmSense {}
 pub enum Error {
     /// Fan encountered a hardware failure.
     Hardware,
+    /// An invalid argument was provided.
+    InvalidArgument,
+    /// The fan did not respond within its bus timeout.
+    Timeout,
 }
 
 /// Fan event.
@@ -62,6 +66,12 @@ pub trait FanService {
     fn rpm_average(&self) -> impl Future<Output = u16>;
     /// Immediately samples the fan for an RPM measurement and returns the result.
     fn rpm_immediate(&self) -> impl Future<Output = Result<u16, Error>>;
+    /// Returns the duty cycle percentage the fan was last commanded to run at, by automatic control or a manual
+    /// command.
+    ///
+    /// Unlike [`Self::rpm`], this is the requested speed rather than a measurement. A command given in RPM is
+    /// reported as a percentage of [`Self::max_rpm`].
+    fn duty_percent(&self) -> impl Future<Output = u8>;
     /// Sets the fan to run at the specified RPM (and disables automatic control).
     fn set_rpm(&self, rpm: u16) -> impl Future<Output = Result<(), Error>>;
     /// Sets the fan to run at the specified duty cycle percentage (and disables automatic control).
@@ -103,6 +113,10 @@ impl<T: FanService> FanService for &T {
         T::rpm_immediate(self)
     }
 
+    fn duty_percent(&self) -> impl Future<Output = u8> {
+        T::duty_percent(self)
+    }
+
     fn set_rpm(&self, rpm: u16) -> impl Future<Output = Result<(), Error>> {
         T::set_rpm(self, rpm)
     }
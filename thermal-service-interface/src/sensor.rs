@@ -1,9 +1,43 @@
 use core::future::Future;
-use embassy_time::Duration;
+use embassy_time::{Duration, Instant};
 use embedded_sensors_hal_async::temperature::{DegreesCelsius, TemperatureSensor};
 
 /// Ensures all necessary traits are implemented for the underlying sensor driver.
-pub trait Driver: TemperatureSensor {}
+pub trait Driver: TemperatureSensor {
+    /// Returns the capabilities of the sensor.
+    ///
+    /// Defaults to an unknown measurable range with every threshold evaluated in software.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Programs the hardware to signal a threshold event when the temperature leaves the `low..high` window.
+    ///
+    /// Only called if [`Capabilities::hardware_thresholds`] is set.
+    fn set_hardware_thresholds(
+        &mut self,
+        _low: DegreesCelsius,
+        _high: DegreesCelsius,
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        async { Ok(()) }
+    }
+
+    /// Returns a future that completes when the hardware signals a threshold event.
+    ///
+    /// Only called if [`Capabilities::hardware_thresholds`] is set, the default implementation never completes.
+    /// The future doesn't borrow the driver so the driver isn't locked while waiting, it would usually wait on a
+    /// signal raised by the sensor's alert interrupt.
+    fn wait_threshold_event(&self) -> impl Future<Output = ()> + 'static {
+        core::future::pending()
+    }
+
+    /// Re-runs the hardware init sequence, used to recover a sensor after a failure.
+    ///
+    /// Defaults to doing nothing for sensors which don't require initialization.
+    fn reinitialize(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+        async { Ok(()) }
+    }
+}
 
 /// Sensor error.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,6 +48,10 @@ pub enum Error {
     Hardware,
     /// Retry attempts to communicate with sensor exhausted.
     RetryExhausted,
+    /// Sensor has not been sampled within the expected period.
+    Stalled,
+    /// An invalid argument was provided.
+    InvalidArgument,
 }
 
 /// Sensor event.
@@ -43,24 +81,159 @@ pub enum Threshold {
     Critical,
 }
 
+/// Direction in which the temperature must move to exceed a threshold.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ThresholdDirection {
+    /// High-side threshold, exceeded when the temperature rises to or above it.
+    Rising,
+    /// Low-side threshold, exceeded when the temperature falls to or below it.
+    Falling,
+}
+
+impl Threshold {
+    /// Returns the direction in which the temperature must move to exceed this threshold.
+    pub const fn direction(self) -> ThresholdDirection {
+        match self {
+            Threshold::WarnLow => ThresholdDirection::Falling,
+            Threshold::WarnHigh | Threshold::Prochot | Threshold::Critical => ThresholdDirection::Rising,
+        }
+    }
+
+    /// All threshold types.
+    pub const ALL: &'static [Threshold] = &[
+        Threshold::WarnLow,
+        Threshold::WarnHigh,
+        Threshold::Prochot,
+        Threshold::Critical,
+    ];
+}
+
+/// Configured value of a sensor threshold.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ThresholdSetting {
+    /// Threshold type.
+    pub threshold: Threshold,
+    /// Temperature at which the threshold is exceeded in degrees Celsius.
+    pub value: DegreesCelsius,
+    /// Distance in degrees Celsius the temperature must move back past the threshold before it is cleared.
+    pub hysteresis: DegreesCelsius,
+}
+
+/// Sensor capabilities.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Capabilities {
+    /// Minimum measurable temperature in degrees Celsius.
+    pub min_temp: DegreesCelsius,
+    /// Maximum measurable temperature in degrees Celsius.
+    pub max_temp: DegreesCelsius,
+    /// Supported threshold types.
+    pub thresholds: &'static [Threshold],
+    /// Whether the sensor hardware can signal a threshold event.
+    ///
+    /// If true, [`Driver::set_hardware_thresholds`] and [`Driver::wait_threshold_event`] are used to sample
+    /// as soon as a threshold is crossed instead of waiting for the next sample period.
+    pub hardware_thresholds: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            min_temp: DegreesCelsius::MIN,
+            max_temp: DegreesCelsius::MAX,
+            thresholds: Threshold::ALL,
+            hardware_thresholds: false,
+        }
+    }
+}
+
+/// Sampling heartbeat of a sensor, used to detect a stalled sampling task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Heartbeat {
+    /// When the sensor was last sampled, [`None`] if it has not been sampled yet.
+    pub last_sample: Option<Instant>,
+    /// Period at which the sensor is expected to be sampled.
+    pub sample_period: Duration,
+    /// Whether periodic sampling is enabled, no heartbeat is expected if disabled.
+    pub sampling_enabled: bool,
+    /// Whether sampling is paused with [`SensorService::pause_sampling`], no heartbeat is expected while paused.
+    ///
+    /// Unlike disabled sampling, a pause is intentional and doesn't indicate a failure. The most recent sample
+    /// remains available.
+    pub paused: bool,
+}
+
+/// Temperature trend over the recently stored samples of a sensor.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Trend {
+    /// Rate of change in degrees Celsius per second, 0.0 if fewer than two samples are available.
+    pub slope: f32,
+    /// Lowest temperature in the window in degrees Celsius, 0.0 if no samples are available.
+    pub min: DegreesCelsius,
+    /// Highest temperature in the window in degrees Celsius, 0.0 if no samples are available.
+    pub max: DegreesCelsius,
+    /// Number of samples the trend was computed over.
+    pub samples: usize,
+}
+
 /// Sensor service interface trait
 pub trait SensorService {
     /// Returns the most recently sampled temperature measurement in degrees Celsius.
     fn temperature(&self) -> impl Future<Output = DegreesCelsius>;
     /// Returns the average temperature over a sampling period in degrees Celsius.
     fn temperature_average(&self) -> impl Future<Output = DegreesCelsius>;
+    /// Returns the temperature trend over the stored samples.
+    fn temperature_trend(&self) -> impl Future<Output = Trend>;
     /// Immediately samples the sensor for a temperature measurement and returns the result in degrees Celsius.
+    ///
+    /// The measurement is not stored as a sample, so it doesn't affect [`Self::temperature`],
+    /// [`Self::temperature_average`] or threshold events.
     fn temperature_immediate(&self) -> impl Future<Output = Result<DegreesCelsius, Error>>;
+    /// Takes a sample now rather than waiting for the next sample period and returns it in degrees Celsius.
+    ///
+    /// Unlike [`Self::temperature_immediate`], the measurement is stored as a sample while periodic sampling is
+    /// enabled. A request made while a sample is already being taken is served by that sample.
+    fn sample_now(&self) -> impl Future<Output = Result<DegreesCelsius, Error>>;
     /// Sets the temperature for which a sensor event will be generated when the threshold is exceeded, in degrees Celsius.
     fn set_threshold(&self, threshold: Threshold, value: DegreesCelsius) -> impl Future<Output = ()>;
     /// Returns the temperature threshold value for the specified threshold type in degrees Celsius.
     fn threshold(&self, threshold: Threshold) -> impl Future<Output = DegreesCelsius>;
+    /// Returns the configuration of every threshold, in the order of [`Threshold::ALL`].
+    fn thresholds(&self) -> impl Future<Output = [ThresholdSetting; Threshold::ALL.len()]>;
     /// Sets the rate at which temperature measurements are sampled.
     fn set_sample_period(&self, period: Duration) -> impl Future<Output = ()>;
     /// Enable periodic temperature sampling.
     fn enable_sampling(&self) -> impl Future<Output = ()>;
     /// Disable periodic temperature sampling.
     fn disable_sampling(&self) -> impl Future<Output = ()>;
+    /// Temporarily stop accessing the sensor hardware, e.g. while another subsystem is using a shared bus.
+    ///
+    /// Returns once any in-progress read has completed. No samples are taken and no staleness or failure is
+    /// reported until [`Self::resume_sampling`] is called. Unlike [`Self::disable_sampling`], the enabled state is
+    /// kept.
+    fn pause_sampling(&self) -> impl Future<Output = ()>;
+    /// Resume sampling after [`Self::pause_sampling`].
+    fn resume_sampling(&self) -> impl Future<Output = ()>;
+    /// Returns the capabilities of the sensor.
+    fn capabilities(&self) -> impl Future<Output = Capabilities>;
+    /// Returns the sampling heartbeat of the sensor.
+    fn heartbeat(&self) -> impl Future<Output = Heartbeat>;
+    /// Returns when the most recent sample was stored, [`None`] if the sensor has not been sampled yet.
+    ///
+    /// Unlike [`Heartbeat::last_sample`], this is not restarted when sampling is enabled or resumed.
+    fn last_sample_time(&self) -> impl Future<Output = Option<Instant>>;
+    /// Reinitializes the sensor hardware and, on success, resumes periodic sampling.
+    ///
+    /// Used to recover a sensor after a [`Event::Failure`] without rebooting.
+    fn reinitialize(&self) -> impl Future<Output = Result<(), Error>>;
+    /// Returns the human-readable label of the sensor used in diagnostics, if any.
+    fn label(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 impl<T: SensorService> SensorService for &T {
@@ -72,10 +245,18 @@ impl<T: SensorService> SensorService for &T {
         T::temperature_average(self).await
     }
 
+    async fn temperature_trend(&self) -> Trend {
+        T::temperature_trend(self).await
+    }
+
     async fn temperature_immediate(&self) -> Result<DegreesCelsius, Error> {
         T::temperature_immediate(self).await
     }
 
+    async fn sample_now(&self) -> Result<DegreesCelsius, Error> {
+        T::sample_now(self).await
+    }
+
     async fn set_threshold(&self, threshold: Threshold, value: DegreesCelsius) {
         T::set_threshold(self, threshold, value).await
     }
@@ -84,6 +265,10 @@ impl<T: SensorService> SensorService for &T {
         T::threshold(self, threshold).await
     }
 
+    async fn thresholds(&self) -> [ThresholdSetting; Threshold::ALL.len()] {
+        T::thresholds(self).await
+    }
+
     async fn set_sample_period(&self, period: Duration) {
         T::set_sample_period(self, period).await
     }
@@ -95,4 +280,32 @@ impl<T: SensorService> SensorService for &T {
     async fn disable_sampling(&self) {
         T::disable_sampling(self).await
     }
+
+    async fn pause_sampling(&self) {
+        T::pause_sampling(self).await
+    }
+
+    async fn resume_sampling(&self) {
+        T::resume_sampling(self).await
+    }
+
+    async fn capabilities(&self) -> Capabilities {
+        T::capabilities(self).await
+    }
+
+    async fn heartbeat(&self) -> Heartbeat {
+        T::heartbeat(self).await
+    }
+
+    async fn last_sample_time(&self) -> Option<Instant> {
+        T::last_sample_time(self).await
+    }
+
+    async fn reinitialize(&self) -> Result<(), Error> {
+        T::reinitialize(self).await
+    }
+
+    fn label(&self) -> Option<&'static str> {
+        T::label(self)
+    }
 }
@@ -2,19 +2,42 @@ use crate::utils::SampleBuf;
 use core::marker::PhantomData;
 use embassy_sync::mutex::Mutex;
 use embassy_sync::signal::Signal;
-use embassy_time::{Duration, Timer};
-use embedded_fans_async::Error as _;
+use embassy_time::{Duration, Timer, with_timeout};
+use embedded_mcu_hal::nvram::NvramStorage;
 use embedded_sensors_hal_async::temperature::DegreesCelsius;
 use embedded_services::event::NonBlockingSender;
-use embedded_services::{GlobalRawMutex, error, trace};
+use embedded_services::{GlobalRawMutex, error, info, trace, warn};
 use thermal_service_interface::{fan, sensor};
 
+/* Helper macro for accessing the fan driver with a timeout.
+ *
+ * Acquiring the driver lock is included in the timeout, so a call stuck on a wedged bus holding the lock doesn't
+ * block other users indefinitely either.
+ */
+macro_rules! with_bus_timeout {
+    (
+        $inner:expr,
+        $bus_method:expr
+    ) => {{
+        let bus_timeout = $inner.config.lock().await.bus_timeout;
+        match with_timeout(bus_timeout, async { $bus_method.await }).await {
+            Ok(result) => result.map_err(|_| fan::Error::Hardware),
+            Err(_) => {
+                error!("Fan bus access timed out");
+                Err(fan::Error::Timeout)
+            }
+        }
+    }};
+}
+
 /// Fan service configuration parameters.
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Config {
     /// Rate at which to sample the fan RPM.
     pub sample_period: Duration,
+    /// Delay before the first RPM sample, used to stagger fans sharing a bus so they don't all sample at once.
+    pub sample_phase: Duration,
     /// Rate at which to update the fan state based on temperature readings when auto control is enabled.
     pub update_period: Duration,
     /// Whether automatic fan control based on temperature is enabled.
@@ -27,46 +50,286 @@ pub struct Config {
     pub ramp_temp: DegreesCelsius,
     /// Temperature at which the fan will run at its maximum RPM.
     pub max_temp: DegreesCelsius,
+    /// Behavior under automatic control while the associated sensor is marked failed.
+    pub fail_safe: FailSafe,
+    /// Maximum duty cycle percentage the temperature curve may run the fan at, e.g. to limit noise in quiet
+    /// environments.
+    ///
+    /// The fan still runs at its maximum RPM once the temperature reaches the sensor's critical threshold.
+    pub max_duty_cap: u8,
+    /// Timeout for each access to the fan driver, e.g. an RPM read.
+    pub bus_timeout: Duration,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             sample_period: Duration::from_secs(1),
+            sample_phase: Duration::from_millis(0),
             update_period: Duration::from_secs(1),
             auto_control: true,
             hysteresis: 2.0,
             min_temp: 25.0,
             ramp_temp: 35.0,
             max_temp: 45.0,
+            fail_safe: FailSafe::default(),
+            max_duty_cap: 100,
+            bus_timeout: Duration::from_millis(200),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the temperature curve used for automatic fan control.
+    pub fn profile(&self) -> Profile {
+        Profile {
+            hysteresis: self.hysteresis,
+            min_temp: self.min_temp,
+            ramp_temp: self.ramp_temp,
+            max_temp: self.max_temp,
+            fail_safe: self.fail_safe,
+            max_duty_cap: self.max_duty_cap,
+            bus_timeout: self.bus_timeout,
+        }
+    }
+
+    /// Replaces the temperature curve used for automatic fan control.
+    pub fn set_profile(&mut self, profile: &Profile) {
+        self.hysteresis = profile.hysteresis;
+        self.min_temp = profile.min_temp;
+        self.ramp_temp = profile.ramp_temp;
+        self.max_temp = profile.max_temp;
+        self.fail_safe = profile.fail_safe;
+        self.max_duty_cap = profile.max_duty_cap;
+        self.bus_timeout = profile.bus_timeout;
+    }
+}
+
+/// How the fan speed is currently controlled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ControlMode {
+    /// Speed follows the temperature curve.
+    ///
+    /// Entered with [`fan::FanService::enable_auto_control`].
+    Auto,
+    /// Speed is fixed by the last manual command and the temperature curve is ignored.
+    ///
+    /// Entered with [`fan::FanService::set_duty_percent`], [`fan::FanService::set_rpm`] or
+    /// [`fan::FanService::stop`], or when automatic control is disabled after a failure.
+    Manual,
+}
+
+/// Fan behavior under automatic control while its sensor is marked failed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FailSafe {
+    /// Run the fan at its maximum RPM.
+    #[default]
+    FullSpeed,
+    /// Keep the fan at whatever speed it was running when the sensor failed.
+    HoldLast,
+    /// Run the fan at the given duty cycle percentage.
+    FixedDuty(u8),
+}
+
+/// How the temperature driving automatic control is derived from the fan's sensors.
+pub enum Aggregation<'hw, S: sensor::SensorService> {
+    /// Temperature of the fan's own sensor.
+    Single,
+    /// Weighted blend of the given sensors' temperatures, e.g. `[(cpu, 7.0), (ssd, 3.0)]`.
+    ///
+    /// Weights are normalized over the sensors in use, so they don't need to sum to 1. A sensor whose sampling is
+    /// disabled, e.g. after a failure, is left out and its weight redistributed over the remaining sensors. A paused
+    /// sensor keeps contributing its most recent sample. The configured [`FailSafe`] is applied if no sensor is left.
+    /// The fan's own sensor still provides the critical threshold which overrides the duty cap.
+    Weighted(&'hw [(S, f32)]),
+}
+
+/// Temperature curve used for automatic fan control, along with how long the fan hardware may take to respond.
+///
+/// See [`Config`] for the meaning of each field.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Profile {
+    /// Hysteresis value to prevent rapid toggling between fan states.
+    pub hysteresis: DegreesCelsius,
+    /// Temperature at which the fan will turn on and begin running at its minimum RPM.
+    pub min_temp: DegreesCelsius,
+    /// Temperature at which the fan will follow a speed curve between its minimum and maximum RPM.
+    pub ramp_temp: DegreesCelsius,
+    /// Temperature at which the fan will run at its maximum RPM.
+    pub max_temp: DegreesCelsius,
+    /// Behavior while the associated sensor is marked failed.
+    pub fail_safe: FailSafe,
+    /// Maximum duty cycle percentage the temperature curve may run the fan at, below the critical threshold.
+    pub max_duty_cap: u8,
+    /// Timeout for each access to the fan driver.
+    pub bus_timeout: Duration,
+}
+
+impl Profile {
+    /// Returns true if all temperatures are finite, the hysteresis is non-negative, the state temperatures are
+    /// in ascending order, any fail-safe duty cycle and the duty cap are valid percentages and the bus timeout is
+    /// non-zero and fits in a [`u32`] of milliseconds.
+    pub fn is_valid(&self) -> bool {
+        [self.hysteresis, self.min_temp, self.ramp_temp, self.max_temp]
+            .iter()
+            .all(|temp| temp.is_finite())
+            && self.hysteresis >= 0.0
+            && self.min_temp <= self.ramp_temp
+            && self.ramp_temp <= self.max_temp
+            && !matches!(self.fail_safe, FailSafe::FixedDuty(duty) if duty > 100)
+            && self.max_duty_cap <= 100
+            && self.bus_timeout.as_ticks() > 0
+            && u32::try_from(self.bus_timeout.as_millis()).is_ok()
+    }
+}
+
+/// Number of NVRAM words used by [`ProfileStorage`].
+pub const PROFILE_STORAGE_LEN: usize = 9;
+
+mod profile_storage {
+    use super::{Duration, FailSafe, NvramStorage, PROFILE_STORAGE_LEN, Profile};
+
+    /// Marks a valid profile record, the low half holds the record length.
+    const RECORD_MAGIC: u32 = 0xFA4E_0000;
+
+    /// Fail-safe word values, a fixed duty cycle is stored in the low byte.
+    const FAIL_SAFE_FULL_SPEED: u32 = 0;
+    const FAIL_SAFE_HOLD_LAST: u32 = 1;
+    const FAIL_SAFE_FIXED_DUTY: u32 = 0x100;
+
+    fn fail_safe_to_word(fail_safe: FailSafe) -> u32 {
+        match fail_safe {
+            FailSafe::FullSpeed => FAIL_SAFE_FULL_SPEED,
+            FailSafe::HoldLast => FAIL_SAFE_HOLD_LAST,
+            FailSafe::FixedDuty(duty) => FAIL_SAFE_FIXED_DUTY | u32::from(duty),
+        }
+    }
+
+    fn fail_safe_from_word(word: u32) -> Option<FailSafe> {
+        match word {
+            FAIL_SAFE_FULL_SPEED => Some(FailSafe::FullSpeed),
+            FAIL_SAFE_HOLD_LAST => Some(FailSafe::HoldLast),
+            _ if word & !0xFF == FAIL_SAFE_FIXED_DUTY => Some(FailSafe::FixedDuty(word as u8)),
+            _ => None,
+        }
+    }
+
+    /// NVRAM backing for a fan [`Profile`].
+    ///
+    /// A record is laid out as a header word, the four profile temperatures, the fail-safe behavior, the duty cap, the
+    /// bus timeout in milliseconds and a trailing checksum word.
+    pub struct ProfileStorage<'hw> {
+        storage: [&'hw mut dyn NvramStorage<'hw, u32>; PROFILE_STORAGE_LEN],
+    }
+
+    impl<'hw> ProfileStorage<'hw> {
+        /// Create a new instance backed by the given NVRAM words.
+        pub fn new(storage: [&'hw mut dyn NvramStorage<'hw, u32>; PROFILE_STORAGE_LEN]) -> Self {
+            Self { storage }
+        }
+
+        /// Checksum over all record words except the checksum itself.
+        fn checksum(words: &[u32]) -> u32 {
+            !words.iter().fold(0u32, |acc, word| acc.rotate_left(1) ^ word)
+        }
+
+        /// Writes the given profile to NVRAM.
+        pub fn set_profile(&mut self, profile: &Profile) {
+            let mut words = [
+                RECORD_MAGIC | PROFILE_STORAGE_LEN as u32,
+                profile.hysteresis.to_bits(),
+                profile.min_temp.to_bits(),
+                profile.ramp_temp.to_bits(),
+                profile.max_temp.to_bits(),
+                fail_safe_to_word(profile.fail_safe),
+                u32::from(profile.max_duty_cap),
+                // A valid profile's timeout always fits, an invalid one is rejected when read back
+                u32::try_from(profile.bus_timeout.as_millis()).unwrap_or(0),
+                0,
+            ];
+            words[PROFILE_STORAGE_LEN - 1] = Self::checksum(&words[..PROFILE_STORAGE_LEN - 1]);
+
+            for (storage, word) in self.storage.iter_mut().zip(words) {
+                storage.write(word);
+            }
+        }
+
+        /// Retrieves the stored profile.
+        ///
+        /// Returns [`None`] if the stored record is invalid, implying that the NVRAM has never been initialized.
+        pub fn get_profile(&self) -> Option<Profile> {
+            let mut words = [0u32; PROFILE_STORAGE_LEN];
+            for (word, storage) in words.iter_mut().zip(self.storage.iter()) {
+                *word = storage.read();
+            }
+
+            if words[0] != RECORD_MAGIC | PROFILE_STORAGE_LEN as u32
+                || words[PROFILE_STORAGE_LEN - 1] != Self::checksum(&words[..PROFILE_STORAGE_LEN - 1])
+            {
+                return None;
+            }
+
+            let profile = Profile {
+                hysteresis: f32::from_bits(words[1]),
+                min_temp: f32::from_bits(words[2]),
+                ramp_temp: f32::from_bits(words[3]),
+                max_temp: f32::from_bits(words[4]),
+                fail_safe: fail_safe_from_word(words[5])?,
+                max_duty_cap: u8::try_from(words[6]).ok()?,
+                bus_timeout: Duration::from_millis(u64::from(words[7])),
+            };
+            profile.is_valid().then_some(profile)
         }
     }
 }
+pub use profile_storage::ProfileStorage;
 
-struct ServiceInner<T: fan::Driver, const SAMPLE_BUF_LEN: usize> {
+struct ServiceInner<'hw, T: fan::Driver, const SAMPLE_BUF_LEN: usize> {
     driver: Mutex<GlobalRawMutex, T>,
     state: Mutex<GlobalRawMutex, fan::State>,
     en_signal: Signal<GlobalRawMutex, ()>,
     config: Mutex<GlobalRawMutex, Config>,
     samples: Mutex<GlobalRawMutex, SampleBuf<u16, SAMPLE_BUF_LEN>>,
+    sensor_failed: Mutex<GlobalRawMutex, bool>,
+    commanded_duty: Mutex<GlobalRawMutex, u8>,
+    profile_storage: Mutex<GlobalRawMutex, Option<ProfileStorage<'hw>>>,
 }
 
-impl<T: fan::Driver, const SAMPLE_BUF_LEN: usize> ServiceInner<T, SAMPLE_BUF_LEN> {
-    fn new(driver: T, config: Config) -> Self {
+impl<'hw, T: fan::Driver, const SAMPLE_BUF_LEN: usize> ServiceInner<'hw, T, SAMPLE_BUF_LEN> {
+    fn new(driver: T, mut config: Config, profile_storage: Option<ProfileStorage<'hw>>) -> Self {
+        if let Some(storage) = &profile_storage {
+            match storage.get_profile() {
+                Some(profile) => {
+                    info!("Loaded fan profile from NVRAM: {:?}", profile);
+                    config.set_profile(&profile);
+                }
+                None => warn!("No valid fan profile in NVRAM, using default profile"),
+            }
+        }
+
         Self {
             driver: Mutex::new(driver),
             state: Mutex::new(fan::State::Off),
             en_signal: Signal::new(),
             config: Mutex::new(config),
             samples: Mutex::new(SampleBuf::create()),
+            sensor_failed: Mutex::new(false),
+            commanded_duty: Mutex::new(0),
+            profile_storage: Mutex::new(profile_storage),
         }
     }
 
     async fn handle_sampling(&self) {
+        let sample_phase = self.config.lock().await.sample_phase;
+        Timer::after(sample_phase).await;
+
         loop {
-            match self.driver.lock().await.rpm().await {
+            match with_bus_timeout!(self, self.driver.lock().await.rpm()) {
                 Ok(rpm) => self.samples.lock().await.push(rpm),
-                Err(e) => error!("Fan error sampling fan rpm: {:?}", e.kind()),
+                Err(e) => error!("Fan error sampling fan rpm: {:?}", e),
             }
 
             let period = self.config.lock().await.sample_period;
@@ -74,30 +337,54 @@ impl<T: fan::Driver, const SAMPLE_BUF_LEN: usize> ServiceInner<T, SAMPLE_BUF_LEN
         }
     }
 
+    /// Records the duty cycle percentage the fan was last commanded to run at.
+    async fn record_duty(&self, duty: u8) {
+        *self.commanded_duty.lock().await = duty;
+    }
+
+    /// Records the RPM the fan was last commanded to run at, as a percentage of its maximum RPM.
+    async fn record_rpm(&self, rpm: u16) {
+        let max_rpm = self.driver.lock().await.max_rpm();
+        let duty = (u32::from(rpm) * 100)
+            .checked_div(u32::from(max_rpm))
+            .unwrap_or(0)
+            .min(100);
+        self.record_duty(duty as u8).await;
+    }
+
     async fn change_state(&self, to: fan::State) -> Result<(), fan::Error> {
-        let mut driver = self.driver.lock().await;
         match to {
             fan::State::Off => {
-                driver.stop().await.map_err(|_| fan::Error::Hardware)?;
+                with_bus_timeout!(self, self.driver.lock().await.stop())?;
+                self.record_duty(0).await;
             }
             fan::State::On(fan::OnState::Min) => {
-                driver.start().await.map_err(|_| fan::Error::Hardware)?;
+                with_bus_timeout!(self, self.driver.lock().await.start())?;
+                let min_start_rpm = self.driver.lock().await.min_start_rpm();
+                self.record_rpm(min_start_rpm).await;
             }
             fan::State::On(fan::OnState::Ramping) => {
                 // Ramp state will continuously update RPM according to its ramp response function
             }
             fan::State::On(fan::OnState::Max) => {
-                let max_rpm = driver.max_rpm();
-                let _ = driver.set_speed_rpm(max_rpm).await.map_err(|_| fan::Error::Hardware)?;
+                with_bus_timeout!(self, async {
+                    let mut driver = self.driver.lock().await;
+                    let max_rpm = driver.max_rpm();
+                    driver.set_speed_rpm(max_rpm).await
+                })?;
+                self.record_duty(100).await;
             }
         }
-        drop(driver);
 
+        self.set_state(to).await;
+        Ok(())
+    }
+
+    /// Records a state transition without commanding the fan, for transitions whose speed was already set.
+    async fn set_state(&self, to: fan::State) {
         let mut state = self.state.lock().await;
         trace!("Fan transitioned to {:?} state from {:?} state", to, *state);
         *state = to;
-
-        Ok(())
     }
 }
 
@@ -109,7 +396,7 @@ pub struct Service<
     E: NonBlockingSender<fan::Event>,
     const SAMPLE_BUF_LEN: usize,
 > {
-    inner: &'hw ServiceInner<T, SAMPLE_BUF_LEN>,
+    inner: &'hw ServiceInner<'hw, T, SAMPLE_BUF_LEN>,
     _phantom: PhantomData<(S, E)>,
 }
 
@@ -155,47 +442,30 @@ impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Ev
     }
 
     async fn rpm_immediate(&self) -> Result<u16, fan::Error> {
-        self.inner
-            .driver
-            .lock()
-            .await
-            .rpm()
-            .await
-            .map_err(|_| fan::Error::Hardware)
+        with_bus_timeout!(self.inner, self.inner.driver.lock().await.rpm())
+    }
+
+    async fn duty_percent(&self) -> u8 {
+        *self.inner.commanded_duty.lock().await
     }
 
     async fn set_rpm(&self, rpm: u16) -> Result<(), fan::Error> {
-        self.inner
-            .driver
-            .lock()
-            .await
-            .set_speed_rpm(rpm)
-            .await
-            .map_err(|_| fan::Error::Hardware)?;
+        with_bus_timeout!(self.inner, self.inner.driver.lock().await.set_speed_rpm(rpm))?;
+        self.inner.record_rpm(rpm).await;
         self.inner.config.lock().await.auto_control = false;
         Ok(())
     }
 
     async fn set_duty_percent(&self, duty: u8) -> Result<(), fan::Error> {
-        self.inner
-            .driver
-            .lock()
-            .await
-            .set_speed_percent(duty)
-            .await
-            .map_err(|_| fan::Error::Hardware)?;
+        with_bus_timeout!(self.inner, self.inner.driver.lock().await.set_speed_percent(duty))?;
+        self.inner.record_duty(duty).await;
         self.inner.config.lock().await.auto_control = false;
         Ok(())
     }
 
     async fn stop(&self) -> Result<(), fan::Error> {
-        self.inner
-            .driver
-            .lock()
-            .await
-            .stop()
-            .await
-            .map_err(|_| fan::Error::Hardware)?;
+        with_bus_timeout!(self.inner, self.inner.driver.lock().await.stop())?;
+        self.inner.record_duty(0).await;
         self.inner.config.lock().await.auto_control = false;
         Ok(())
     }
@@ -237,16 +507,26 @@ pub struct InitParams<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlock
     pub sensor_service: S,
     /// Event senders for fan events.
     pub event_senders: &'hw mut [E],
+    /// Optional NVRAM backing for the fan profile.
+    ///
+    /// If present, a valid stored profile overrides the profile in `config` and [`Service::set_profile`] persists
+    /// the new profile.
+    pub profile_storage: Option<ProfileStorage<'hw>>,
+    /// How the temperature used for automatic control is derived.
+    pub aggregation: Aggregation<'hw, S>,
 }
 
 /// The memory resources required by the fan.
-pub struct Resources<T: fan::Driver, const SAMPLE_BUF_LEN: usize> {
-    inner: Option<ServiceInner<T, SAMPLE_BUF_LEN>>,
+///
+/// `SAMPLE_BUF_LEN` is the number of RPM samples averaged by [`fan::FanService::rpm_average`], and can be chosen per
+/// fan. It must be at least 1, a zero length fails to compile.
+pub struct Resources<'hw, T: fan::Driver, const SAMPLE_BUF_LEN: usize> {
+    inner: Option<ServiceInner<'hw, T, SAMPLE_BUF_LEN>>,
 }
 
 // Note: We can't derive Default unless we trait bound T by Default,
 // but we don't want that restriction since the default is just the None case
-impl<T: fan::Driver, const SAMPLE_BUF_LEN: usize> Default for Resources<T, SAMPLE_BUF_LEN> {
+impl<T: fan::Driver, const SAMPLE_BUF_LEN: usize> Default for Resources<'_, T, SAMPLE_BUF_LEN> {
     fn default() -> Self {
         Self { inner: None }
     }
@@ -260,9 +540,12 @@ pub struct Runner<
     E: NonBlockingSender<fan::Event>,
     const SAMPLE_BUF_LEN: usize,
 > {
-    service: &'hw ServiceInner<T, SAMPLE_BUF_LEN>,
+    service: &'hw ServiceInner<'hw, T, SAMPLE_BUF_LEN>,
     sensor: S,
+    aggregation: Aggregation<'hw, S>,
     event_senders: &'hw mut [E],
+    // Whether the fail-safe behavior is currently applied
+    fail_safe_active: bool,
 }
 
 impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Event>, const SAMPLE_BUF_LEN: usize>
@@ -279,9 +562,10 @@ impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Ev
     async fn ramp_response(&self, temp: DegreesCelsius) -> Result<(), fan::Error> {
         let config = *self.service.config.lock().await;
 
-        let mut driver = self.service.driver.lock().await;
+        let driver = self.service.driver.lock().await;
         let min_rpm = driver.min_start_rpm();
         let max_rpm = driver.max_rpm();
+        drop(driver);
 
         // Provide a linear fan response between its min and max RPM relative to temperature between ramp start and max temp
         let rpm = if temp <= config.ramp_temp {
@@ -294,11 +578,23 @@ impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Ev
             min_rpm + (ratio * range) as u16
         };
 
-        driver
-            .set_speed_rpm(rpm)
-            .await
-            .map(|_| ())
-            .map_err(|_| fan::Error::Hardware)
+        self.set_curve_rpm(temp, rpm).await
+    }
+
+    /// Sets the RPM requested by the temperature curve, limited by the duty cap unless the temperature has reached
+    /// the sensor's critical threshold.
+    async fn set_curve_rpm(&self, temp: DegreesCelsius, rpm: u16) -> Result<(), fan::Error> {
+        let max_duty_cap = self.service.config.lock().await.max_duty_cap;
+        let rpm = if temp >= self.sensor.threshold(sensor::Threshold::Critical).await {
+            rpm
+        } else {
+            let max_rpm = self.service.driver.lock().await.max_rpm();
+            rpm.min((u32::from(max_rpm) * u32::from(max_duty_cap) / 100) as u16)
+        };
+
+        with_bus_timeout!(self.service, self.service.driver.lock().await.set_speed_rpm(rpm))?;
+        self.service.record_rpm(rpm).await;
+        Ok(())
     }
 
     async fn handle_fan_off_state(&self, temp: DegreesCelsius) -> Result<(), fan::Error> {
@@ -329,7 +625,9 @@ impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Ev
         if temp < (config.ramp_temp - config.hysteresis) {
             self.service.change_state(fan::State::On(fan::OnState::Min)).await?;
         } else if temp >= config.max_temp {
-            self.service.change_state(fan::State::On(fan::OnState::Max)).await?;
+            // Follow the curve into max rather than changing state directly, which would briefly ignore the duty cap
+            self.ramp_response(temp).await?;
+            self.service.set_state(fan::State::On(fan::OnState::Max)).await;
         } else {
             self.ramp_response(temp).await?;
         }
@@ -342,6 +640,10 @@ impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Ev
 
         if temp < (config.max_temp - config.hysteresis) {
             self.service.change_state(fan::State::On(fan::OnState::Ramping)).await?;
+        } else {
+            // Re-applied every update since the cap no longer applies once the temperature turns critical
+            let max_rpm = self.service.driver.lock().await.max_rpm();
+            self.set_curve_rpm(temp, max_rpm).await?;
         }
 
         Ok(())
@@ -357,11 +659,70 @@ impl<'hw, T: fan::Driver, S: sensor::SensorService, E: NonBlockingSender<fan::Ev
         }
     }
 
+    async fn apply_fail_safe(&self) -> Result<(), fan::Error> {
+        let fail_safe = self.service.config.lock().await.fail_safe;
+        warn!("Fan sensor failed, applying fail-safe {:?}", fail_safe);
+
+        match fail_safe {
+            FailSafe::FullSpeed => self.service.change_state(fan::State::On(fan::OnState::Max)).await,
+            FailSafe::HoldLast => Ok(()),
+            FailSafe::FixedDuty(duty) => {
+                with_bus_timeout!(self.service, self.service.driver.lock().await.set_speed_percent(duty))?;
+                self.service.record_duty(duty).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the temperature driving automatic control, [`None`] if no sensor is available.
+    async fn control_temperature(&self) -> Option<DegreesCelsius> {
+        match self.aggregation {
+            Aggregation::Single => Some(self.sensor.temperature().await),
+            Aggregation::Weighted(sensors) => {
+                let mut weighted_sum = 0.0;
+                let mut total_weight = 0.0;
+                for (sensor, weight) in sensors {
+                    if !sensor.heartbeat().await.sampling_enabled {
+                        continue;
+                    }
+                    weighted_sum += sensor.temperature().await * weight;
+                    total_weight += weight;
+                }
+
+                (total_weight > 0.0).then(|| weighted_sum / total_weight)
+            }
+        }
+    }
+
+    async fn handle_sensor_state(&mut self) -> Result<(), fan::Error> {
+        let temp = if *self.service.sensor_failed.lock().await {
+            None
+        } else {
+            self.control_temperature().await
+        };
+
+        let Some(temp) = temp else {
+            if !self.fail_safe_active {
+                self.fail_safe_active = true;
+                self.apply_fail_safe().await?;
+            }
+            return Ok(());
+        };
+
+        if self.fail_safe_active {
+            // The fan speed no longer matches the state, so restart the state machine from off
+            info!("Fan sensor recovered, resuming normal control");
+            self.fail_safe_active = false;
+            self.service.change_state(fan::State::Off).await?;
+        }
+
+        self.handle_fan_state(temp).await
+    }
+
     async fn handle_auto_control(&mut self) {
         loop {
             if self.service.config.lock().await.auto_control {
-                let temp = self.sensor.temperature().await;
-                if let Err(e) = self.handle_fan_state(temp).await {
+                if let Err(e) = self.handle_sensor_state().await {
                     error!("Error handling fan state transition, disabling auto control: {:?}", e);
                     self.service.config.lock().await.auto_control = false;
                     self.broadcast_event(fan::Event::Failure(e));
@@ -403,7 +764,7 @@ impl<
 > odp_service_common::runnable_service::Service<'hw> for Service<'hw, T, S, E, SAMPLE_BUF_LEN>
 {
     type Runner = Runner<'hw, T, S, E, SAMPLE_BUF_LEN>;
-    type Resources = Resources<T, SAMPLE_BUF_LEN>;
+    type Resources = Resources<'hw, T, SAMPLE_BUF_LEN>;
 }
 
 impl<
@@ -416,12 +777,14 @@ impl<
 {
     /// Initializes an instance of the fan service.
     pub async fn new(
-        service_storage: &'hw mut Resources<T, SAMPLE_BUF_LEN>,
+        service_storage: &'hw mut Resources<'hw, T, SAMPLE_BUF_LEN>,
         init_params: InitParams<'hw, T, S, E>,
     ) -> Result<(Self, Runner<'hw, T, S, E, SAMPLE_BUF_LEN>), fan::Error> {
-        let service = service_storage
-            .inner
-            .insert(ServiceInner::new(init_params.driver, init_params.config));
+        let service = service_storage.inner.insert(ServiceInner::new(
+            init_params.driver,
+            init_params.config,
+            init_params.profile_storage,
+        ));
         Ok((
             Self {
                 inner: service,
@@ -430,8 +793,533 @@ impl<
             Runner {
                 service,
                 sensor: init_params.sensor_service,
+                aggregation: init_params.aggregation,
                 event_senders: init_params.event_senders,
+                fail_safe_active: false,
             },
         ))
     }
+
+    /// Returns the temperature curve used for automatic fan control.
+    pub async fn profile(&self) -> Profile {
+        self.inner.config.lock().await.profile()
+    }
+
+    /// Sets the temperature curve used for automatic fan control, persisting it to NVRAM if storage was provided.
+    ///
+    /// Unlike [`fan::FanService::set_state_temp`], changes made through this method survive a reset.
+    pub async fn set_profile(&self, profile: Profile) -> Result<(), fan::Error> {
+        if !profile.is_valid() {
+            error!("Invalid fan profile: {:?}", profile);
+            return Err(fan::Error::InvalidArgument);
+        }
+
+        self.inner.config.lock().await.set_profile(&profile);
+        if let Some(storage) = self.inner.profile_storage.lock().await.as_mut() {
+            storage.set_profile(&profile);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the fan currently follows the temperature curve or a manual command.
+    pub async fn control_mode(&self) -> ControlMode {
+        if self.inner.config.lock().await.auto_control {
+            ControlMode::Auto
+        } else {
+            ControlMode::Manual
+        }
+    }
+
+    /// Marks whether the sensor used for automatic control has failed, e.g. in response to a sensor failure event.
+    ///
+    /// While failed, automatic control applies the configured [`FailSafe`] instead of following the temperature
+    /// curve, and resumes normal control once the sensor is marked recovered.
+    pub async fn set_sensor_failed(&self, failed: bool) {
+        *self.inner.sensor_failed.lock().await = failed;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::mock::fan::MockFan;
+    use crate::mock::sensor::MockSensor;
+    use crate::sensor as sensor_service;
+    use crate::test_utils::{HangingFan, new_fan, new_fan_with_sensor, new_sensor, new_sensor_with_senders};
+    use embassy_futures::select::select;
+    use embassy_time::with_timeout;
+    use fan::FanService as _;
+    use odp_service_common::runnable_service::ServiceRunner;
+
+    /// Single NVRAM word
+    #[derive(Clone, Copy, Default)]
+    struct MockNvramWord(u32);
+
+    impl<'a> NvramStorage<'a, u32> for MockNvramWord {
+        fn read(&self) -> u32 {
+            self.0
+        }
+
+        fn write(&mut self, value: u32) {
+            self.0 = value;
+        }
+    }
+
+    fn storage(words: &mut [MockNvramWord; PROFILE_STORAGE_LEN]) -> ProfileStorage<'_> {
+        ProfileStorage::new(words.each_mut().map(|word| word as &mut dyn NvramStorage<'_, u32>))
+    }
+
+    /// Profile that differs from the default config in every field
+    const PROFILE: Profile = Profile {
+        hysteresis: 1.5,
+        min_temp: 30.0,
+        ramp_temp: 42.5,
+        max_temp: 60.0,
+        fail_safe: FailSafe::FixedDuty(75),
+        max_duty_cap: 80,
+        bus_timeout: Duration::from_millis(50),
+    };
+
+    /// Returns the profile a fan constructed over the given NVRAM words would use
+    fn loaded_profile(words: &mut [MockNvramWord; PROFILE_STORAGE_LEN]) -> Profile {
+        let inner = ServiceInner::<_, 1>::new(MockFan::new(), Config::default(), Some(storage(words)));
+        inner.config.into_inner().profile()
+    }
+
+    #[test]
+    fn test_profile_round_trip() {
+        let mut words = [MockNvramWord::default(); PROFILE_STORAGE_LEN];
+        storage(&mut words).set_profile(&PROFILE);
+
+        assert_eq!(storage(&mut words).get_profile(), Some(PROFILE));
+        assert_eq!(loaded_profile(&mut words), PROFILE);
+    }
+
+    #[test]
+    fn test_uninitialized_profile_uses_default() {
+        let mut words = [MockNvramWord::default(); PROFILE_STORAGE_LEN];
+        assert_eq!(storage(&mut words).get_profile(), None);
+        assert_eq!(loaded_profile(&mut words), Config::default().profile());
+
+        let mut words = [MockNvramWord(u32::MAX); PROFILE_STORAGE_LEN];
+        assert_eq!(storage(&mut words).get_profile(), None);
+        assert_eq!(loaded_profile(&mut words), Config::default().profile());
+    }
+
+    #[test]
+    fn test_corrupt_profile_uses_default() {
+        let mut words = [MockNvramWord::default(); PROFILE_STORAGE_LEN];
+        storage(&mut words).set_profile(&PROFILE);
+        words[2].0 ^= 1;
+
+        assert_eq!(storage(&mut words).get_profile(), None);
+        assert_eq!(loaded_profile(&mut words), Config::default().profile());
+    }
+
+    #[test]
+    fn test_invalid_profile_not_loaded() {
+        // Checksum is valid, but the state temperatures are out of order
+        let mut words = [MockNvramWord::default(); PROFILE_STORAGE_LEN];
+        storage(&mut words).set_profile(&Profile {
+            min_temp: 50.0,
+            ..PROFILE
+        });
+
+        assert_eq!(storage(&mut words).get_profile(), None);
+        assert_eq!(loaded_profile(&mut words), Config::default().profile());
+    }
+
+    #[test]
+    fn test_invalid_fail_safe_duty() {
+        let profile = Profile {
+            fail_safe: FailSafe::FixedDuty(101),
+            ..PROFILE
+        };
+        assert!(!profile.is_valid());
+    }
+
+    #[test]
+    fn test_invalid_max_duty_cap() {
+        let profile = Profile {
+            max_duty_cap: 101,
+            ..PROFILE
+        };
+        assert!(!profile.is_valid());
+    }
+
+    #[test]
+    fn test_invalid_bus_timeout() {
+        let profile = Profile {
+            bus_timeout: Duration::from_ticks(0),
+            ..PROFILE
+        };
+        assert!(!profile.is_valid());
+
+        // Stored in milliseconds as a single NVRAM word
+        let profile = Profile {
+            bus_timeout: Duration::from_millis(u64::from(u32::MAX) + 1),
+            ..PROFILE
+        };
+        assert!(!profile.is_valid());
+    }
+
+    /// Waits until the fan reports the given RPM
+    async fn wait_rpm(fan: &impl fan::FanService, rpm: u16) {
+        with_timeout(Duration::from_secs(1), async {
+            while fan.rpm_immediate().await.unwrap() != rpm {
+                Timer::after(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    async fn run_fail_safe_test(fail_safe: FailSafe, expected_rpm: u16) {
+        // Sampling is disabled so the sensor keeps reporting a temperature below the fan's minimum
+        let (service, runner, _sensor, _sensor_runner) = new_fan_with_sensor(
+            Config {
+                update_period: Duration::from_millis(1),
+                fail_safe,
+                ..MockFan::config()
+            },
+            sensor_service::Config {
+                sampling_enabled: false,
+                ..MockSensor::config()
+            },
+        )
+        .await;
+
+        select(runner.run(), async {
+            service.enable_auto_control().await.unwrap();
+            wait_rpm(&service, 0).await;
+
+            // Run the fan so holding the last speed is observable
+            service.inner.driver.lock().await.set_speed_rpm(3000).await.unwrap();
+            service.set_sensor_failed(true).await;
+            wait_rpm(&service, expected_rpm).await;
+            // Give the runner a few update periods to observe the failure
+            Timer::after(Duration::from_millis(10)).await;
+            assert_eq!(service.rpm_immediate().await.unwrap(), expected_rpm);
+
+            // Fan should return to following the temperature curve
+            service.set_sensor_failed(false).await;
+            wait_rpm(&service, 0).await;
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_bus_timeout() {
+        let (sensor, _sensor_runner) = new_sensor(MockSensor::config()).await;
+        let (service, _runner) = new_fan(HangingFan, Config::default(), sensor, Aggregation::Single).await;
+
+        // The timeout is part of the fan profile
+        service
+            .set_profile(Profile {
+                bus_timeout: Duration::from_millis(10),
+                ..service.profile().await
+            })
+            .await
+            .unwrap();
+
+        let result = with_timeout(Duration::from_secs(1), service.rpm_immediate()).await;
+        assert_eq!(result, Ok(Err(fan::Error::Timeout)));
+
+        // The timed out read must release the driver for other users
+        assert_eq!(
+            with_timeout(Duration::from_secs(1), service.set_rpm(3000)).await,
+            Ok(Ok(()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inject_sensor_temp() {
+        use embassy_sync::channel::Channel;
+        use sensor::SensorService as _;
+
+        static CHANNEL: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+
+        // Thresholds and the fan curve sit above the mock's sawtooth, so only an injected temperature reaches them
+        let (temp_sensor, sensor_runner) = new_sensor_with_senders(
+            sensor_service::Config {
+                sample_period: Duration::from_millis(1),
+                warn_high_threshold: 50.0,
+                prochot_threshold: 60.0,
+                critical_threshold: 70.0,
+                ..MockSensor::config()
+            },
+            [CHANNEL.dyn_sender()],
+        )
+        .await;
+        let (fan_service, fan_runner) = new_fan(
+            MockFan::new(),
+            Config {
+                update_period: Duration::from_millis(1),
+                min_temp: 50.0,
+                ramp_temp: 60.0,
+                max_temp: 70.0,
+                ..MockFan::config()
+            },
+            temp_sensor,
+            Aggregation::Single,
+        )
+        .await;
+
+        let sensors = [temp_sensor];
+        let fans = [fan_service];
+        let mut thermal_resources = crate::Resources::default();
+        let thermal = crate::Service::init(
+            &mut thermal_resources,
+            crate::InitParams {
+                sensors: &sensors,
+                fans: &fans,
+            },
+        );
+
+        select(
+            sensor_runner.run(),
+            select(fan_runner.run(), async {
+                fan_service.enable_auto_control().await.unwrap();
+                wait_rpm(&fan_service, 0).await;
+
+                thermal.inject_sensor_temp(0, 75.0).await.unwrap();
+                for threshold in [
+                    sensor::Threshold::WarnHigh,
+                    sensor::Threshold::Prochot,
+                    sensor::Threshold::Critical,
+                ] {
+                    let event = with_timeout(Duration::from_secs(1), CHANNEL.receive()).await.unwrap();
+                    assert_eq!(event, sensor::Event::ThresholdExceeded(threshold));
+                }
+                assert_eq!(temp_sensor.temperature().await, 75.0);
+                wait_rpm(&fan_service, 6000).await;
+
+                // Clearing the injected temperature returns to the mock's readings
+                thermal.clear_sensor_temp(0).await.unwrap();
+                for threshold in [
+                    sensor::Threshold::WarnHigh,
+                    sensor::Threshold::Prochot,
+                    sensor::Threshold::Critical,
+                ] {
+                    let event = with_timeout(Duration::from_secs(1), CHANNEL.receive()).await.unwrap();
+                    assert_eq!(event, sensor::Event::ThresholdCleared(threshold));
+                }
+                wait_rpm(&fan_service, 0).await;
+
+                // No sensor registered with this ID
+                assert!(thermal.inject_sensor_temp(1, 75.0).await.is_none());
+            }),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_max_duty_cap() {
+        use sensor_service::TemperatureInjection as _;
+
+        let (service, runner, temp_sensor, sensor_runner) = new_fan_with_sensor(
+            Config {
+                update_period: Duration::from_millis(1),
+                min_temp: 50.0,
+                ramp_temp: 60.0,
+                max_temp: 70.0,
+                max_duty_cap: 60,
+                ..MockFan::config()
+            },
+            sensor_service::Config {
+                sample_period: Duration::from_millis(1),
+                critical_threshold: 80.0,
+                ..MockSensor::config()
+            },
+        )
+        .await;
+
+        select(
+            sensor_runner.run(),
+            select(runner.run(), async {
+                service.enable_auto_control().await.unwrap();
+
+                // The curve asks for full speed, but is held at the cap
+                temp_sensor.inject_temperature(75.0).await;
+                wait_rpm(&service, 3600).await;
+                // Give the runner a few update periods to (not) exceed the cap
+                Timer::after(Duration::from_millis(10)).await;
+                assert_eq!(service.rpm_immediate().await.unwrap(), 3600);
+                // Including while entering the max state
+                assert!(
+                    service
+                        .inner
+                        .driver
+                        .lock()
+                        .await
+                        .commanded_rpms()
+                        .all(|rpm| rpm <= 3600)
+                );
+
+                // A critical temperature overrides the cap
+                temp_sensor.inject_temperature(85.0).await;
+                wait_rpm(&service, 6000).await;
+
+                // And the cap applies again once the temperature is no longer critical
+                temp_sensor.inject_temperature(75.0).await;
+                wait_rpm(&service, 3600).await;
+            }),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_weighted_aggregation() {
+        use sensor::SensorService as _;
+        use sensor_service::TemperatureInjection as _;
+
+        let sensor_config = sensor_service::Config {
+            sample_period: Duration::from_millis(1),
+            critical_threshold: 80.0,
+            ..MockSensor::config()
+        };
+        let (cpu, cpu_runner) = new_sensor(sensor_config).await;
+        let (ssd, ssd_runner) = new_sensor(sensor_config).await;
+
+        // Weights are normalized to 75% CPU and 25% SSD
+        let blend = [(cpu, 3.0), (ssd, 1.0)];
+        let (service, runner) = new_fan(
+            MockFan::new(),
+            Config {
+                update_period: Duration::from_millis(1),
+                min_temp: 50.0,
+                ramp_temp: 60.0,
+                max_temp: 70.0,
+                ..MockFan::config()
+            },
+            cpu,
+            Aggregation::Weighted(&blend),
+        )
+        .await;
+
+        select(
+            select(cpu_runner.run(), ssd_runner.run()),
+            select(runner.run(), async {
+                service.enable_auto_control().await.unwrap();
+
+                // Neither sensor alone is at the blended temperature of 64 degrees, 40% of the way up the ramp
+                cpu.inject_temperature(68.0).await;
+                ssd.inject_temperature(52.0).await;
+                wait_rpm(&service, 3000).await;
+
+                // A paused sensor isn't a failed one, its most recent sample is still used
+                ssd.pause_sampling().await;
+                Timer::after(Duration::from_millis(20)).await;
+                assert_eq!(service.rpm_immediate().await.unwrap(), 3000);
+                ssd.resume_sampling().await;
+
+                // A failed sensor's weight is redistributed, leaving the CPU at 80% of the way up the ramp
+                ssd.disable_sampling().await;
+                wait_rpm(&service, 5000).await;
+
+                // With no sensor left, the fail-safe applies
+                cpu.disable_sampling().await;
+                wait_rpm(&service, 6000).await;
+            }),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_duty_percent() {
+        use sensor_service::TemperatureInjection as _;
+
+        let (service, runner, temp_sensor, sensor_runner) = new_fan_with_sensor(
+            Config {
+                update_period: Duration::from_millis(1),
+                ..MockFan::config()
+            },
+            sensor_service::Config {
+                sample_period: Duration::from_millis(1),
+                critical_threshold: 80.0,
+                ..MockSensor::config()
+            },
+        )
+        .await;
+
+        select(
+            sensor_runner.run(),
+            select(runner.run(), async {
+                service
+                    .set_profile(Profile {
+                        min_temp: 50.0,
+                        ramp_temp: 60.0,
+                        max_temp: 70.0,
+                        ..service.profile().await
+                    })
+                    .await
+                    .unwrap();
+                service.enable_auto_control().await.unwrap();
+                assert_eq!(service.duty_percent().await, 0);
+
+                // 64 degrees is 40% of the way up the ramp, halfway between the fan's start and maximum RPM
+                temp_sensor.inject_temperature(64.0).await;
+                wait_rpm(&service, 3000).await;
+                assert_eq!(service.duty_percent().await, 50);
+
+                // A manual command is reported as given
+                service.set_duty_percent(30).await.unwrap();
+                assert_eq!(service.duty_percent().await, 30);
+                service.stop().await.unwrap();
+                assert_eq!(service.duty_percent().await, 0);
+            }),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_control_mode() {
+        // Sampling is disabled so the sensor keeps reporting a temperature below the fan's minimum
+        let (service, runner, _sensor, _sensor_runner) = new_fan_with_sensor(
+            Config {
+                update_period: Duration::from_millis(1),
+                ..MockFan::config()
+            },
+            sensor_service::Config {
+                sampling_enabled: false,
+                ..MockSensor::config()
+            },
+        )
+        .await;
+
+        select(runner.run(), async {
+            assert_eq!(service.control_mode().await, ControlMode::Auto);
+            wait_rpm(&service, 0).await;
+
+            // A fixed duty holds even though the curve would keep the fan off
+            service.set_duty_percent(50).await.unwrap();
+            assert_eq!(service.control_mode().await, ControlMode::Manual);
+            wait_rpm(&service, 3000).await;
+            // Give the runner a few update periods to (not) apply the curve
+            Timer::after(Duration::from_millis(10)).await;
+            assert_eq!(service.rpm_immediate().await.unwrap(), 3000);
+
+            // Back to following the curve
+            service.enable_auto_control().await.unwrap();
+            assert_eq!(service.control_mode().await, ControlMode::Auto);
+            wait_rpm(&service, 0).await;
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_fail_safe_full_speed() {
+        run_fail_safe_test(FailSafe::FullSpeed, 6000).await;
+    }
+
+    #[tokio::test]
+    async fn test_fail_safe_hold_last() {
+        run_fail_safe_test(FailSafe::HoldLast, 3000).await;
+    }
+
+    #[tokio::test]
+    async fn test_fail_safe_fixed_duty() {
+        run_fail_safe_test(FailSafe::FixedDuty(40), 2400).await;
+    }
 }
@@ -0,0 +1,131 @@
+//! Bounded history of thermal events for diagnostics.
+use core::cell::RefCell;
+use embassy_sync::blocking_mutex::Mutex;
+use embedded_services::GlobalRawMutex;
+use embedded_services::event::NonBlockingSender;
+use heapless::Deque;
+
+/// Retains the most recent `N` events sent to it.
+///
+/// A reference to the history implements [`NonBlockingSender`], so it can be registered alongside other event
+/// senders for a sensor or fan. Once full, the oldest event is evicted to make room for each new one.
+pub struct EventHistory<E: Copy, const N: usize> {
+    events: Mutex<GlobalRawMutex, RefCell<Deque<E, N>>>,
+}
+
+impl<E: Copy, const N: usize> EventHistory<E, N> {
+    /// Create a new, empty event history.
+    pub const fn new() -> Self {
+        Self {
+            events: Mutex::new(RefCell::new(Deque::new())),
+        }
+    }
+
+    /// Record an event, evicting the oldest if the history is full.
+    pub fn record(&self, event: E) {
+        self.events.lock(|events| {
+            let mut events = events.borrow_mut();
+            if events.is_full() {
+                let _ = events.pop_front();
+            }
+
+            // There will always be room in the buffer if we get here
+            let _ = events.push_back(event);
+        });
+    }
+
+    /// Returns a snapshot of the retained events, from oldest to most recent.
+    pub fn recent_events(&self) -> heapless::Vec<E, N> {
+        self.events.lock(|events| events.borrow().iter().copied().collect())
+    }
+
+    /// Returns the most recently recorded event, if any.
+    ///
+    /// Unlike receiving from an event channel this doesn't consume the event, so any number of observers can poll it
+    /// without taking events from the handler they are delivered to.
+    pub fn last_event(&self) -> Option<E> {
+        self.events.lock(|events| events.borrow().back().copied())
+    }
+
+    /// Discard all retained events.
+    pub fn clear(&self) {
+        self.events.lock(|events| events.borrow_mut().clear());
+    }
+}
+
+impl<E: Copy, const N: usize> Default for EventHistory<E, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: Copy, const N: usize> NonBlockingSender<E> for &EventHistory<E, N> {
+    fn try_send(&mut self, event: E) -> Option<()> {
+        self.record(event);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_sync::channel::Channel;
+    use thermal_service_interface::sensor;
+
+    #[test]
+    fn test_event_history_retains_latest() {
+        let history: EventHistory<sensor::Event, 3> = EventHistory::new();
+        assert!(history.recent_events().is_empty());
+
+        let events = [
+            sensor::Event::ThresholdExceeded(sensor::Threshold::WarnLow),
+            sensor::Event::ThresholdCleared(sensor::Threshold::WarnLow),
+            sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh),
+            sensor::Event::Failure(sensor::Error::RetryExhausted),
+            sensor::Event::ThresholdExceeded(sensor::Threshold::Critical),
+        ];
+
+        let mut sender = &history;
+        for event in events {
+            assert_eq!(sender.try_send(event), Some(()));
+        }
+
+        // Only the latest 3 events are retained, in the order they were sent
+        let [_, _, latest @ ..] = events;
+        assert_eq!(history.recent_events(), latest);
+
+        history.clear();
+        assert!(history.recent_events().is_empty());
+    }
+
+    #[test]
+    fn test_event_history_last_event() {
+        let history: EventHistory<sensor::Event, 3> = EventHistory::new();
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        assert_eq!(history.last_event(), None);
+
+        let events = [
+            sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh),
+            sensor::Event::ThresholdExceeded(sensor::Threshold::Prochot),
+            sensor::Event::ThresholdCleared(sensor::Threshold::Prochot),
+        ];
+
+        // Deliver each event to both the history and the handler's channel, as a sensor with both registered would
+        let mut history_sender = &history;
+        let mut channel_sender = channel.dyn_sender();
+        for event in events {
+            assert_eq!(history_sender.try_send(event), Some(()));
+            assert_eq!(channel_sender.try_send(event), Some(()));
+
+            // Peeking repeatedly always returns the latest event
+            assert_eq!(history.last_event(), Some(event));
+            assert_eq!(history.last_event(), Some(event));
+        }
+
+        // The handler still receives every event in order
+        for event in events {
+            assert_eq!(channel.try_receive(), Ok(event));
+        }
+        assert!(channel.try_receive().is_err());
+    }
+}
@@ -1,13 +1,18 @@
 //! Thermal service
 #![no_std]
 
+use embedded_sensors_hal_async::temperature::DegreesCelsius;
 use thermal_service_interface::{fan::FanService, sensor::SensorService};
 
 pub mod fan;
-#[cfg(feature = "mock")]
+pub mod history;
+#[cfg(any(test, feature = "mock"))]
 pub mod mock;
 pub mod sensor;
+#[cfg(test)]
+mod test_utils;
 mod utils;
+pub mod watchdog;
 
 struct ServiceInner<'hw, S: SensorService, F: FanService> {
     sensors: &'hw [S],
@@ -49,6 +54,10 @@ impl<S: SensorService, F: FanService> Default for Resources<'_, S, F> {
 
 impl<'hw, S: SensorService, F: FanService> Service<'hw, S, F> {
     /// Initializes the thermal service with the provided sensors and fans.
+    ///
+    /// The set of sensors and fans is fixed here, there is no way to register another device afterwards. Each
+    /// sensor and fan is sampled or controlled by its own runner, which the caller spawns independently of this
+    /// service.
     pub fn init(resources: &'hw mut Resources<'hw, S, F>, init_params: InitParams<'hw, S, F>) -> Self {
         let inner = resources.inner.insert(ServiceInner {
             sensors: init_params.sensors,
@@ -56,6 +65,82 @@ impl<'hw, S: SensorService, F: FanService> Service<'hw, S, F> {
         });
         Self { inner }
     }
+
+    /// Returns the registered sensors for which `pred` returns true, along with their instance IDs.
+    pub fn sensors_where<P: Fn(&S) -> bool>(&self, pred: P) -> impl Iterator<Item = (u8, &'hw S)> {
+        self.inner
+            .sensors
+            .iter()
+            .enumerate()
+            .filter(move |(_, sensor)| pred(sensor))
+            .map(|(id, sensor)| (id as u8, sensor))
+    }
+
+    /// Set every registered fan to `duty` percent concurrently, e.g. to cool down as fast as possible on a critical
+    /// thermal event.
+    ///
+    /// Returns the result for each fan by instance ID. `N` must be the number of registered fans, otherwise no fan
+    /// is commanded and [`InvalidArgument`](thermal_service_interface::fan::Error::InvalidArgument) is returned.
+    ///
+    /// As with [`FanService::set_duty_percent`], this takes precedence over automatic control, which stays
+    /// disabled on each commanded fan until it is re-enabled with [`FanService::enable_auto_control`].
+    pub async fn set_all_fans<const N: usize>(
+        &self,
+        duty: u8,
+    ) -> Result<[Result<(), thermal_service_interface::fan::Error>; N], thermal_service_interface::fan::Error> {
+        let fans: &[F; N] = self
+            .inner
+            .fans
+            .try_into()
+            .map_err(|_| thermal_service_interface::fan::Error::InvalidArgument)?;
+        Ok(embassy_futures::join::join_array(fans.each_ref().map(|fan| fan.set_duty_percent(duty))).await)
+    }
+
+    /// Sample every registered sensor now, e.g. for a synchronized snapshot rather than readings taken on each
+    /// sensor's independent sample period.
+    ///
+    /// Returns the sample for each sensor by instance ID. `N` must be the number of registered sensors, otherwise no
+    /// sensor is sampled and [`InvalidArgument`](thermal_service_interface::sensor::Error::InvalidArgument) is
+    /// returned.
+    pub async fn sample_all_now<const N: usize>(
+        &self,
+    ) -> Result<
+        [Result<DegreesCelsius, thermal_service_interface::sensor::Error>; N],
+        thermal_service_interface::sensor::Error,
+    > {
+        let sensors: &[S; N] = self
+            .inner
+            .sensors
+            .try_into()
+            .map_err(|_| thermal_service_interface::sensor::Error::InvalidArgument)?;
+        Ok(embassy_futures::join::join_array(sensors.each_ref().map(|sensor| sensor.sample_now())).await)
+    }
+
+    /// Returns the label of the sensor with instance ID `id`.
+    ///
+    /// Returns `None` if no sensor is registered with that ID or the sensor is unlabeled.
+    pub fn get_sensor_label(&self, id: u8) -> Option<&'static str> {
+        self.inner.sensors.get(id as usize)?.label()
+    }
+}
+
+#[cfg(any(test, feature = "test-hooks"))]
+impl<'hw, S: SensorService + sensor::TemperatureInjection, F: FanService> Service<'hw, S, F> {
+    /// Override the temperature reported by the sensor with instance ID `id`.
+    ///
+    /// Returns `None` if no sensor is registered with that ID.
+    pub async fn inject_sensor_temp(&self, id: u8, temp: DegreesCelsius) -> Option<()> {
+        self.inner.sensors.get(id as usize)?.inject_temperature(temp).await;
+        Some(())
+    }
+
+    /// Clear an injected temperature for the sensor with instance ID `id`.
+    ///
+    /// Returns `None` if no sensor is registered with that ID.
+    pub async fn clear_sensor_temp(&self, id: u8) -> Option<()> {
+        self.inner.sensors.get(id as usize)?.clear_injected_temperature().await;
+        Some(())
+    }
 }
 
 impl<'hw, S: SensorService + Copy, F: FanService + Copy> thermal_service_interface::ThermalService
@@ -72,3 +157,301 @@ impl<'hw, S: SensorService + Copy, F: FanService + Copy> thermal_service_interfa
         self.inner.fans.get(id as usize).copied()
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use embassy_time::{Duration, Instant};
+    use embedded_sensors_hal_async::temperature::DegreesCelsius;
+    use embedded_services::event::NoopSender;
+    use odp_service_common::runnable_service::ServiceRunner;
+    use thermal_service_interface::sensor::{Capabilities, Error, Heartbeat, Threshold, ThresholdSetting, Trend};
+
+    /// Sensor stub carrying the profile fields the tests filter on.
+    struct ProfiledSensor {
+        zone: u8,
+        critical: Option<DegreesCelsius>,
+    }
+
+    impl SensorService for ProfiledSensor {
+        async fn temperature(&self) -> DegreesCelsius {
+            0.0
+        }
+
+        async fn temperature_average(&self) -> DegreesCelsius {
+            0.0
+        }
+
+        async fn temperature_trend(&self) -> Trend {
+            Trend::default()
+        }
+
+        async fn temperature_immediate(&self) -> Result<DegreesCelsius, Error> {
+            Ok(0.0)
+        }
+
+        async fn sample_now(&self) -> Result<DegreesCelsius, Error> {
+            Ok(0.0)
+        }
+
+        async fn set_threshold(&self, _threshold: Threshold, _value: DegreesCelsius) {}
+
+        async fn threshold(&self, _threshold: Threshold) -> DegreesCelsius {
+            0.0
+        }
+
+        async fn thresholds(&self) -> [ThresholdSetting; Threshold::ALL.len()] {
+            [
+                Threshold::WarnLow,
+                Threshold::WarnHigh,
+                Threshold::Prochot,
+                Threshold::Critical,
+            ]
+            .map(|threshold| ThresholdSetting {
+                threshold,
+                value: 0.0,
+                hysteresis: 0.0,
+            })
+        }
+
+        async fn set_sample_period(&self, _period: Duration) {}
+
+        async fn enable_sampling(&self) {}
+
+        async fn disable_sampling(&self) {}
+
+        async fn pause_sampling(&self) {}
+
+        async fn resume_sampling(&self) {}
+
+        async fn capabilities(&self) -> Capabilities {
+            Capabilities::default()
+        }
+
+        async fn heartbeat(&self) -> Heartbeat {
+            Heartbeat {
+                last_sample: None,
+                sample_period: Duration::from_secs(1),
+                sampling_enabled: false,
+                paused: false,
+            }
+        }
+
+        async fn last_sample_time(&self) -> Option<Instant> {
+            None
+        }
+
+        async fn reinitialize(&self) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    type FanType = fan::Service<'static, mock::fan::MockFan, ProfiledSensor, NoopSender, 1>;
+
+    #[test]
+    fn test_sensors_where() {
+        let sensors = [
+            ProfiledSensor {
+                zone: 1,
+                critical: Some(90.0),
+            },
+            ProfiledSensor {
+                zone: 2,
+                critical: None,
+            },
+            ProfiledSensor {
+                zone: 2,
+                critical: Some(85.0),
+            },
+            ProfiledSensor {
+                zone: 3,
+                critical: None,
+            },
+        ];
+        let mut resources = Resources::<_, FanType>::default();
+        let service = Service::init(
+            &mut resources,
+            InitParams {
+                sensors: &sensors,
+                fans: &[],
+            },
+        );
+
+        let ids = |pred: &dyn Fn(&ProfiledSensor) -> bool| -> heapless::Vec<u8, 4> {
+            service.sensors_where(pred).map(|(id, _)| id).collect()
+        };
+
+        assert_eq!(ids(&|sensor| sensor.zone == 2), [1, 2]);
+        assert_eq!(ids(&|sensor| sensor.critical.is_some()), [0, 2]);
+        assert_eq!(ids(&|sensor| sensor.zone == 2 && sensor.critical.is_some()), [2]);
+        assert!(ids(&|sensor| sensor.zone == 4).is_empty());
+    }
+
+    #[test]
+    fn test_set_all_fans() {
+        embassy_futures::block_on(async {
+            let mut resources0 = fan::Resources::<mock::fan::MockFan, 1>::default();
+            let mut resources1 = fan::Resources::<mock::fan::MockFan, 1>::default();
+            let mut fans = heapless::Vec::<_, 2>::new();
+            for resources in [&mut resources0, &mut resources1] {
+                let (fan, _runner) = fan::Service::<_, _, NoopSender, 1>::new(
+                    resources,
+                    fan::InitParams {
+                        driver: mock::fan::MockFan::new(),
+                        config: mock::fan::MockFan::config(),
+                        sensor_service: ProfiledSensor {
+                            zone: 0,
+                            critical: None,
+                        },
+                        event_senders: &mut [],
+                        profile_storage: None,
+                        aggregation: fan::Aggregation::Single,
+                    },
+                )
+                .await
+                .unwrap();
+                fans.push(fan).ok().unwrap();
+            }
+
+            let mut resources = Resources::<ProfiledSensor, _>::default();
+            let service = Service::init(
+                &mut resources,
+                InitParams {
+                    sensors: &[],
+                    fans: &fans,
+                },
+            );
+
+            // A result count that doesn't match the registered fans is rejected without commanding any fan
+            assert_eq!(
+                service.set_all_fans::<1>(100).await,
+                Err(thermal_service_interface::fan::Error::InvalidArgument)
+            );
+            assert_eq!(
+                service.set_all_fans::<3>(100).await,
+                Err(thermal_service_interface::fan::Error::InvalidArgument)
+            );
+            for fan in &fans {
+                assert_ne!(fan.rpm_immediate().await.unwrap(), fan.max_rpm().await);
+            }
+
+            // Every fan is commanded
+            let results = service.set_all_fans::<2>(100).await;
+            assert_eq!(results, Ok([Ok(()), Ok(())]));
+            for fan in &fans {
+                assert_eq!(fan.rpm_immediate().await.unwrap(), fan.max_rpm().await);
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn test_sample_all_now() {
+        let mut resources0 = sensor::Resources::<mock::sensor::MockSensor, 4>::default();
+        let mut resources1 = sensor::Resources::<mock::sensor::MockSensor, 4>::default();
+        let mut sensors = heapless::Vec::<_, 2>::new();
+        let mut runners = heapless::Vec::<_, 2>::new();
+        for resources in [&mut resources0, &mut resources1] {
+            let (sensor, runner) = sensor::Service::<_, NoopSender, 4>::new(
+                resources,
+                sensor::InitParams {
+                    driver: mock::sensor::MockSensor::new(),
+                    config: sensor::Config {
+                        // Long enough that only requested samples are taken during the test
+                        sample_period: Duration::from_secs(3600),
+                        ..mock::sensor::MockSensor::config()
+                    },
+                    event_senders: &mut [],
+                },
+            )
+            .await
+            .unwrap();
+            sensors.push(sensor).ok().unwrap();
+            runners.push(runner).ok().unwrap();
+        }
+
+        let mut resources = Resources::<_, FanType>::default();
+        let service = Service::init(
+            &mut resources,
+            InitParams {
+                sensors: &sensors,
+                fans: &[],
+            },
+        );
+
+        let [runner0, runner1] = runners.into_array().ok().unwrap();
+        embassy_futures::select::select3(runner0.run(), runner1.run(), async {
+            // Wait for the initial periodic sample
+            for sensor in &sensors {
+                while sensor.heartbeat().await.last_sample.is_none() {
+                    embassy_time::Timer::after(Duration::from_millis(1)).await;
+                }
+            }
+
+            // A result count that doesn't match the registered sensors is rejected without sampling any sensor
+            assert_eq!(
+                service.sample_all_now::<1>().await,
+                Err(thermal_service_interface::sensor::Error::InvalidArgument)
+            );
+            assert_eq!(
+                service.sample_all_now::<3>().await,
+                Err(thermal_service_interface::sensor::Error::InvalidArgument)
+            );
+
+            // Every sensor takes a fresh sample, which is stored like a periodic sample
+            let results = service.sample_all_now::<2>().await;
+            let expected = mock::MIN_TEMP + 1.0;
+            assert_eq!(results, Ok([Ok(expected), Ok(expected)]));
+            for sensor in &sensors {
+                assert_eq!(sensor.temperature().await, expected);
+            }
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_get_sensor_label() {
+        embassy_futures::block_on(async {
+            let mut labeled_resources = sensor::Resources::<mock::sensor::MockSensor, 1>::default();
+            let (labeled, _labeled_runner) = sensor::Service::<_, NoopSender, 1>::new(
+                &mut labeled_resources,
+                sensor::InitParams {
+                    driver: mock::sensor::MockSensor::new(),
+                    config: sensor::Config {
+                        label: Some("CPU"),
+                        ..mock::sensor::MockSensor::config()
+                    },
+                    event_senders: &mut [],
+                },
+            )
+            .await
+            .unwrap();
+
+            let mut unlabeled_resources = sensor::Resources::<mock::sensor::MockSensor, 1>::default();
+            let (unlabeled, _unlabeled_runner) = sensor::Service::<_, NoopSender, 1>::new(
+                &mut unlabeled_resources,
+                sensor::InitParams {
+                    driver: mock::sensor::MockSensor::new(),
+                    config: mock::sensor::MockSensor::config(),
+                    event_senders: &mut [],
+                },
+            )
+            .await
+            .unwrap();
+
+            let sensors = [labeled, unlabeled];
+            let mut resources = Resources::<_, FanType>::default();
+            let service = Service::init(
+                &mut resources,
+                InitParams {
+                    sensors: &sensors,
+                    fans: &[],
+                },
+            );
+
+            assert_eq!(service.get_sensor_label(0), Some("CPU"));
+            assert_eq!(service.get_sensor_label(1), None);
+            assert_eq!(service.get_sensor_label(2), None);
+        });
+    }
+}
@@ -1,5 +1,6 @@
 use crate::fan::Config;
 use embedded_fans_async::{Error, ErrorKind, ErrorType, Fan, RpmSense};
+use heapless::HistoryBuffer;
 use thermal_service_interface::fan as fan_interface;
 
 /// `MockFan` error.
@@ -11,10 +12,14 @@ impl Error for MockFanError {
     }
 }
 
+/// Number of commanded RPMs remembered by a `MockFan`.
+const COMMAND_HISTORY_LEN: usize = 128;
+
 /// Mock fan.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct MockFan {
     rpm: u16,
+    commanded_rpms: HistoryBuffer<u16, COMMAND_HISTORY_LEN>,
 }
 
 impl MockFan {
@@ -32,6 +37,11 @@ impl MockFan {
             ..Default::default()
         }
     }
+
+    /// Returns the RPMs the fan was commanded to run at, oldest first, up to the most recent 128 commands.
+    pub fn commanded_rpms(&self) -> impl Iterator<Item = u16> + '_ {
+        self.commanded_rpms.oldest_ordered().copied()
+    }
 }
 
 impl ErrorType for MockFan {
@@ -53,6 +63,7 @@ impl Fan for MockFan {
 
     async fn set_speed_rpm(&mut self, rpm: u16) -> Result<u16, Self::Error> {
         self.rpm = rpm;
+        self.commanded_rpms.write(rpm);
         Ok(rpm)
     }
 }
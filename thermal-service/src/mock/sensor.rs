@@ -17,18 +17,27 @@ impl sensor_traits::ErrorType for MockSensor {
 }
 
 /// Mock sensor.
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug)]
 pub struct MockSensor {
     temp: DegreesCelsius,
     falling: bool,
+    min_temp: DegreesCelsius,
+    max_temp: DegreesCelsius,
 }
 
 impl MockSensor {
     /// Create a new `MockSensor`.
     pub fn new() -> Self {
+        Self::with_range(super::MIN_TEMP, super::MAX_TEMP)
+    }
+
+    /// Create a new `MockSensor` moving between `min_temp` and `max_temp`, starting at `min_temp`.
+    pub fn with_range(min_temp: DegreesCelsius, max_temp: DegreesCelsius) -> Self {
         Self {
-            temp: super::MIN_TEMP,
+            temp: min_temp,
             falling: false,
+            min_temp,
+            max_temp,
         }
     }
 
@@ -43,6 +52,12 @@ impl MockSensor {
     }
 }
 
+impl Default for MockSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TemperatureSensor for MockSensor {
     async fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
         let t = self.temp;
@@ -50,14 +65,14 @@ impl TemperatureSensor for MockSensor {
         // Creates a sawtooth pattern
         if self.falling {
             self.temp -= 1.0;
-            if self.temp <= super::MIN_TEMP {
-                self.temp = super::MIN_TEMP;
+            if self.temp <= self.min_temp {
+                self.temp = self.min_temp;
                 self.falling = false;
             }
         } else {
             self.temp += 1.0;
-            if self.temp >= super::MAX_TEMP {
-                self.temp = super::MAX_TEMP;
+            if self.temp >= self.max_temp {
+                self.temp = self.max_temp;
                 self.falling = true;
             }
         }
@@ -77,4 +92,12 @@ impl TemperatureThresholdSet for MockSensor {
     }
 }
 
-impl sensor::Driver for MockSensor {}
+impl sensor::Driver for MockSensor {
+    fn capabilities(&self) -> sensor::Capabilities {
+        sensor::Capabilities {
+            min_temp: self.min_temp,
+            max_temp: self.max_temp,
+            ..Default::default()
+        }
+    }
+}
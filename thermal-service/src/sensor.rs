@@ -1,15 +1,16 @@
 use crate::utils::SampleBuf;
+use core::cell::RefCell;
+use core::future::poll_fn;
 use core::marker::PhantomData;
-use embassy_sync::{mutex::Mutex, signal::Signal};
-use embassy_time::{Duration, Timer, with_timeout};
+use core::task::Poll;
+use embassy_futures::select::{Either3, select, select3};
+use embassy_sync::{blocking_mutex, mutex::Mutex, signal::Signal, waitqueue::MultiWakerRegistration};
+use embassy_time::{Duration, Instant, Timer, with_timeout};
 use embedded_sensors_hal_async::temperature::DegreesCelsius;
 use embedded_services::event::NonBlockingSender;
-use embedded_services::{GlobalRawMutex, error};
+use embedded_services::{AtomicUsize, GlobalRawMutex, Ordering, error, warn};
 use thermal_service_interface::sensor;
 
-// Timeout period for physical bus access
-const BUS_TIMEOUT: Duration = Duration::from_millis(200);
-
 /* Helper macro for calling a bus function with automatic retry after timeout or failure.
  *
  * Necessary since often the sensor bus is shared and occasionally the underlying bus driver
@@ -20,14 +21,15 @@ macro_rules! with_retry {
         $self:expr,
         $bus_method:expr
     ) => {{
-        let mut retry_attempts = $self.config.lock().await.retry_attempts;
+        let config = *$self.config.lock().await;
+        let mut retry_attempts = config.retry_attempts;
 
         loop {
             if retry_attempts == 0 {
                 break Err(sensor::Error::RetryExhausted);
             }
 
-            match with_timeout(BUS_TIMEOUT, $bus_method).await {
+            match with_timeout(config.bus_timeout, $bus_method).await {
                 Ok(Ok(val)) => break Ok(val),
                 _ => {
                     retry_attempts -= 1;
@@ -41,12 +43,16 @@ macro_rules! with_retry {
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Config {
+    /// Human-readable label identifying the sensor in diagnostics.
+    pub label: Option<&'static str>,
     /// Rate at which to sample the sensor when operating in normal conditions.
     pub sample_period: Duration,
     /// Rate at which to sample the sensor when operating in fast conditions.
     pub fast_sample_period: Duration,
     /// Whether periodic sampling is enabled.
     pub sampling_enabled: bool,
+    /// Delay before the first sample, used to stagger sensors sharing a bus so they don't all sample at once.
+    pub sample_phase: Duration,
     /// Hysteresis value to prevent rapid generation of threshold events when temperature is near a threshold.
     pub hysteresis: DegreesCelsius,
     /// Temperature threshold below which a warning event will be generated.
@@ -63,14 +69,23 @@ pub struct Config {
     pub offset: DegreesCelsius,
     /// Number of retry attempts for bus operations.
     pub retry_attempts: u8,
+    /// Timeout for each bus operation attempt.
+    pub bus_timeout: Duration,
+    /// Number of times to retry sending an event to a receiver that is temporarily full.
+    pub event_retry_attempts: u8,
+    /// Minimum delay between attempts to send an event. Retries never delay sampling, while sampling they are made
+    /// with the next sample.
+    pub event_retry_interval: Duration,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            label: None,
             sample_period: Duration::from_secs(1),
             fast_sample_period: Duration::from_millis(200),
             sampling_enabled: true,
+            sample_phase: Duration::from_millis(0),
             hysteresis: 2.0,
             warn_low_threshold: DegreesCelsius::MIN,
             warn_high_threshold: DegreesCelsius::MAX,
@@ -79,24 +94,98 @@ impl Default for Config {
             fast_sampling_threshold: DegreesCelsius::MAX,
             offset: 0.0,
             retry_attempts: 5,
+            bus_timeout: Duration::from_millis(200),
+            event_retry_attempts: 3,
+            event_retry_interval: Duration::from_millis(10),
         }
     }
 }
 
+impl Config {
+    /// Returns the configured temperature for `threshold`.
+    pub fn threshold(&self, threshold: sensor::Threshold) -> DegreesCelsius {
+        match threshold {
+            sensor::Threshold::WarnLow => self.warn_low_threshold,
+            sensor::Threshold::WarnHigh => self.warn_high_threshold,
+            sensor::Threshold::Prochot => self.prochot_threshold,
+            sensor::Threshold::Critical => self.critical_threshold,
+        }
+    }
+}
+
+/// Maximum number of events waiting to be re-sent to receivers that were full
+const PENDING_EVENTS: usize = 8;
+
+/// Event waiting to be re-sent to a receiver that was full
+#[derive(Clone, Copy)]
+struct PendingEvent {
+    /// Index of the receiver's sender
+    sender: usize,
+    event: sensor::Event,
+    /// Number of attempts left before the event is dropped
+    attempts_left: u8,
+    /// Time of the next attempt
+    retry_at: Instant,
+}
+
+/// Number of [`sensor::SensorService::sample_now`] callers that can wait without being woken to re-register
+const SAMPLE_WAITERS: usize = 4;
+
+/// Results of requested samples
+struct Sampled {
+    /// Incremented each time a sample completes
+    generation: u32,
+    /// Result of the most recent sample
+    result: Option<Result<DegreesCelsius, sensor::Error>>,
+    /// Callers waiting for the next sample to complete
+    wakers: MultiWakerRegistration<SAMPLE_WAITERS>,
+}
+
 struct ServiceInner<T: sensor::Driver, const SAMPLE_BUF_LEN: usize> {
     driver: Mutex<GlobalRawMutex, T>,
+    // Copied out of the config since it never changes and is read without locking
+    label: Option<&'static str>,
+    // Signaled when sampling is enabled, paused or resumed
     en_signal: Signal<GlobalRawMutex, ()>,
+    // Sampling is paused, the hardware is not accessed until resumed
+    paused: Mutex<GlobalRawMutex, bool>,
+    sample_request: Signal<GlobalRawMutex, ()>,
+    // Result of the most recent sample, shared by every caller waiting on it
+    sampled: blocking_mutex::Mutex<GlobalRawMutex, RefCell<Sampled>>,
     config: Mutex<GlobalRawMutex, Config>,
     samples: Mutex<GlobalRawMutex, SampleBuf<DegreesCelsius, SAMPLE_BUF_LEN>>,
+    // Time of each entry in `samples` in microseconds, always locked after `samples`
+    sample_times: Mutex<GlobalRawMutex, SampleBuf<u64, SAMPLE_BUF_LEN>>,
+    last_sample: Mutex<GlobalRawMutex, Option<Instant>>,
+    // Time of the most recent stored sample, unlike `last_sample` this is only updated by sampling
+    last_sample_time: Mutex<GlobalRawMutex, Option<Instant>>,
+    // Number of events that couldn't be sent because a receiver stayed full through every retry
+    dropped_events: AtomicUsize,
+    #[cfg(any(test, feature = "test-hooks"))]
+    injected_temp: Mutex<GlobalRawMutex, Option<DegreesCelsius>>,
 }
 
 impl<T: sensor::Driver, const SAMPLE_BUF_LEN: usize> ServiceInner<T, SAMPLE_BUF_LEN> {
     fn new(driver: T, config: Config) -> Self {
         Self {
             driver: Mutex::new(driver),
+            label: config.label,
             en_signal: Signal::new(),
+            paused: Mutex::new(false),
+            sample_request: Signal::new(),
+            sampled: blocking_mutex::Mutex::new(RefCell::new(Sampled {
+                generation: 0,
+                result: None,
+                wakers: MultiWakerRegistration::new(),
+            })),
             config: Mutex::new(config),
             samples: Mutex::new(SampleBuf::create()),
+            sample_times: Mutex::new(SampleBuf::create()),
+            last_sample: Mutex::new(None),
+            last_sample_time: Mutex::new(None),
+            dropped_events: AtomicUsize::new(0),
+            #[cfg(any(test, feature = "test-hooks"))]
+            injected_temp: Mutex::new(None),
         }
     }
 }
@@ -133,10 +222,35 @@ impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_B
         self.inner.samples.lock().await.average()
     }
 
+    async fn temperature_trend(&self) -> sensor::Trend {
+        let samples = self.inner.samples.lock().await;
+        let sample_times = self.inner.sample_times.lock().await;
+        trend(samples.iter().copied().zip(sample_times.iter().copied()))
+    }
+
     async fn temperature_immediate(&self) -> Result<DegreesCelsius, sensor::Error> {
         with_retry!(self.inner, self.inner.driver.lock().await.temperature())
     }
 
+    async fn sample_now(&self) -> Result<DegreesCelsius, sensor::Error> {
+        // Served by the first sample completing after the request, concurrent callers may share a sample
+        let generation = self.inner.sampled.lock(|sampled| sampled.borrow().generation);
+        self.inner.sample_request.signal(());
+        poll_fn(|cx| {
+            self.inner.sampled.lock(|sampled| {
+                let mut sampled = sampled.borrow_mut();
+                match sampled.result {
+                    Some(result) if sampled.generation != generation => Poll::Ready(result),
+                    _ => {
+                        sampled.wakers.register(cx.waker());
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await
+    }
+
     async fn set_threshold(&self, threshold: sensor::Threshold, value: DegreesCelsius) {
         let mut config = self.inner.config.lock().await;
         match threshold {
@@ -148,13 +262,22 @@ impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_B
     }
 
     async fn threshold(&self, threshold: sensor::Threshold) -> DegreesCelsius {
-        let config = self.inner.config.lock().await;
-        match threshold {
-            sensor::Threshold::WarnLow => config.warn_low_threshold,
-            sensor::Threshold::WarnHigh => config.warn_high_threshold,
-            sensor::Threshold::Prochot => config.prochot_threshold,
-            sensor::Threshold::Critical => config.critical_threshold,
-        }
+        self.inner.config.lock().await.threshold(threshold)
+    }
+
+    async fn thresholds(&self) -> [sensor::ThresholdSetting; sensor::Threshold::ALL.len()] {
+        let config = *self.inner.config.lock().await;
+        [
+            sensor::Threshold::WarnLow,
+            sensor::Threshold::WarnHigh,
+            sensor::Threshold::Prochot,
+            sensor::Threshold::Critical,
+        ]
+        .map(|threshold| sensor::ThresholdSetting {
+            threshold,
+            value: config.threshold(threshold),
+            hysteresis: config.hysteresis,
+        })
     }
 
     async fn set_sample_period(&self, period: Duration) {
@@ -163,12 +286,82 @@ impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_B
 
     async fn enable_sampling(&self) {
         self.inner.config.lock().await.sampling_enabled = true;
+        // Restart the heartbeat so time spent disabled isn't considered a stall
+        *self.inner.last_sample.lock().await = Some(Instant::now());
         self.inner.en_signal.signal(());
     }
 
     async fn disable_sampling(&self) {
         self.inner.config.lock().await.sampling_enabled = false;
     }
+
+    async fn pause_sampling(&self) {
+        // Set with the driver locked, the runner checks it with the driver locked before accessing the hardware. So
+        // once this returns any in-progress read has finished and no new one is started.
+        let driver = self.inner.driver.lock().await;
+        *self.inner.paused.lock().await = true;
+        drop(driver);
+        // Wake the runner so it stops waiting on the hardware
+        self.inner.en_signal.signal(());
+    }
+
+    async fn resume_sampling(&self) {
+        *self.inner.paused.lock().await = false;
+        // Restart the heartbeat so time spent paused isn't considered a stall
+        *self.inner.last_sample.lock().await = Some(Instant::now());
+        self.inner.en_signal.signal(());
+    }
+
+    async fn capabilities(&self) -> sensor::Capabilities {
+        self.inner.driver.lock().await.capabilities()
+    }
+
+    async fn heartbeat(&self) -> sensor::Heartbeat {
+        let config = *self.inner.config.lock().await;
+        sensor::Heartbeat {
+            last_sample: *self.inner.last_sample.lock().await,
+            sample_period: config.sample_period,
+            sampling_enabled: config.sampling_enabled,
+            paused: *self.inner.paused.lock().await,
+        }
+    }
+
+    async fn last_sample_time(&self) -> Option<Instant> {
+        *self.inner.last_sample_time.lock().await
+    }
+
+    async fn reinitialize(&self) -> Result<(), sensor::Error> {
+        with_retry!(self.inner, self.inner.driver.lock().await.reinitialize())?;
+        self.enable_sampling().await;
+        Ok(())
+    }
+
+    fn label(&self) -> Option<&'static str> {
+        self.inner.label
+    }
+}
+
+/// Temperature injection for hardware-in-the-loop testing.
+#[cfg(any(test, feature = "test-hooks"))]
+pub trait TemperatureInjection {
+    /// Override the temperature reported by subsequent samples, regardless of the underlying hardware.
+    fn inject_temperature(&self, temp: DegreesCelsius) -> impl core::future::Future<Output = ()>;
+
+    /// Clear an injected temperature, subsequent samples are read from the hardware again.
+    fn clear_injected_temperature(&self) -> impl core::future::Future<Output = ()>;
+}
+
+#[cfg(any(test, feature = "test-hooks"))]
+impl<T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_BUF_LEN: usize> TemperatureInjection
+    for Service<'_, T, E, SAMPLE_BUF_LEN>
+{
+    async fn inject_temperature(&self, temp: DegreesCelsius) {
+        *self.inner.injected_temp.lock().await = Some(temp);
+    }
+
+    async fn clear_injected_temperature(&self) {
+        *self.inner.injected_temp.lock().await = None;
+    }
 }
 
 /// Parameters required to initialize a sensor service.
@@ -182,6 +375,10 @@ pub struct InitParams<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event
 }
 
 /// The memory resources required by the sensor.
+///
+/// `SAMPLE_BUF_LEN` is the number of samples retained for [`sensor::SensorService::temperature_average`] and
+/// [`sensor::SensorService::temperature_trend`], and can be chosen per sensor. It must be at least 1, a zero length
+/// fails to compile.
 pub struct Resources<T: sensor::Driver, const SAMPLE_BUF_LEN: usize> {
     inner: Option<ServiceInner<T, SAMPLE_BUF_LEN>>,
 }
@@ -204,57 +401,249 @@ struct State {
     is_critical: bool,
 }
 
+impl State {
+    /// Returns a mutable reference to whether `threshold` is currently exceeded
+    fn exceeded_mut(&mut self, threshold: sensor::Threshold) -> &mut bool {
+        match threshold {
+            sensor::Threshold::WarnLow => &mut self.is_warn_low,
+            sensor::Threshold::WarnHigh => &mut self.is_warn_high,
+            sensor::Threshold::Prochot => &mut self.is_prochot,
+            sensor::Threshold::Critical => &mut self.is_critical,
+        }
+    }
+}
+
+/// Computes the temperature window the hardware should watch, given the current threshold state.
+///
+/// Thresholds not yet exceeded bound the window directly, while exceeded thresholds bound it at the point
+/// they would be cleared (accounting for hysteresis).
+fn threshold_window(config: &Config, state: &State) -> (DegreesCelsius, DegreesCelsius) {
+    let mut low = DegreesCelsius::MIN;
+    let mut high = DegreesCelsius::MAX;
+
+    for (threshold, exceeded) in [
+        (config.warn_high_threshold, state.is_warn_high),
+        (config.prochot_threshold, state.is_prochot),
+        (config.critical_threshold, state.is_critical),
+    ] {
+        if exceeded {
+            low = low.max(threshold - config.hysteresis);
+        } else {
+            high = high.min(threshold);
+        }
+    }
+
+    if state.is_warn_low {
+        high = high.min(config.warn_low_threshold + config.hysteresis);
+    } else {
+        low = low.max(config.warn_low_threshold);
+    }
+
+    (low, high)
+}
+
+/// Computes the trend of `(temperature, time in microseconds)` samples.
+///
+/// The slope is the least-squares fit over all samples, so a single noisy reading has limited effect.
+fn trend(samples: impl Iterator<Item = (DegreesCelsius, u64)> + Clone) -> sensor::Trend {
+    let mut result = sensor::Trend::default();
+    let Some((_, origin)) = samples.clone().last() else {
+        return result;
+    };
+
+    // Times are taken relative to the oldest sample to preserve precision
+    let seconds = |time: u64| time.saturating_sub(origin) as f32 / 1_000_000.0;
+
+    let mut temp_sum = 0.0;
+    let mut time_sum = 0.0;
+    result.min = DegreesCelsius::MAX;
+    result.max = DegreesCelsius::MIN;
+    for (temp, time) in samples.clone() {
+        result.min = result.min.min(temp);
+        result.max = result.max.max(temp);
+        result.samples += 1;
+        temp_sum += temp;
+        time_sum += seconds(time);
+    }
+
+    let temp_mean = temp_sum / result.samples as f32;
+    let time_mean = time_sum / result.samples as f32;
+    let mut covariance = 0.0;
+    let mut variance = 0.0;
+    for (temp, time) in samples {
+        let dt = seconds(time) - time_mean;
+        covariance += dt * (temp - temp_mean);
+        variance += dt * dt;
+    }
+
+    // Fewer than two distinct sample times means there is no trend to report
+    if variance > 0.0 {
+        result.slope = covariance / variance;
+    }
+
+    result
+}
+
+/// Waits until `instant`, forever if [`None`].
+async fn wait_until(instant: Option<Instant>) {
+    match instant {
+        Some(instant) => Timer::at(instant).await,
+        None => core::future::pending().await,
+    }
+}
+
 /// A task runner for a sensor. Users must run this in an embassy task or similar async execution context.
 pub struct Runner<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_BUF_LEN: usize> {
     service: &'hw ServiceInner<T, SAMPLE_BUF_LEN>,
     event_senders: &'hw mut [E],
+    // Events that will be re-sent to receivers that were full, oldest first
+    pending_events: heapless::Deque<PendingEvent, PENDING_EVENTS>,
     state: State,
 }
 
 impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_BUF_LEN: usize>
     Runner<'hw, T, E, SAMPLE_BUF_LEN>
 {
-    fn broadcast_event(&mut self, event: sensor::Event) {
-        for sender in self.event_senders.iter_mut() {
-            if sender.try_send(event).is_none() {
+    /// Sends `event` to every receiver without waiting.
+    ///
+    /// The event is queued for a receiver that is full, and re-sent by [`Self::retry_pending_events`] up to
+    /// [`Config::event_retry_attempts`] times.
+    fn broadcast_event(&mut self, config: &Config, event: sensor::Event) {
+        for (index, sender) in self.event_senders.iter_mut().enumerate() {
+            // Events already queued for this receiver go first so it sees them in order
+            let queued = self.pending_events.iter().any(|pending| pending.sender == index);
+            if !queued && sender.try_send(event).is_some() {
+                continue;
+            }
+
+            let pending = PendingEvent {
+                sender: index,
+                event,
+                attempts_left: config.event_retry_attempts,
+                retry_at: Instant::now() + config.event_retry_interval,
+            };
+            if config.event_retry_attempts == 0 || self.pending_events.push_back(pending).is_err() {
+                self.service.dropped_events.fetch_add(1, Ordering::Relaxed);
                 error!("Failed to send sensor event");
             }
         }
     }
 
-    async fn check_thresholds(&mut self, temp: DegreesCelsius) {
-        let config = *self.service.config.lock().await;
+    /// Re-sends queued events whose retry is due, dropping those that have run out of attempts.
+    fn retry_pending_events(&mut self, config: &Config) {
+        let now = Instant::now();
+        let mut pending_events = core::mem::replace(&mut self.pending_events, heapless::Deque::new());
+        while let Some(mut pending) = pending_events.pop_front() {
+            // A later event for a receiver that is still full waits behind the earlier one
+            let blocked = self.pending_events.iter().any(|other| other.sender == pending.sender);
+            if blocked || pending.retry_at > now {
+                // Can't fail, no more events are queued than were taken
+                let _ = self.pending_events.push_back(pending);
+                continue;
+            }
+
+            let Some(sender) = self.event_senders.get_mut(pending.sender) else {
+                continue;
+            };
+            if sender.try_send(pending.event).is_some() {
+                continue;
+            }
+
+            pending.attempts_left = pending.attempts_left.saturating_sub(1);
+            pending.retry_at = now + config.event_retry_interval;
+            if pending.attempts_left == 0 || self.pending_events.push_back(pending).is_err() {
+                self.service.dropped_events.fetch_add(1, Ordering::Relaxed);
+                error!("Failed to send sensor event after retrying");
+            }
+        }
+    }
+
+    /// Returns when the next queued event is due to be re-sent, [`None`] if no events are queued.
+    fn next_event_retry(&self) -> Option<Instant> {
+        self.pending_events.iter().map(|pending| pending.retry_at).min()
+    }
+
+    /// Samples the temperature with the configured offset applied, [`None`] if sampling is paused.
+    ///
+    /// An injected temperature is reported as-is, without sampling the hardware.
+    async fn sample(&self, config: &Config) -> Option<Result<DegreesCelsius, sensor::Error>> {
+        // Held across retries so a pause can't start in between
+        let mut driver = self.service.driver.lock().await;
+        if *self.service.paused.lock().await {
+            return None;
+        }
 
-        if temp >= config.warn_high_threshold && !self.state.is_warn_high {
-            self.state.is_warn_high = true;
-            self.broadcast_event(sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh));
-        } else if temp < (config.warn_high_threshold - config.hysteresis) && self.state.is_warn_high {
-            self.state.is_warn_high = false;
-            self.broadcast_event(sensor::Event::ThresholdCleared(sensor::Threshold::WarnHigh));
+        #[cfg(any(test, feature = "test-hooks"))]
+        if let Some(temp) = *self.service.injected_temp.lock().await {
+            return Some(Ok(temp));
         }
 
-        if temp <= config.warn_low_threshold && !self.state.is_warn_low {
-            self.state.is_warn_low = true;
-            self.broadcast_event(sensor::Event::ThresholdExceeded(sensor::Threshold::WarnLow));
-        } else if temp > (config.warn_low_threshold + config.hysteresis) && self.state.is_warn_low {
-            self.state.is_warn_low = false;
-            self.broadcast_event(sensor::Event::ThresholdCleared(sensor::Threshold::WarnLow));
+        // Add offset to measured temperature
+        Some(with_retry!(self.service, driver.temperature()).map(|temp| temp + config.offset))
+    }
+
+    async fn wait_next_sample(&self, config: &Config, sleep_duration: Duration) {
+        let mut driver = self.service.driver.lock().await;
+        if *self.service.paused.lock().await {
+            // Handled by the runner on the next iteration
+            return;
         }
 
-        if temp >= config.prochot_threshold && !self.state.is_prochot {
-            self.state.is_prochot = true;
-            self.broadcast_event(sensor::Event::ThresholdExceeded(sensor::Threshold::Prochot));
-        } else if temp < (config.prochot_threshold - config.hysteresis) && self.state.is_prochot {
-            self.state.is_prochot = false;
-            self.broadcast_event(sensor::Event::ThresholdCleared(sensor::Threshold::Prochot));
+        if !driver.capabilities().hardware_thresholds {
+            drop(driver);
+            Timer::after(sleep_duration).await;
+            return;
         }
 
-        if temp >= config.critical_threshold && !self.state.is_critical {
-            self.state.is_critical = true;
-            self.broadcast_event(sensor::Event::ThresholdExceeded(sensor::Threshold::Critical));
-        } else if temp < (config.critical_threshold - config.hysteresis) && self.state.is_critical {
-            self.state.is_critical = false;
-            self.broadcast_event(sensor::Event::ThresholdCleared(sensor::Threshold::Critical));
+        let (low, high) = threshold_window(config, &self.state);
+        if driver.set_hardware_thresholds(low, high).await.is_err() {
+            drop(driver);
+            warn!("Failed to set hardware thresholds, falling back to polling");
+            Timer::after(sleep_duration).await;
+            return;
+        }
+
+        // Release the driver while waiting so other users aren't blocked until the next sample
+        let threshold_event = driver.wait_threshold_event();
+        drop(driver);
+        select(Timer::after(sleep_duration), threshold_event).await;
+    }
+
+    /// Completes all pending [`sensor::SensorService::sample_now`] requests with `result`.
+    fn complete_sample_request(&self, result: Result<DegreesCelsius, sensor::Error>) {
+        // A request made while sampling is served by this sample rather than triggering another
+        self.service.sample_request.reset();
+        self.service.sampled.lock(|sampled| {
+            let mut sampled = sampled.borrow_mut();
+            sampled.generation = sampled.generation.wrapping_add(1);
+            sampled.result = Some(result);
+            sampled.wakers.wake();
+        });
+    }
+
+    async fn check_thresholds(&mut self, temp: DegreesCelsius) {
+        let config = *self.service.config.lock().await;
+
+        for &threshold in sensor::Threshold::ALL {
+            let value = config.threshold(threshold);
+            // Hysteresis always applies on the side the temperature returns from
+            let (tripped, cleared) = match threshold.direction() {
+                sensor::ThresholdDirection::Rising => (temp >= value, temp < value - config.hysteresis),
+                sensor::ThresholdDirection::Falling => (temp <= value, temp > value + config.hysteresis),
+            };
+
+            let exceeded = self.state.exceeded_mut(threshold);
+            let event = if tripped && !*exceeded {
+                *exceeded = true;
+                sensor::Event::ThresholdExceeded(threshold)
+            } else if cleared && *exceeded {
+                *exceeded = false;
+                sensor::Event::ThresholdCleared(threshold)
+            } else {
+                continue;
+            };
+
+            self.broadcast_event(&config, event);
         }
     }
 }
@@ -263,26 +652,42 @@ impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_B
     odp_service_common::runnable_service::ServiceRunner<'hw> for Runner<'hw, T, E, SAMPLE_BUF_LEN>
 {
     async fn run(mut self) -> embedded_services::Never {
+        let sample_phase = self.service.config.lock().await.sample_phase;
+        Timer::after(sample_phase).await;
+
         loop {
             let config = *self.service.config.lock().await;
+            let paused = *self.service.paused.lock().await;
+            self.retry_pending_events(&config);
 
             // Only sample temperature if enabled
-            if config.sampling_enabled {
-                let temp = match with_retry!(self.service, self.service.driver.lock().await.temperature()) {
-                    Ok(temp) => temp,
-                    Err(e) => {
+            if config.sampling_enabled && !paused {
+                let temp = match self.sample(&config).await {
+                    Some(Ok(temp)) => temp,
+                    // Paused since the state was read, pending sample requests are served once resumed
+                    None => continue,
+                    Some(Err(e)) => {
+                        self.complete_sample_request(Err(e));
                         self.service.config.lock().await.sampling_enabled = false;
-                        self.broadcast_event(sensor::Event::Failure(e));
-                        error!("Error sampling sensor, disabling sampling");
+                        self.broadcast_event(&config, sensor::Event::Failure(e));
+                        error!(
+                            "Error sampling sensor {}, disabling sampling",
+                            self.service.label.unwrap_or("(unlabeled)")
+                        );
                         continue;
                     }
                 };
 
-                // Add offset to measured temperature
-                let temp = temp + config.offset;
-
                 // Cache in buffer for quick retrieval from other services
-                self.service.samples.lock().await.push(temp);
+                let now = Instant::now();
+                {
+                    let mut samples = self.service.samples.lock().await;
+                    samples.push(temp);
+                    self.service.sample_times.lock().await.push(now.as_micros());
+                }
+                *self.service.last_sample.lock().await = Some(now);
+                *self.service.last_sample_time.lock().await = Some(now);
+                self.complete_sample_request(Ok(temp));
 
                 // Check thresholds
                 self.check_thresholds(temp).await;
@@ -294,12 +699,33 @@ impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event>, const SAMPLE_B
                     config.sample_period
                 };
 
-                // Sleep in-between sampling periods
-                Timer::after(sleep_duration).await;
+                // Sleep in-between sampling periods, waking early if the hardware signals a threshold event, a
+                // sample is requested or sampling is paused
+                select3(
+                    self.wait_next_sample(&config, sleep_duration),
+                    self.service.sample_request.wait(),
+                    self.service.en_signal.wait(),
+                )
+                .await;
 
-            // Otherwise sleep and wait to be re-enabled
-            } else {
-                self.service.en_signal.wait().await;
+            // Don't access the hardware while paused, pending sample requests are served once resumed
+            } else if paused {
+                select(self.service.en_signal.wait(), wait_until(self.next_event_retry())).await;
+
+            // Otherwise sleep and wait to be re-enabled, only sampling on request
+            } else if let Either3::Second(()) = select3(
+                self.service.en_signal.wait(),
+                self.service.sample_request.wait(),
+                wait_until(self.next_event_retry()),
+            )
+            .await
+            {
+                // Not stored since periodic sampling is disabled
+                match self.sample(&config).await {
+                    Some(result) => self.complete_sample_request(result),
+                    // Paused since the state was read, request the sample again so it's served once resumed
+                    None => self.service.sample_request.signal(()),
+                }
             }
         }
     }
@@ -330,8 +756,866 @@ impl<'hw, T: sensor::Driver, E: NonBlockingSender<sensor::Event> + 'hw, const SA
             Runner {
                 service,
                 event_senders: init_params.event_senders,
+                pending_events: heapless::Deque::new(),
                 state: State::default(),
             },
         ))
     }
+
+    /// Returns the number of events dropped because a receiver stayed full through every retry.
+    ///
+    /// Events are never waited on so a slow receiver can't delay sampling, each receiver that misses an event
+    /// counts once.
+    pub fn dropped_events(&self) -> usize {
+        self.inner.dropped_events.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::mock::sensor::{MockSensor, MockSensorError};
+    use crate::test_utils::HangingSensor;
+    use embassy_futures::select::{select, select4};
+    use embassy_sync::channel::{Channel, DynamicSender};
+    use embedded_sensors_hal_async::sensor as sensor_traits;
+    use embedded_sensors_hal_async::temperature::TemperatureSensor;
+    use embedded_services::event::NoopSender;
+    use odp_service_common::runnable_service::ServiceRunner;
+    use sensor::SensorService as _;
+
+    /// Sender that reports itself as full for a number of sends before forwarding events.
+    struct FlakySender<'a> {
+        failures: u8,
+        sender: DynamicSender<'a, sensor::Event>,
+    }
+
+    impl NonBlockingSender<sensor::Event> for FlakySender<'_> {
+        fn try_send(&mut self, event: sensor::Event) -> Option<()> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return None;
+            }
+
+            self.sender.try_send(event).ok()
+        }
+    }
+
+    /// Sensor which fails every read until it is reinitialized.
+    struct FailingSensor {
+        failed: bool,
+        inner: MockSensor,
+    }
+
+    impl sensor_traits::ErrorType for FailingSensor {
+        type Error = MockSensorError;
+    }
+
+    impl TemperatureSensor for FailingSensor {
+        async fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
+            if self.failed {
+                Err(MockSensorError)
+            } else {
+                self.inner.temperature().await
+            }
+        }
+    }
+
+    impl sensor::Driver for FailingSensor {
+        async fn reinitialize(&mut self) -> Result<(), Self::Error> {
+            self.failed = false;
+            Ok(())
+        }
+    }
+
+    /// Sensor reporting a constant temperature which signals threshold events through `alert`, if enabled.
+    struct ThresholdSensor {
+        hardware_thresholds: bool,
+        alert: &'static Signal<GlobalRawMutex, ()>,
+        /// Window last programmed with [`sensor::Driver::set_hardware_thresholds`]
+        window: Option<(DegreesCelsius, DegreesCelsius)>,
+    }
+
+    impl ThresholdSensor {
+        fn new(hardware_thresholds: bool, alert: &'static Signal<GlobalRawMutex, ()>) -> Self {
+            Self {
+                hardware_thresholds,
+                alert,
+                window: None,
+            }
+        }
+    }
+
+    impl sensor_traits::ErrorType for ThresholdSensor {
+        type Error = MockSensorError;
+    }
+
+    impl TemperatureSensor for ThresholdSensor {
+        async fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
+            Ok(25.0)
+        }
+    }
+
+    impl sensor::Driver for ThresholdSensor {
+        fn capabilities(&self) -> sensor::Capabilities {
+            sensor::Capabilities {
+                hardware_thresholds: self.hardware_thresholds,
+                ..Default::default()
+            }
+        }
+
+        async fn set_hardware_thresholds(
+            &mut self,
+            low: DegreesCelsius,
+            high: DegreesCelsius,
+        ) -> Result<(), Self::Error> {
+            self.window = Some((low, high));
+            Ok(())
+        }
+
+        fn wait_threshold_event(&self) -> impl Future<Output = ()> + 'static {
+            self.alert.wait()
+        }
+    }
+
+    /// Config for a [`ThresholdSensor`], sampling slowly enough that only a threshold event triggers another sample
+    fn threshold_sensor_config() -> Config {
+        Config {
+            sample_period: Duration::from_secs(60),
+            warn_low_threshold: 10.0,
+            warn_high_threshold: 50.0,
+            ..Default::default()
+        }
+    }
+
+    /// Waits for the runner to store a sample taken after `previous`
+    async fn next_sample_time<T: sensor::Driver, const N: usize>(
+        service: &Service<'_, T, NoopSender, N>,
+        previous: Option<Instant>,
+    ) -> Instant {
+        with_timeout(Duration::from_secs(1), async {
+            loop {
+                match service.last_sample_time().await {
+                    Some(time) if Some(time) > previous => break time,
+                    _ => Timer::after_millis(1).await,
+                }
+            }
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hardware_threshold_sampling() {
+        static ALERT: Signal<GlobalRawMutex, ()> = Signal::new();
+
+        let mut resources = Resources::<ThresholdSensor, 1>::default();
+        let (service, runner) = Service::<_, NoopSender, 1>::new(
+            &mut resources,
+            InitParams {
+                driver: ThresholdSensor::new(true, &ALERT),
+                config: threshold_sensor_config(),
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            let first = next_sample_time(&service, None).await;
+
+            // The driver is available while the runner waits for a threshold event
+            let window = with_timeout(Duration::from_secs(1), async {
+                loop {
+                    if let Some(window) = service.inner.driver.lock().await.window {
+                        break window;
+                    }
+                    Timer::after_millis(1).await;
+                }
+            })
+            .await
+            .unwrap();
+            assert_eq!(window, (10.0, 50.0));
+            assert_eq!(service.temperature_immediate().await, Ok(25.0));
+
+            // A threshold event samples immediately rather than after the sample period
+            ALERT.signal(());
+            next_sample_time(&service, Some(first)).await;
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_polling_without_hardware_thresholds() {
+        static ALERT: Signal<GlobalRawMutex, ()> = Signal::new();
+
+        let mut resources = Resources::<ThresholdSensor, 1>::default();
+        let (service, runner) = Service::<_, NoopSender, 1>::new(
+            &mut resources,
+            InitParams {
+                driver: ThresholdSensor::new(false, &ALERT),
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    ..threshold_sensor_config()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            // Samples are taken every sample period without ever programming the hardware
+            let first = next_sample_time(&service, None).await;
+            next_sample_time(&service, Some(first)).await;
+            assert_eq!(service.inner.driver.lock().await.window, None);
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_mock_sensor_capabilities() {
+        embassy_futures::block_on(async {
+            let mut resources = Resources::<MockSensor, 1>::default();
+            let (service, _runner) = Service::<_, NoopSender, 1>::new(
+                &mut resources,
+                InitParams {
+                    driver: MockSensor::new(),
+                    config: MockSensor::config(),
+                    event_senders: &mut [],
+                },
+            )
+            .await
+            .unwrap();
+
+            let capabilities = service.capabilities().await;
+            assert_eq!(capabilities.min_temp, crate::mock::MIN_TEMP);
+            assert_eq!(capabilities.max_temp, crate::mock::MAX_TEMP);
+            assert_eq!(capabilities.thresholds, sensor::Threshold::ALL);
+            assert!(!capabilities.hardware_thresholds);
+        });
+    }
+
+    #[test]
+    fn test_immediate_read_not_sampled() {
+        embassy_futures::block_on(async {
+            let mut resources = Resources::<MockSensor, 4>::default();
+            let (service, _runner) = Service::<_, NoopSender, 4>::new(
+                &mut resources,
+                InitParams {
+                    driver: MockSensor::new(),
+                    config: MockSensor::config(),
+                    event_senders: &mut [],
+                },
+            )
+            .await
+            .unwrap();
+
+            service.inner.samples.lock().await.push(30.0);
+
+            // Reads come straight from the hardware
+            assert_eq!(service.temperature_immediate().await, Ok(crate::mock::MIN_TEMP));
+            assert_eq!(service.temperature_immediate().await, Ok(crate::mock::MIN_TEMP + 1.0));
+
+            // Stored samples are untouched
+            assert_eq!(service.temperature().await, 30.0);
+            assert_eq!(service.temperature_average().await, 30.0);
+        });
+    }
+
+    #[test]
+    fn test_threshold_window() {
+        let config = Config {
+            hysteresis: 2.0,
+            warn_low_threshold: 10.0,
+            warn_high_threshold: 50.0,
+            prochot_threshold: 70.0,
+            critical_threshold: 90.0,
+            ..Default::default()
+        };
+
+        // Nothing exceeded, watch between the lowest and highest unexceeded thresholds
+        assert_eq!(threshold_window(&config, &State::default()), (10.0, 50.0));
+
+        // Warn high exceeded, watch for it clearing or prochot being exceeded
+        let state = State {
+            is_warn_high: true,
+            ..Default::default()
+        };
+        assert_eq!(threshold_window(&config, &state), (48.0, 70.0));
+
+        // Warn low exceeded, only watch for it clearing
+        let state = State {
+            is_warn_low: true,
+            ..Default::default()
+        };
+        assert_eq!(threshold_window(&config, &state), (DegreesCelsius::MIN, 12.0));
+
+        // Sub-zero thresholds, hysteresis still moves the window towards zero on the low side
+        let config = Config {
+            hysteresis: 2.0,
+            warn_low_threshold: -10.0,
+            warn_high_threshold: -5.0,
+            ..Default::default()
+        };
+        assert_eq!(threshold_window(&config, &State::default()), (-10.0, -5.0));
+        let state = State {
+            is_warn_low: true,
+            ..Default::default()
+        };
+        assert_eq!(threshold_window(&config, &state), (DegreesCelsius::MIN, -8.0));
+    }
+
+    #[test]
+    fn test_trend() {
+        // No samples yet
+        assert_eq!(trend(core::iter::empty()), sensor::Trend::default());
+
+        // Single sample, no slope yet
+        let single = trend([(30.0, 1_000_000)].into_iter());
+        assert_eq!(single.slope, 0.0);
+        assert_eq!((single.min, single.max, single.samples), (30.0, 30.0, 1));
+
+        // Ramp of 0.5 degrees every 200ms, stored most recent first
+        let mut samples = SampleBuf::<(DegreesCelsius, u64), 8>::create();
+        for i in 0..5u16 {
+            samples.push((20.0 + f32::from(i) * 0.5, 1_000_000 + u64::from(i) * 200_000));
+        }
+        let ramp = trend(samples.iter().copied());
+        assert!((ramp.slope - 2.5).abs() < 0.001);
+        assert_eq!((ramp.min, ramp.max, ramp.samples), (20.0, 22.0, 5));
+
+        // Falling temperature reports a negative slope
+        let falling = trend([(25.0, 2_000_000), (26.0, 1_000_000)].into_iter());
+        assert!((falling.slope + 1.0).abs() < 0.001);
+    }
+
+    /// Push `count` samples rising by 1 degree every 100ms and return the resulting average and trend
+    async fn fill_samples<const N: usize>(count: u16) -> (DegreesCelsius, sensor::Trend) {
+        let mut resources = Resources::<MockSensor, N>::default();
+        let (service, _runner) = Service::<_, NoopSender, N>::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: MockSensor::config(),
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        for i in 0..count {
+            service.inner.samples.lock().await.push(20.0 + f32::from(i));
+            service.inner.sample_times.lock().await.push(u64::from(i) * 100_000);
+        }
+
+        (service.temperature_average().await, service.temperature_trend().await)
+    }
+
+    #[test]
+    fn test_sample_buf_len() {
+        embassy_futures::block_on(async {
+            // A small buffer only retains the latest 4 of the 8 samples
+            let (average, trend) = fill_samples::<4>(8).await;
+            assert_eq!(average, 25.5);
+            assert_eq!((trend.min, trend.max, trend.samples), (24.0, 27.0, 4));
+            assert!((trend.slope - 10.0).abs() < 0.001);
+
+            // A large buffer retains all of them
+            let (average, trend) = fill_samples::<64>(8).await;
+            assert_eq!(average, 23.5);
+            assert_eq!((trend.min, trend.max, trend.samples), (20.0, 27.0, 8));
+            assert!((trend.slope - 10.0).abs() < 0.001);
+
+            // A single sample buffer only ever holds the most recent sample
+            let (average, trend) = fill_samples::<1>(8).await;
+            assert_eq!(average, 27.0);
+            assert_eq!(trend.samples, 1);
+            assert_eq!(trend.slope, 0.0);
+        });
+    }
+
+    #[tokio::test]
+    async fn test_trend_from_samples() {
+        let mut resources = Resources::<MockSensor, 8>::default();
+        let (service, runner) = Service::<_, NoopSender, 8>::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    // Only the initial sample and requested samples are taken
+                    sample_period: Duration::from_secs(60),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            next_sample_time(&service, None).await;
+
+            // The mock sensor rises by a degree on every read
+            for _ in 0..4 {
+                Timer::after_millis(10).await;
+                service.sample_now().await.unwrap();
+            }
+
+            let trend = service.temperature_trend().await;
+            assert_eq!(
+                (trend.min, trend.max, trend.samples),
+                (crate::mock::MIN_TEMP, crate::mock::MIN_TEMP + 4.0, 5)
+            );
+            assert!(trend.slope > 0.0);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_event_send_retry() {
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        let mut event_senders = [FlakySender {
+            failures: 3,
+            sender: channel.dyn_sender(),
+        }];
+
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (service, runner) = Service::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    event_retry_attempts: 3,
+                    event_retry_interval: Duration::from_millis(1),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut event_senders,
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            // The first send and two retries fail, the event should still be delivered by the last retry
+            let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+            assert_eq!(event, sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh));
+            // Later events waited behind it rather than overtaking it
+            let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+            assert_eq!(event, sensor::Event::ThresholdExceeded(sensor::Threshold::Prochot));
+            assert_eq!(service.dropped_events(), 0);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_event_send_failure() {
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        let mut event_senders = [FlakySender {
+            failures: 2,
+            sender: channel.dyn_sender(),
+        }];
+
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (service, runner) = Service::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    event_retry_attempts: 1,
+                    event_retry_interval: Duration::from_millis(1),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut event_senders,
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            // The first send and its only retry fail, the event is dropped and the next one is delivered
+            let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+            assert_eq!(event, sensor::Event::ThresholdExceeded(sensor::Threshold::Prochot));
+            assert_eq!(service.dropped_events(), 1);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_bus_timeout() {
+        let mut resources = Resources::<HangingSensor, 1>::default();
+        let (service, _runner) = Service::<_, NoopSender, 1>::new(
+            &mut resources,
+            InitParams {
+                driver: HangingSensor,
+                config: Config {
+                    retry_attempts: 2,
+                    bus_timeout: Duration::from_millis(10),
+                    ..Default::default()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        // Each attempt times out rather than waiting on the hung read forever
+        let result = with_timeout(Duration::from_secs(1), service.temperature_immediate()).await;
+        assert_eq!(result, Ok(Err(sensor::Error::RetryExhausted)));
+
+        // The timed out reads must release the driver for other users
+        assert!(
+            with_timeout(Duration::from_secs(1), service.capabilities())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reinitialize() {
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        let mut event_senders = [channel.dyn_sender()];
+
+        let mut resources = Resources::<FailingSensor, 1>::default();
+        let (service, runner) = Service::new(
+            &mut resources,
+            InitParams {
+                driver: FailingSensor {
+                    failed: true,
+                    inner: MockSensor::new(),
+                },
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    bus_timeout: Duration::from_millis(10),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut event_senders,
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+            assert_eq!(event, sensor::Event::Failure(sensor::Error::RetryExhausted));
+            assert!(!service.heartbeat().await.sampling_enabled);
+
+            service.reinitialize().await.unwrap();
+            assert!(service.heartbeat().await.sampling_enabled);
+
+            // Sampling resumes, so the mock's rising temperature eventually crosses a threshold
+            let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+            assert_eq!(event, sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_thresholds() {
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (service, _runner) = Service::<_, NoopSender, 1>::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    hysteresis: 1.5,
+                    warn_high_threshold: 40.0,
+                    ..Default::default()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        service.set_threshold(sensor::Threshold::WarnLow, -10.0).await;
+        service.set_threshold(sensor::Threshold::Prochot, 85.0).await;
+        service.set_threshold(sensor::Threshold::Critical, 95.0).await;
+
+        let thresholds = service.thresholds().await;
+        assert_eq!(
+            thresholds,
+            [
+                (sensor::Threshold::WarnLow, -10.0),
+                (sensor::Threshold::WarnHigh, 40.0),
+                (sensor::Threshold::Prochot, 85.0),
+                (sensor::Threshold::Critical, 95.0),
+            ]
+            .map(|(threshold, value)| sensor::ThresholdSetting {
+                threshold,
+                value,
+                hysteresis: 1.5,
+            })
+        );
+
+        // Readback follows the documented order
+        for (setting, threshold) in thresholds.iter().zip(sensor::Threshold::ALL) {
+            assert_eq!(setting.threshold, *threshold);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_sample_now() {
+        let mut resources = Resources::<MockSensor, 4>::default();
+        let (service, runner) = Service::<_, NoopSender, 4>::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    // Only the initial sample and requested samples are taken
+                    sample_period: Duration::from_secs(60),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            next_sample_time(&service, None).await;
+
+            // Every concurrent caller completes, served by the same sample
+            let results = with_timeout(
+                Duration::from_secs(1),
+                embassy_futures::join::join_array([(); 3].map(|_| service.sample_now())),
+            )
+            .await
+            .unwrap();
+            for result in results {
+                assert_eq!(result, Ok(crate::mock::MIN_TEMP + 1.0));
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_last_sample_time() {
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (service, runner) = Service::<_, NoopSender, 1>::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: MockSensor::config(),
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(service.last_sample_time().await, None);
+
+        select(runner.run(), async {
+            service.sample_now().await.unwrap();
+            let first = service.last_sample_time().await.unwrap();
+
+            Timer::after_millis(1).await;
+            service.sample_now().await.unwrap();
+            let second = service.last_sample_time().await.unwrap();
+            assert!(second > first);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_pause_sampling() {
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        let mut event_senders = [channel.dyn_sender()];
+
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (service, runner) = Service::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut event_senders,
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            Timer::after_millis(10).await;
+            assert!(service.heartbeat().await.last_sample.is_some());
+
+            service.pause_sampling().await;
+            // Let the runner finish handling its last sample
+            Timer::after_millis(10).await;
+            channel.clear();
+            let paused_heartbeat = service.heartbeat().await;
+            assert!(paused_heartbeat.paused);
+            assert!(paused_heartbeat.sampling_enabled);
+
+            // No samples are taken and no events are sent while paused
+            Timer::after_millis(50).await;
+            assert_eq!(service.heartbeat().await, paused_heartbeat);
+            assert!(channel.try_receive().is_err());
+
+            service.resume_sampling().await;
+            let resumed_heartbeat = service.heartbeat().await;
+            assert!(!resumed_heartbeat.paused);
+
+            // Sampling restarts
+            Timer::after_millis(10).await;
+            assert!(service.heartbeat().await.last_sample > resumed_heartbeat.last_sample);
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_sub_zero_thresholds() {
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        let mut event_senders = [channel.dyn_sender()];
+
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (_service, runner) = Service::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::with_range(-20.0, 0.0),
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    hysteresis: 2.0,
+                    warn_low_threshold: -10.0,
+                    warn_high_threshold: -5.0,
+                    ..Default::default()
+                },
+                event_senders: &mut event_senders,
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            // Starting at -20 trips the low threshold, rising past -8 clears it, rising to -5 trips the high
+            // threshold, falling below -7 clears it and falling to -10 trips the low threshold again
+            for expected in [
+                sensor::Event::ThresholdExceeded(sensor::Threshold::WarnLow),
+                sensor::Event::ThresholdCleared(sensor::Threshold::WarnLow),
+                sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh),
+                sensor::Event::ThresholdCleared(sensor::Threshold::WarnHigh),
+                sensor::Event::ThresholdExceeded(sensor::Threshold::WarnLow),
+            ] {
+                let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+                assert_eq!(event, expected);
+            }
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_threshold_direction() {
+        assert_eq!(
+            sensor::Threshold::WarnLow.direction(),
+            sensor::ThresholdDirection::Falling
+        );
+        for threshold in [
+            sensor::Threshold::WarnHigh,
+            sensor::Threshold::Prochot,
+            sensor::Threshold::Critical,
+        ] {
+            assert_eq!(threshold.direction(), sensor::ThresholdDirection::Rising);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_combined_thresholds() {
+        let channel: Channel<GlobalRawMutex, sensor::Event, 4> = Channel::new();
+        let mut event_senders = [channel.dyn_sender()];
+
+        // Under-temperature protection below 0 alongside high-side thresholds on the same sensor
+        let mut resources = Resources::<MockSensor, 1>::default();
+        let (_service, runner) = Service::new(
+            &mut resources,
+            InitParams {
+                driver: MockSensor::with_range(-5.0, 45.0),
+                config: Config {
+                    sample_period: Duration::from_millis(1),
+                    hysteresis: 2.0,
+                    warn_low_threshold: 0.0,
+                    warn_high_threshold: 40.0,
+                    prochot_threshold: 42.0,
+                    ..Default::default()
+                },
+                event_senders: &mut event_senders,
+            },
+        )
+        .await
+        .unwrap();
+
+        select(runner.run(), async {
+            for expected in [
+                // Starts below the low-side threshold, clears once above 2
+                sensor::Event::ThresholdExceeded(sensor::Threshold::WarnLow),
+                sensor::Event::ThresholdCleared(sensor::Threshold::WarnLow),
+                // Rising through the high-side thresholds
+                sensor::Event::ThresholdExceeded(sensor::Threshold::WarnHigh),
+                sensor::Event::ThresholdExceeded(sensor::Threshold::Prochot),
+                // Falling back below each high-side threshold minus hysteresis
+                sensor::Event::ThresholdCleared(sensor::Threshold::Prochot),
+                sensor::Event::ThresholdCleared(sensor::Threshold::WarnHigh),
+                // And back down to the low-side threshold
+                sensor::Event::ThresholdExceeded(sensor::Threshold::WarnLow),
+            ] {
+                let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+                assert_eq!(event, expected);
+            }
+        })
+        .await;
+    }
+
+    async fn new_phased_sensor(
+        resources: &mut Resources<MockSensor, 1>,
+        phase_ms: u64,
+    ) -> (
+        Service<'_, MockSensor, NoopSender, 1>,
+        Runner<'_, MockSensor, NoopSender, 1>,
+    ) {
+        Service::new(
+            resources,
+            InitParams {
+                driver: MockSensor::new(),
+                config: Config {
+                    sample_period: Duration::from_secs(1),
+                    sample_phase: Duration::from_millis(phase_ms),
+                    ..MockSensor::config()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sample_phase() {
+        let mut resources = [
+            Resources::<MockSensor, 1>::default(),
+            Resources::default(),
+            Resources::default(),
+        ];
+        let [resources0, resources1, resources2] = &mut resources;
+        let (sensor0, runner0) = new_phased_sensor(resources0, 0).await;
+        let (sensor1, runner1) = new_phased_sensor(resources1, 100).await;
+        let (sensor2, runner2) = new_phased_sensor(resources2, 200).await;
+
+        // The runners start after this, so each first sample is at least its phase past it
+        let start = Instant::now();
+        select4(runner0.run(), runner1.run(), runner2.run(), async {
+            let first = next_sample_time(&sensor0, None).await;
+            let second = next_sample_time(&sensor1, None).await;
+            let third = next_sample_time(&sensor2, None).await;
+
+            assert!(second - start >= Duration::from_millis(100));
+            assert!(third - start >= Duration::from_millis(200));
+            // Each sensor takes its first sample at its own phase within the period
+            assert!(first < second && second < third);
+        })
+        .await;
+    }
 }
@@ -0,0 +1,135 @@
+//! Test doubles and fixtures shared between the service tests
+#![allow(clippy::unwrap_used)]
+extern crate std;
+
+use crate::fan as fan_service;
+use crate::mock::fan::{MockFan, MockFanError};
+use crate::mock::sensor::{MockSensor, MockSensorError};
+use crate::sensor as sensor_service;
+use embedded_sensors_hal_async::sensor as sensor_traits;
+use embedded_sensors_hal_async::temperature::{DegreesCelsius, TemperatureSensor};
+use embedded_services::event::{NonBlockingSender, NoopSender};
+use std::boxed::Box;
+use thermal_service_interface::{fan, sensor};
+
+/// Sensor whose reads never complete, simulating a hung bus.
+pub(crate) struct HangingSensor;
+
+impl sensor_traits::ErrorType for HangingSensor {
+    type Error = MockSensorError;
+}
+
+impl TemperatureSensor for HangingSensor {
+    async fn temperature(&mut self) -> Result<DegreesCelsius, Self::Error> {
+        core::future::pending().await
+    }
+}
+
+impl sensor::Driver for HangingSensor {}
+
+/// Fan whose RPM reads never complete, simulating a wedged bus.
+pub(crate) struct HangingFan;
+
+impl embedded_fans_async::ErrorType for HangingFan {
+    type Error = MockFanError;
+}
+
+impl embedded_fans_async::Fan for HangingFan {
+    fn min_rpm(&self) -> u16 {
+        0
+    }
+
+    fn max_rpm(&self) -> u16 {
+        6000
+    }
+
+    fn min_start_rpm(&self) -> u16 {
+        1000
+    }
+
+    async fn set_speed_rpm(&mut self, rpm: u16) -> Result<u16, Self::Error> {
+        Ok(rpm)
+    }
+}
+
+impl embedded_fans_async::RpmSense for HangingFan {
+    async fn rpm(&mut self) -> Result<u16, Self::Error> {
+        core::future::pending().await
+    }
+}
+
+impl fan::Driver for HangingFan {}
+
+/// Mock sensor service, as followed by the fans created here
+pub(crate) type MockSensorService<E = NoopSender> = sensor_service::Service<'static, MockSensor, E, 1>;
+/// Runner for a [`MockSensorService`]
+pub(crate) type MockSensorRunner<E = NoopSender> = sensor_service::Runner<'static, MockSensor, E, 1>;
+/// Fan service following a [`MockSensorService`]
+pub(crate) type FanService<'a, T = MockFan, E = NoopSender> =
+    fan_service::Service<'a, T, MockSensorService<E>, NoopSender, 1>;
+/// Runner for a [`FanService`]
+pub(crate) type FanRunner<'a, T = MockFan, E = NoopSender> =
+    fan_service::Runner<'a, T, MockSensorService<E>, NoopSender, 1>;
+
+/// Creates a mock sensor service.
+///
+/// The service's resources are leaked so the service can be handed to other services.
+pub(crate) async fn new_sensor(config: sensor_service::Config) -> (MockSensorService, MockSensorRunner) {
+    new_sensor_with_senders(config, []).await
+}
+
+/// Creates a mock sensor service which sends its events to `event_senders`, leaking them like [`new_sensor`].
+pub(crate) async fn new_sensor_with_senders<E: NonBlockingSender<sensor::Event> + 'static, const N: usize>(
+    config: sensor_service::Config,
+    event_senders: [E; N],
+) -> (MockSensorService<E>, MockSensorRunner<E>) {
+    sensor_service::Service::new(
+        Box::leak(Box::default()),
+        sensor_service::InitParams {
+            driver: MockSensor::new(),
+            config,
+            event_senders: Box::leak(Box::new(event_senders)),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Creates a fan service over `driver`, following the temperature of `sensor` as derived by `aggregation`.
+///
+/// The service's resources are leaked, like those of [`new_sensor`].
+pub(crate) async fn new_fan<'a, T: fan::Driver + 'a, E: NonBlockingSender<sensor::Event> + 'static>(
+    driver: T,
+    config: fan_service::Config,
+    sensor: MockSensorService<E>,
+    aggregation: fan_service::Aggregation<'a, MockSensorService<E>>,
+) -> (FanService<'a, T, E>, FanRunner<'a, T, E>) {
+    fan_service::Service::new(
+        Box::leak(Box::default()),
+        fan_service::InitParams {
+            driver,
+            config,
+            sensor_service: sensor,
+            event_senders: &mut [],
+            profile_storage: None,
+            aggregation,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+/// Creates a mock fan following the temperature of its own mock sensor.
+pub(crate) async fn new_fan_with_sensor(
+    config: fan_service::Config,
+    sensor_config: sensor_service::Config,
+) -> (
+    FanService<'static>,
+    FanRunner<'static>,
+    MockSensorService,
+    MockSensorRunner,
+) {
+    let (sensor, sensor_runner) = new_sensor(sensor_config).await;
+    let (fan, fan_runner) = new_fan(MockFan::new(), config, sensor, fan_service::Aggregation::Single).await;
+    (fan, fan_runner, sensor, sensor_runner)
+}
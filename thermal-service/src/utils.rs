@@ -7,8 +7,12 @@ pub struct SampleBuf<T: Default + Copy + core::fmt::Debug, const N: usize> {
 }
 
 impl<T: Default + Copy + core::fmt::Debug, const N: usize> SampleBuf<T, N> {
+    /// Evaluated when a buffer is created, rejecting a zero length at compile time
+    const NON_ZERO_LEN: () = assert!(N > 0, "sample buffer length must be at least 1");
+
     /// Create a new sample buffer
     pub fn create() -> Self {
+        let () = Self::NON_ZERO_LEN;
         Self { deque: Deque::new() }
     }
 
@@ -26,6 +30,11 @@ impl<T: Default + Copy + core::fmt::Debug, const N: usize> SampleBuf<T, N> {
     pub fn recent(&self) -> T {
         *self.deque.front().unwrap_or(&T::default())
     }
+
+    /// Iterate over the samples in the buffer, from most to least recent
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.deque.iter()
+    }
 }
 
 impl<const N: usize> SampleBuf<f32, N> {
@@ -0,0 +1,201 @@
+//! Sensor watchdog, detects sensors whose sampling task has stalled.
+use embassy_time::{Duration, Instant, Timer};
+use embedded_services::event::NonBlockingSender;
+use embedded_services::{error, info};
+use thermal_service_interface::Event;
+use thermal_service_interface::sensor::{self, SensorService};
+
+/// Watchdog configuration parameters.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Rate at which sensor heartbeats are checked.
+    pub check_period: Duration,
+    /// Number of sample periods a sensor may go without sampling before it is considered stalled.
+    pub stale_sample_periods: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            check_period: Duration::from_secs(1),
+            stale_sample_periods: 3,
+        }
+    }
+}
+
+/// Parameters required to initialize the sensor watchdog.
+pub struct InitParams<'hw, S: SensorService, E: NonBlockingSender<Event>, const SENSOR_COUNT: usize> {
+    /// Sensors to monitor, the index of each sensor is used as its instance ID in events.
+    pub sensors: &'hw [S; SENSOR_COUNT],
+    /// Watchdog configuration.
+    pub config: Config,
+    /// Event senders for sensor failure events.
+    pub event_senders: &'hw mut [E],
+}
+
+/// A task runner for the sensor watchdog. Users must run this in an embassy task or similar async execution context.
+pub struct Runner<'hw, S: SensorService, E: NonBlockingSender<Event>, const SENSOR_COUNT: usize> {
+    sensors: &'hw [S; SENSOR_COUNT],
+    config: Config,
+    event_senders: &'hw mut [E],
+    // Whether a failure has already been reported for each sensor
+    stalled: [bool; SENSOR_COUNT],
+    // Reference point for sensors which have not been sampled yet
+    start: Instant,
+}
+
+impl<'hw, S: SensorService, E: NonBlockingSender<Event>, const SENSOR_COUNT: usize> Runner<'hw, S, E, SENSOR_COUNT> {
+    /// Create a new sensor watchdog.
+    pub fn new(init_params: InitParams<'hw, S, E, SENSOR_COUNT>) -> Self {
+        Self {
+            sensors: init_params.sensors,
+            config: init_params.config,
+            event_senders: init_params.event_senders,
+            stalled: [false; SENSOR_COUNT],
+            start: Instant::now(),
+        }
+    }
+
+    fn broadcast_event(event_senders: &mut [E], event: Event) {
+        for sender in event_senders.iter_mut() {
+            if sender.try_send(event).is_none() {
+                error!("Failed to send watchdog event");
+            }
+        }
+    }
+
+    async fn check_sensors(&mut self) {
+        let now = Instant::now();
+
+        for ((id, sensor), was_stalled) in self.sensors.iter().enumerate().zip(self.stalled.iter_mut()) {
+            let heartbeat = sensor.heartbeat().await;
+            let stalled = heartbeat.sampling_enabled
+                && !heartbeat.paused
+                && now.saturating_duration_since(heartbeat.last_sample.unwrap_or(self.start))
+                    > heartbeat.sample_period * self.config.stale_sample_periods;
+
+            if stalled && !*was_stalled {
+                error!("Sensor {} {} has stalled", id, sensor.label().unwrap_or("(unlabeled)"));
+                match u8::try_from(id) {
+                    Ok(id) => {
+                        Self::broadcast_event(self.event_senders, Event::SensorFailure(id, sensor::Error::Stalled))
+                    }
+                    Err(_) => error!("Sensor {} has no instance ID representable in an event", id),
+                }
+            } else if !stalled && *was_stalled {
+                info!(
+                    "Sensor {} {} has recovered",
+                    id,
+                    sensor.label().unwrap_or("(unlabeled)")
+                );
+            }
+
+            *was_stalled = stalled;
+        }
+    }
+}
+
+impl<'hw, S: SensorService + 'hw, E: NonBlockingSender<Event> + 'hw, const SENSOR_COUNT: usize>
+    odp_service_common::runnable_service::ServiceRunner<'hw> for Runner<'hw, S, E, SENSOR_COUNT>
+{
+    async fn run(mut self) -> embedded_services::Never {
+        loop {
+            Timer::after(self.config.check_period).await;
+            self.check_sensors().await;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::sensor as sensor_service;
+    use crate::test_utils::{HangingSensor, new_sensor};
+    use embassy_futures::select::select3;
+    use embassy_sync::channel::Channel;
+    use embassy_time::with_timeout;
+    use embedded_services::GlobalRawMutex;
+    use embedded_services::event::NoopSender;
+    use odp_service_common::runnable_service::ServiceRunner;
+
+    #[tokio::test]
+    async fn test_stalled_sensor() {
+        let mut resources = sensor_service::Resources::<HangingSensor, 1>::default();
+        let (service, sensor_runner) = sensor_service::Service::<_, NoopSender, 1>::new(
+            &mut resources,
+            sensor_service::InitParams {
+                driver: HangingSensor,
+                config: sensor_service::Config {
+                    sample_period: Duration::from_millis(10),
+                    // Keep retrying so the sampling task stays stuck on the hung read
+                    retry_attempts: u8::MAX,
+                    ..Default::default()
+                },
+                event_senders: &mut [],
+            },
+        )
+        .await
+        .unwrap();
+
+        let sensors = [service];
+        let channel: Channel<GlobalRawMutex, Event, 4> = Channel::new();
+        let mut event_senders = [channel.dyn_sender()];
+        let watchdog = Runner::new(InitParams {
+            sensors: &sensors,
+            config: Config {
+                check_period: Duration::from_millis(10),
+                stale_sample_periods: 3,
+            },
+            event_senders: &mut event_senders,
+        });
+
+        select3(sensor_runner.run(), watchdog.run(), async {
+            let event = with_timeout(Duration::from_secs(1), channel.receive()).await.unwrap();
+            assert_eq!(event, Event::SensorFailure(0, sensor::Error::Stalled));
+
+            // The failure should only be reported once
+            assert!(
+                with_timeout(Duration::from_millis(100), channel.receive())
+                    .await
+                    .is_err()
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_paused_sensor() {
+        use sensor::SensorService as _;
+
+        let (service, sensor_runner) = new_sensor(sensor_service::Config {
+            sample_period: Duration::from_millis(10),
+            ..crate::mock::sensor::MockSensor::config()
+        })
+        .await;
+
+        let sensors = [service];
+        let channel: Channel<GlobalRawMutex, Event, 4> = Channel::new();
+        let mut event_senders = [channel.dyn_sender()];
+        let watchdog = Runner::new(InitParams {
+            sensors: &sensors,
+            config: Config {
+                check_period: Duration::from_millis(10),
+                stale_sample_periods: 3,
+            },
+            event_senders: &mut event_senders,
+        });
+
+        select3(sensor_runner.run(), watchdog.run(), async {
+            // A paused sensor isn't sampled, but isn't reported as stalled either
+            service.pause_sampling().await;
+            assert!(
+                with_timeout(Duration::from_millis(100), channel.receive())
+                    .await
+                    .is_err()
+            );
+        })
+        .await;
+    }
+}
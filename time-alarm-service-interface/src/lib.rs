@@ -66,6 +66,12 @@ impl AcpiTimerId {
     }
 }
 
+/// Message notifying the time-alarm service that the system has switched power source.  The contained ID is the
+/// timer for the new power source, which becomes the active timer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerSourceChanged(pub AcpiTimerId);
+
 bitfield!(
     /// Describes the current status of a timer, including whether it has expired and whether it triggered a wake event.
     #[derive(Copy, Clone, Default, PartialEq, Eq)]
@@ -6,6 +6,7 @@ use embassy_sync::signal::Signal;
 use embedded_mcu_hal::nvram::NvramStorage;
 use embedded_mcu_hal::time::{Datetime, DatetimeClock, DatetimeClockError};
 use embedded_services::GlobalRawMutex;
+use embedded_services::comms;
 use embedded_services::{info, warn};
 use time_alarm_service_interface::*;
 
@@ -76,12 +77,47 @@ struct ClockState<'hw> {
 
 // -------------------------------------------------
 
-struct Timers<'hw> {
+/// Minimum backing clock resolution required to report real time with millisecond accuracy.
+const MILLISECOND_RESOLUTION_HZ: u32 = 1000;
+
+// -------------------------------------------------
+
+/// Determines how a programmed timer reacts when the current time is changed with `set_real_time`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimerValueMode {
+    /// The expiration time is pinned to the wall clock.  Moving the clock forward past the expiration time causes the
+    /// timer to expire immediately, and moving it backward delays the expiration.  This is the ACPI TAD behavior.
+    #[default]
+    WallClock,
+    /// The timer expires after the programmed number of seconds regardless of any changes to the current time.
+    /// This is not persisted, so after a reset the timer reverts to [`TimerValueMode::WallClock`].
+    Relative,
+}
+
+// -------------------------------------------------
+
+/// Identifies an application-defined timer by the position of its storage in the `app_timer_storage` passed to
+/// [`Service::new_with_app_timers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AppTimerId(pub u8);
+
+/// NVRAM storage backing an application-defined timer.
+pub struct TimerStorage<'hw> {
+    /// Storage for the timer's expiration time.
+    pub expiration: &'hw mut dyn NvramStorage<'hw, u32>,
+    /// Storage for the timer's expired timer policy.
+    pub policy: &'hw mut dyn NvramStorage<'hw, u32>,
+}
+
+struct Timers<'hw, const N: usize> {
     ac_timer: Timer<'hw>,
     dc_timer: Timer<'hw>,
+    app_timers: [Timer<'hw>; N],
 }
 
-impl<'hw> Timers<'hw> {
+impl<'hw, const N: usize> Timers<'hw, N> {
     fn get_timer(&self, timer: AcpiTimerId) -> &Timer<'hw> {
         match timer {
             AcpiTimerId::AcPower => &self.ac_timer,
@@ -89,15 +125,28 @@ impl<'hw> Timers<'hw> {
         }
     }
 
+    fn get_app_timer(&self, timer: AppTimerId) -> Option<&Timer<'hw>> {
+        self.app_timers.get(usize::from(timer.0))
+    }
+
+    /// Iterate over every timer, ACPI timers first.
+    fn iter(&self) -> impl Iterator<Item = &Timer<'hw>> {
+        [&self.ac_timer, &self.dc_timer]
+            .into_iter()
+            .chain(self.app_timers.iter())
+    }
+
     fn new(
         ac_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         ac_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         dc_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         dc_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+        app_timer_storage: [TimerStorage<'hw>; N],
     ) -> Self {
         Self {
             ac_timer: Timer::new(ac_expiration_storage, ac_policy_storage),
             dc_timer: Timer::new(dc_expiration_storage, dc_policy_storage),
+            app_timers: app_timer_storage.map(|storage| Timer::new(storage.expiration, storage.policy)),
         }
     }
 }
@@ -106,18 +155,30 @@ impl<'hw> Timers<'hw> {
 
 /// The main service implementation.  Users will interact with this via the Service struct, which is a thin wrapper around this that allows
 /// the client to provide storage for the service.
-struct ServiceInner<'hw> {
+struct ServiceInner<'hw, const N: usize> {
     clock_state: Mutex<GlobalRawMutex, RefCell<ClockState<'hw>>>,
 
-    // TODO [POWER_SOURCE] signal this whenever the power source changes
+    // Receives PowerSourceChanged messages once registered with comms
+    endpoint: comms::Endpoint,
+
+    // Signaled with the new power source whenever a PowerSourceChanged message is received
     power_source_signal: Signal<GlobalRawMutex, AcpiTimerId>,
 
-    timers: Timers<'hw>,
+    // Signaled with the new power source once its timer has been made the active one
+    power_source_change_signal: Signal<GlobalRawMutex, AcpiTimerId>,
+
+    // Signaled with the ID of a timer whenever it expires and triggers a wake
+    timer_expiry_signal: Signal<GlobalRawMutex, AcpiTimerId>,
+
+    // Signaled with the ID of an application-defined timer whenever it expires
+    app_timer_expiry_signal: Signal<GlobalRawMutex, AppTimerId>,
+
+    timers: Timers<'hw, N>,
 
     capabilities: TimeAlarmDeviceCapabilities,
 }
 
-impl<'hw> ServiceInner<'hw> {
+impl<'hw, const N: usize> ServiceInner<'hw, N> {
     fn new(
         backing_clock: &'hw mut dyn DatetimeClock,
         tz_storage: &'hw mut dyn NvramStorage<'hw, u32>,
@@ -125,18 +186,26 @@ impl<'hw> ServiceInner<'hw> {
         ac_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         dc_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         dc_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+        app_timer_storage: [TimerStorage<'hw>; N],
     ) -> Self {
+        // Only report millisecond accuracy if the backing clock can actually resolve milliseconds
+        let millisecond_accuracy = backing_clock.resolution_hz() >= MILLISECOND_RESOLUTION_HZ;
         Self {
             clock_state: Mutex::new(RefCell::new(ClockState {
                 datetime_clock: backing_clock,
                 tz_data: TimeZoneData::new(tz_storage),
             })),
+            endpoint: comms::Endpoint::uninit(comms::EndpointID::Internal(comms::Internal::TimeAlarm)),
             power_source_signal: Signal::new(),
+            power_source_change_signal: Signal::new(),
+            timer_expiry_signal: Signal::new(),
+            app_timer_expiry_signal: Signal::new(),
             timers: Timers::new(
                 ac_expiration_storage,
                 ac_policy_storage,
                 dc_expiration_storage,
                 dc_policy_storage,
+                app_timer_storage,
             ),
             capabilities: {
                 // TODO [CONFIG] We could consider making some of these user-configurable, e.g. if we want to support devices that don't have a battery
@@ -144,7 +213,7 @@ impl<'hw> ServiceInner<'hw> {
                 caps.set_ac_wake_implemented(true);
                 caps.set_dc_wake_implemented(true);
                 caps.set_realtime_implemented(true);
-                caps.set_realtime_accuracy_in_milliseconds(false);
+                caps.set_realtime_accuracy_in_milliseconds(millisecond_accuracy);
                 caps.set_get_wake_status_supported(true);
                 caps.set_ac_s4_wake_supported(true);
                 caps.set_ac_s5_wake_supported(true);
@@ -164,7 +233,11 @@ impl<'hw> ServiceInner<'hw> {
     fn get_real_time(&self) -> Result<AcpiTimestamp, DatetimeClockError> {
         self.clock_state.lock(|clock_state| {
             let clock_state = clock_state.borrow();
-            let datetime = clock_state.datetime_clock.now()?;
+            let mut datetime = clock_state.datetime_clock.now()?;
+            if !self.capabilities.realtime_accuracy_in_milliseconds() {
+                // Sub-second values from a low-resolution clock aren't meaningful, only report whole seconds
+                datetime = Datetime::from_unix_timestamp(datetime.unix_timestamp());
+            }
             let (time_zone, dst_status) = clock_state.tz_data.get_data();
             Ok(AcpiTimestamp {
                 datetime,
@@ -176,12 +249,20 @@ impl<'hw> ServiceInner<'hw> {
 
     /// Change the current time.  Analogous to ACPI TAD's _SRT method.
     fn set_real_time(&self, timestamp: AcpiTimestamp) -> Result<(), DatetimeClockError> {
-        self.clock_state.lock(|clock_state| {
+        let delta_secs = self.clock_state.lock(|clock_state| {
             let mut clock_state = clock_state.borrow_mut();
+            let previous_time = clock_state.datetime_clock.now()?;
             clock_state.datetime_clock.set(timestamp.datetime)?;
             clock_state.tz_data.set_data(timestamp.time_zone, timestamp.dst_status);
-            Ok(())
-        })
+            Ok::<_, DatetimeClockError>(
+                timestamp.datetime.unix_timestamp() as i64 - previous_time.unix_timestamp() as i64,
+            )
+        })?;
+
+        for timer in self.timers.iter() {
+            timer.handle_clock_change(&self.clock_state, delta_secs)?;
+        }
+        Ok(())
     }
 
     /// Query the current wake status.  Analogous to ACPI TAD's _GWS method.
@@ -202,7 +283,7 @@ impl<'hw> ServiceInner<'hw> {
     ) -> Result<(), DatetimeClockError> {
         self.timers
             .get_timer(timer_id)
-            .set_timer_wake_policy(&self.clock_state, policy)?;
+            .set_explicit_timer_wake_policy(&self.clock_state, policy)?;
         Ok(())
     }
 
@@ -212,7 +293,12 @@ impl<'hw> ServiceInner<'hw> {
     }
 
     /// Change the expiry time for the given timer.  Analogous to ACPI TAD's _STV method.
-    fn set_timer_value(&self, timer_id: AcpiTimerId, timer_value: AlarmTimerSeconds) -> Result<(), DatetimeClockError> {
+    fn set_timer_value(
+        &self,
+        timer: &Timer<'hw>,
+        timer_value: AlarmTimerSeconds,
+        mode: TimerValueMode,
+    ) -> Result<(), DatetimeClockError> {
         let new_expiration_time = match timer_value {
             AlarmTimerSeconds::DISABLED => None,
             AlarmTimerSeconds(secs) => {
@@ -226,31 +312,42 @@ impl<'hw> ServiceInner<'hw> {
             }
         };
 
-        self.timers
-            .get_timer(timer_id)
-            .set_expiration_time(&self.clock_state, new_expiration_time)?;
+        timer.set_expiration_time(&self.clock_state, new_expiration_time, mode == TimerValueMode::Relative)?;
         Ok(())
     }
 
     /// Query the expiry time for the given timer.  Analogous to ACPI TAD's _TIV method.
-    fn get_timer_value(&self, timer_id: AcpiTimerId) -> Result<AlarmTimerSeconds, DatetimeClockError> {
-        let expiration_time = self.timers.get_timer(timer_id).get_expiration_time();
+    fn get_timer_value(&self, timer: &Timer<'hw>) -> Result<AlarmTimerSeconds, DatetimeClockError> {
+        let expiration_time = timer.get_expiration_time();
         match expiration_time {
             Some(expiration_time) => {
                 let current_time = self
                     .clock_state
                     .lock(|clock_state| clock_state.borrow().datetime_clock.now())?;
 
+                // Report timers too far out to represent as the furthest representable value rather than wrapping
+                // around, and never as the reserved disabled value.
+                let max_secs = AlarmTimerSeconds::DISABLED.0 - 1;
+                let remaining_secs = expiration_time
+                    .unix_timestamp()
+                    .saturating_sub(current_time.unix_timestamp());
                 Ok(AlarmTimerSeconds(
-                    expiration_time
-                        .unix_timestamp()
-                        .saturating_sub(current_time.unix_timestamp()) as u32,
+                    u32::try_from(remaining_secs).map_or(max_secs, |secs| secs.min(max_secs)),
                 ))
             }
             None => Ok(AlarmTimerSeconds::DISABLED),
         }
     }
 
+    /// Disable all timers and reset their wake policies to the default, persisting the cleared state.
+    fn clear_all_timers(&self) -> Result<(), DatetimeClockError> {
+        for timer in self.timers.iter() {
+            timer.set_expiration_time(&self.clock_state, None, false)?;
+            timer.set_timer_wake_policy(&self.clock_state, AlarmExpiredWakePolicy::default())?;
+        }
+        Ok(())
+    }
+
     async fn handle_power_source_updates(&'hw self) -> ! {
         loop {
             let new_power_source = self.power_source_signal.wait().await;
@@ -262,6 +359,7 @@ impl<'hw> ServiceInner<'hw> {
             self.timers
                 .get_timer(new_power_source)
                 .set_active(&self.clock_state, true);
+            self.power_source_change_signal.signal(new_power_source);
         }
     }
 
@@ -284,42 +382,96 @@ impl<'hw> ServiceInner<'hw> {
                 timer_id
             );
             // TODO [COMMS] We can't currently trigger a wake because the power service isn't implemented yet - when it is, we need to notify it here
+            self.timer_expiry_signal.signal(timer_id);
+        }
+    }
+
+    async fn handle_app_timer(&'hw self, index: usize) -> ! {
+        let timer_id = AppTimerId(index as u8);
+        let Some(timer) = self.timers.get_app_timer(timer_id) else {
+            // Only called with indices of existing timers
+            return core::future::pending().await;
+        };
+
+        loop {
+            timer.wait_until_wake(&self.clock_state).await;
+            info!("[Time/Alarm] Application timer {:?} expired", timer_id);
+            self.app_timer_expiry_signal.signal(timer_id);
         }
     }
 }
 
-/// The memory resources required by the time/alarm service.
+/// The memory resources required by the time/alarm service, with room for `N` application-defined timers.
 #[derive(Default)]
-pub struct Resources<'hw> {
-    inner: Option<ServiceInner<'hw>>,
+pub struct Resources<'hw, const N: usize = 0> {
+    inner: Option<ServiceInner<'hw, N>>,
 }
 
 /// A task runner for the time/alarm service. Users of the service must run this object in an embassy task or similar async execution context.
-pub struct Runner<'hw> {
-    service: &'hw ServiceInner<'hw>,
+pub struct Runner<'hw, const N: usize = 0> {
+    service: &'hw ServiceInner<'hw, N>,
 }
 
-impl<'hw> odp_service_common::runnable_service::ServiceRunner<'hw> for Runner<'hw> {
+impl<'hw, const N: usize> odp_service_common::runnable_service::ServiceRunner<'hw> for Runner<'hw, N> {
     /// Run the service.
     async fn run(self) -> embedded_services::Never {
         loop {
-            embassy_futures::select::select3(
+            embassy_futures::select::select4(
                 self.service.handle_power_source_updates(),
                 self.service.handle_timer(AcpiTimerId::AcPower),
                 self.service.handle_timer(AcpiTimerId::DcPower),
+                embassy_futures::select::select_array(core::array::from_fn::<_, N, _>(|index| {
+                    self.service.handle_app_timer(index)
+                })),
             )
             .await;
         }
     }
 }
 
+/// Control handle for an application-defined timer, see [`Service::app_timer`].
+///
+/// Application timers are not exposed through ACPI.  They are always active, so they expire regardless of the
+/// current power source.
+#[derive(Clone, Copy)]
+pub struct AppTimer<'hw, const N: usize = 0> {
+    inner: &'hw ServiceInner<'hw, N>,
+    timer: &'hw Timer<'hw>,
+}
+
+impl<'hw, const N: usize> AppTimer<'hw, N> {
+    /// Query the current wake status.
+    pub fn get_wake_status(&self) -> TimerStatus {
+        self.timer.get_wake_status()
+    }
+
+    /// Clear the current wake status.
+    pub fn clear_wake_status(&self) {
+        self.timer.clear_wake_status();
+    }
+
+    /// Change the expiry time, specifying how the timer reacts to later changes of the current time.
+    pub fn set_timer_value(
+        &self,
+        timer_value: AlarmTimerSeconds,
+        mode: TimerValueMode,
+    ) -> Result<(), DatetimeClockError> {
+        self.inner.set_timer_value(self.timer, timer_value, mode)
+    }
+
+    /// Query the expiry time.
+    pub fn get_timer_value(&self) -> Result<AlarmTimerSeconds, DatetimeClockError> {
+        self.inner.get_timer_value(self.timer)
+    }
+}
+
 /// Control handle for the time-alarm service.  Use this to manipulate the time on the service.
 #[derive(Clone, Copy)]
-pub struct Service<'hw> {
-    inner: &'hw ServiceInner<'hw>,
+pub struct Service<'hw, const N: usize = 0> {
+    inner: &'hw ServiceInner<'hw, N>,
 }
 
-impl<'hw> TimeAlarmService for Service<'hw> {
+impl<'hw, const N: usize> TimeAlarmService for Service<'hw, N> {
     fn get_capabilities(&self) -> TimeAlarmDeviceCapabilities {
         self.inner.get_capabilities()
     }
@@ -360,31 +512,113 @@ impl<'hw> TimeAlarmService for Service<'hw> {
 
     /// Change the expiry time for the given timer.  Analogous to ACPI TAD's _STV method.
     fn set_timer_value(&self, timer_id: AcpiTimerId, timer_value: AlarmTimerSeconds) -> Result<(), DatetimeClockError> {
-        self.inner.set_timer_value(timer_id, timer_value)
+        self.inner.set_timer_value(
+            self.inner.timers.get_timer(timer_id),
+            timer_value,
+            TimerValueMode::WallClock,
+        )
     }
 
     /// Query the expiry time for the given timer.  Analogous to ACPI TAD's _TIV method.
     fn get_timer_value(&self, timer_id: AcpiTimerId) -> Result<AlarmTimerSeconds, DatetimeClockError> {
-        self.inner.get_timer_value(timer_id)
+        self.inner.get_timer_value(self.inner.timers.get_timer(timer_id))
     }
 }
 
-impl<'hw> odp_service_common::runnable_service::Service<'hw> for Service<'hw> {
-    type Runner = Runner<'hw>;
-    type Resources = Resources<'hw>;
+impl<'hw, const N: usize> comms::MailboxDelegate for ServiceInner<'hw, N> {
+    fn receive(&self, message: &comms::Message) -> Result<(), comms::MailboxDelegateError> {
+        let PowerSourceChanged(power_source) = message
+            .data
+            .get::<PowerSourceChanged>()
+            .ok_or(comms::MailboxDelegateError::MessageNotFound)?;
+
+        self.power_source_signal.signal(*power_source);
+        Ok(())
+    }
 }
 
-impl<'hw> Service<'hw> {
-    /// Initializes an instance of the time-alarm service.
-    pub async fn new(
-        service_storage: &'hw mut Resources<'hw>,
+impl<'hw, const N: usize> odp_service_common::runnable_service::Service<'hw> for Service<'hw, N> {
+    type Runner = Runner<'hw, N>;
+    type Resources = Resources<'hw, N>;
+}
+
+impl<'hw, const N: usize> Service<'hw, N> {
+    /// Change the expiry time for the given timer, specifying how the timer reacts to later changes of the current time.
+    /// [`TimeAlarmService::set_timer_value`] is equivalent to calling this with [`TimerValueMode::WallClock`].
+    pub fn set_timer_value_with_mode(
+        &self,
+        timer_id: AcpiTimerId,
+        timer_value: AlarmTimerSeconds,
+        mode: TimerValueMode,
+    ) -> Result<(), DatetimeClockError> {
+        self.inner
+            .set_timer_value(self.inner.timers.get_timer(timer_id), timer_value, mode)
+    }
+
+    /// Move the expiry time for the given timer by `delta_secs` seconds, e.g. to extend an alarm without racing against
+    /// another update between reading and setting the timer value.  The timer is disabled if this moves its expiry
+    /// time to or before the current time.  Adjusting a disabled or already expired timer has no effect.
+    pub fn adjust_timer_value(&self, timer_id: AcpiTimerId, delta_secs: i32) -> Result<(), DatetimeClockError> {
+        self.inner
+            .timers
+            .get_timer(timer_id)
+            .adjust_expiration_time(&self.inner.clock_state, delta_secs.into())
+    }
+
+    /// Returns a control handle for the application-defined timer `timer_id`, or `None` if there is no such timer.
+    pub fn app_timer(&self, timer_id: AppTimerId) -> Option<AppTimer<'hw, N>> {
+        Some(AppTimer {
+            inner: self.inner,
+            timer: self.inner.timers.get_app_timer(timer_id)?,
+        })
+    }
+
+    /// Wait for an application-defined timer to expire, returning the ID of that timer.  If several timers expire
+    /// before this is awaited, only the most recent one is reported.
+    pub async fn wait_app_timer_expiry(&self) -> AppTimerId {
+        self.inner.app_timer_expiry_signal.wait().await
+    }
+
+    /// Set the expired timer policy applied to the AC and DC timers when they are armed from the disabled state, e.g. on a
+    /// freshly provisioned system.  The default is only applied to a timer whose policy has not been set with
+    /// [`TimeAlarmService::set_expired_timer_policy`] since boot, and is persisted like a policy set by the host.
+    /// `None`, the initial value, leaves the existing policy in place when a timer is armed.
+    pub fn set_default_expired_timer_policy(&self, policy: Option<AlarmExpiredWakePolicy>) {
+        self.inner.timers.ac_timer.set_default_wake_policy(policy);
+        self.inner.timers.dc_timer.set_default_wake_policy(policy);
+    }
+
+    /// Disable the AC, DC and application-defined timers and reset their expired timer policies, e.g. when
+    /// re-provisioning the system.  The cleared state is persisted so it survives a reset.  Calling this with no timers set has no effect.
+    pub fn clear_all_timers(&self) -> Result<(), DatetimeClockError> {
+        self.inner.clear_all_timers()
+    }
+
+    /// Wait for a timer to expire and trigger a wake, returning the ID of that timer.  If several timers expire before
+    /// this is awaited, only the most recent one is reported.
+    pub async fn wait_timer_expiry(&self) -> AcpiTimerId {
+        self.inner.timer_expiry_signal.wait().await
+    }
+
+    /// Wait for a change of power source to take effect, returning the timer that is now active.  If the power source
+    /// changes several times before this is awaited, only the most recent change is reported.
+    pub async fn wait_power_source_change(&self) -> AcpiTimerId {
+        self.inner.power_source_change_signal.wait().await
+    }
+
+    /// Initializes an instance of the time-alarm service with application-defined timers in addition to the ACPI
+    /// AC and DC timers.  Each entry of `app_timer_storage` backs the timer with the [`AppTimerId`] of its position.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_with_app_timers(
+        service_storage: &'hw mut Resources<'hw, N>,
         backing_clock: &'hw mut dyn DatetimeClock,
         tz_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         ac_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         ac_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         dc_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
         dc_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
-    ) -> Result<(Self, Runner<'hw>), DatetimeClockError> {
+        app_timer_storage: [TimerStorage<'hw>; N],
+    ) -> Result<(Self, Runner<'hw, N>), DatetimeClockError> {
         let service = service_storage.inner.insert(ServiceInner::new(
             backing_clock,
             tz_storage,
@@ -392,13 +626,49 @@ impl<'hw> Service<'hw> {
             ac_policy_storage,
             dc_expiration_storage,
             dc_policy_storage,
+            app_timer_storage,
         ));
 
-        // TODO [POWER_SOURCE] we need to subscribe to messages that tell us if we're on AC or DC power so we can decide which alarms to trigger, but those notifications are not yet implemented - revisit when they are.
         // TODO [POWER_SOURCE] if it's possible to learn which power source is active at init time, we should set that one active rather than defaulting to the AC timer.
         service.timers.ac_timer.start(&service.clock_state, true)?;
         service.timers.dc_timer.start(&service.clock_state, false)?;
+        for timer in service.timers.app_timers.iter() {
+            timer.start(&service.clock_state, true)?;
+        }
 
         Ok((Self { inner: service }, Runner { service }))
     }
 }
+
+impl<const N: usize> Service<'static, N> {
+    /// Register the service's comms endpoint so it is notified through [`PowerSourceChanged`] messages when the power
+    /// source changes, which decides whether the AC or DC timer is active.
+    pub async fn register_endpoint(&self) -> Result<(), embedded_services::intrusive_list::Error> {
+        comms::register_endpoint(self.inner, &self.inner.endpoint).await
+    }
+}
+
+impl<'hw> Service<'hw> {
+    /// Initializes an instance of the time-alarm service with only the ACPI AC and DC timers.
+    pub async fn new(
+        service_storage: &'hw mut Resources<'hw>,
+        backing_clock: &'hw mut dyn DatetimeClock,
+        tz_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+        ac_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+        ac_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+        dc_expiration_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+        dc_policy_storage: &'hw mut dyn NvramStorage<'hw, u32>,
+    ) -> Result<(Self, Runner<'hw>), DatetimeClockError> {
+        Self::new_with_app_timers(
+            service_storage,
+            backing_clock,
+            tz_storage,
+            ac_expiration_storage,
+            ac_policy_storage,
+            dc_expiration_storage,
+            dc_policy_storage,
+            [],
+        )
+        .await
+    }
+}
@@ -95,6 +95,33 @@ impl DatetimeClock for MockDatetimeClock {
     }
 }
 
+/// Paused `DatetimeClock` that reports millisecond resolution, including the sub-second part of the time.
+pub struct MockMillisecondDatetimeClock {
+    frozen_time: Datetime,
+}
+
+impl MockMillisecondDatetimeClock {
+    /// New `MockMillisecondDatetimeClock` frozen at the given time.
+    pub fn new(frozen_time: Datetime) -> Self {
+        Self { frozen_time }
+    }
+}
+
+impl DatetimeClock for MockMillisecondDatetimeClock {
+    fn now(&self) -> Result<Datetime, DatetimeClockError> {
+        Ok(self.frozen_time)
+    }
+
+    fn set(&mut self, datetime: Datetime) -> Result<(), DatetimeClockError> {
+        self.frozen_time = datetime;
+        Ok(())
+    }
+
+    fn resolution_hz(&self) -> u32 {
+        1000
+    }
+}
+
 pub struct MockNvramStorage<'a> {
     value: u32,
     _phantom: core::marker::PhantomData<&'a ()>,
@@ -90,6 +90,17 @@ struct TimerState<'hw> {
     // Whether or not this timer is currently active (i.e. the system is on the power source this timer manages)
     // Even if it's not active, it still counts down if it's programmed - it just won't trigger a wake event if it expires while inactive.
     is_active: bool,
+
+    // Whether the expiration time should move along with the wall clock when the time is set.
+    // Not persisted, so a timer restored from NVRAM after a reset is always treated as a wall-clock timer.
+    relative: bool,
+
+    // Wake policy applied when the timer is armed from the disabled state, unless the policy has been set explicitly.
+    // Not persisted, it is configured again at every boot.
+    default_wake_policy: Option<AlarmExpiredWakePolicy>,
+
+    // Whether the wake policy has been explicitly set since boot.
+    wake_policy_set: bool,
 }
 
 pub(crate) struct Timer<'hw> {
@@ -109,6 +120,9 @@ impl<'hw> Timer<'hw> {
                 wake_state: WakeState::Clear,
                 timer_status: Default::default(),
                 is_active: false,
+                relative: false,
+                default_wake_policy: None,
+                wake_policy_set: false,
             })),
             timer_signal: Signal::new(),
         }
@@ -129,6 +143,7 @@ impl<'hw> Timer<'hw> {
             clock_state,
             self.timer_state
                 .lock(|timer_state| timer_state.borrow().persistent_storage.get_expiration_time()),
+            false,
         )?;
 
         self.set_active(clock_state, active);
@@ -173,10 +188,28 @@ impl<'hw> Timer<'hw> {
         })
     }
 
+    /// Sets the wake policy on behalf of the host.  Once this has been called, the default wake policy is no longer applied.
+    pub fn set_explicit_timer_wake_policy(
+        &self,
+        clock_state: &Mutex<GlobalRawMutex, RefCell<ClockState<'hw>>>,
+        wake_policy: AlarmExpiredWakePolicy,
+    ) -> Result<(), DatetimeClockError> {
+        self.set_timer_wake_policy(clock_state, wake_policy)?;
+        self.timer_state
+            .lock(|timer_state| timer_state.borrow_mut().wake_policy_set = true);
+        Ok(())
+    }
+
+    pub fn set_default_wake_policy(&self, wake_policy: Option<AlarmExpiredWakePolicy>) {
+        self.timer_state
+            .lock(|timer_state| timer_state.borrow_mut().default_wake_policy = wake_policy);
+    }
+
     pub fn set_expiration_time(
         &self,
         clock_state: &Mutex<GlobalRawMutex, RefCell<ClockState<'hw>>>,
         expiration_time: Option<Datetime>,
+        relative: bool,
     ) -> Result<(), DatetimeClockError> {
         self.timer_state.lock(|timer_state| {
             let mut timer_state = timer_state.borrow_mut();
@@ -192,8 +225,16 @@ impl<'hw> Timer<'hw> {
                             .saturating_sub(Self::now(clock_state)?.unix_timestamp()) as u32, // The ACPI spec doesn't provide a facility to program a timer more than u32::MAX seconds in the future, so this cast is safe
                     ));
 
+                    if timer_state.wake_state == WakeState::Clear
+                        && !timer_state.wake_policy_set
+                        && let Some(wake_policy) = timer_state.default_wake_policy
+                    {
+                        timer_state.persistent_storage.set_timer_wake_policy(wake_policy);
+                    }
+
                     timer_state.persistent_storage.set_expiration_time(expiration_time);
                     timer_state.wake_state = WakeState::Armed;
+                    timer_state.relative = relative;
                 }
                 None => self.clear_expiration_time(&mut timer_state),
             }
@@ -202,6 +243,79 @@ impl<'hw> Timer<'hw> {
         })
     }
 
+    /// Updates an armed timer after the wall clock has been moved by `delta_secs` seconds.
+    /// Relative timers keep their remaining duration, while wall-clock timers keep their expiration time and are
+    /// rescheduled against the new clock (expiring immediately if the clock moved past them).
+    pub fn handle_clock_change(
+        &self,
+        clock_state: &Mutex<GlobalRawMutex, RefCell<ClockState<'hw>>>,
+        delta_secs: i64,
+    ) -> Result<(), DatetimeClockError> {
+        self.timer_state.lock(|timer_state| {
+            let mut timer_state = timer_state.borrow_mut();
+            if timer_state.wake_state != WakeState::Armed {
+                return Ok(());
+            }
+
+            let Some(mut expiration_time) = timer_state.persistent_storage.get_expiration_time() else {
+                return Ok(());
+            };
+
+            if timer_state.relative {
+                expiration_time =
+                    Datetime::from_unix_timestamp(expiration_time.unix_timestamp().saturating_add_signed(delta_secs));
+                timer_state
+                    .persistent_storage
+                    .set_expiration_time(Some(expiration_time));
+            }
+
+            // A wall-clock timer can end up further out than a u32 wait after the clock moves backwards.  Waiting
+            // the maximum instead is fine, the timer is rescheduled if it wakes before its expiration time.
+            let remaining_secs = expiration_time
+                .unix_timestamp()
+                .saturating_sub(Self::now(clock_state)?.unix_timestamp());
+            self.timer_signal
+                .signal(Some(u32::try_from(remaining_secs).unwrap_or(u32::MAX)));
+            Ok(())
+        })
+    }
+
+    /// Moves the expiration time of an armed timer by `delta_secs` seconds, reading and writing it under a single lock.
+    /// The timer is disabled if the new expiration time is not in the future.  Timers that aren't armed are left untouched.
+    pub fn adjust_expiration_time(
+        &self,
+        clock_state: &Mutex<GlobalRawMutex, RefCell<ClockState<'hw>>>,
+        delta_secs: i64,
+    ) -> Result<(), DatetimeClockError> {
+        self.timer_state.lock(|timer_state| {
+            let mut timer_state = timer_state.borrow_mut();
+            if timer_state.wake_state != WakeState::Armed {
+                return Ok(());
+            }
+
+            let Some(expiration_time) = timer_state.persistent_storage.get_expiration_time() else {
+                return Ok(());
+            };
+
+            // Per ACPI 6.4 section 9.18.1: "The status of wake timers can be reset by setting the wake alarm".
+            timer_state.timer_status = Default::default();
+
+            let now = Self::now(clock_state)?.unix_timestamp();
+            let new_expiration_time = expiration_time.unix_timestamp().saturating_add_signed(delta_secs);
+            if new_expiration_time <= now {
+                self.clear_expiration_time(&mut timer_state);
+                return Ok(());
+            }
+
+            timer_state
+                .persistent_storage
+                .set_expiration_time(Some(Datetime::from_unix_timestamp(new_expiration_time)));
+            self.timer_signal
+                .signal(Some(u32::try_from(new_expiration_time - now).unwrap_or(u32::MAX)));
+            Ok(())
+        })
+    }
+
     pub fn get_expiration_time(&self) -> Option<Datetime> {
         self.timer_state
             .lock(|timer_state| timer_state.borrow().persistent_storage.get_expiration_time())
@@ -336,7 +450,10 @@ impl<'hw> Timer<'hw> {
                                 // Time hasn't actually passed the mark yet - this can happen if we were reprogrammed with a different time right as the old timer was expiring. Reset the timer.
                                 timer_state.wake_state = WakeState::Armed;
                                 self.timer_signal.signal(Some(
-                                    expiration_time.unix_timestamp().saturating_sub(now.unix_timestamp()) as u32,
+                                    u32::try_from(
+                                        expiration_time.unix_timestamp().saturating_sub(now.unix_timestamp()),
+                                    )
+                                    .unwrap_or(u32::MAX),
                                 ));
                                 return false;
                             }
@@ -384,6 +501,7 @@ impl<'hw> Timer<'hw> {
     fn clear_expiration_time(&self, timer_state: &mut TimerState) {
         timer_state.persistent_storage.set_expiration_time(None);
         timer_state.wake_state = WakeState::Clear;
+        timer_state.relative = false;
         self.timer_signal.signal(None);
     }
 
@@ -5,35 +5,88 @@
 #[cfg(test)]
 mod test {
     use embassy_time::Timer;
-    use embedded_mcu_hal::time::{Datetime, DatetimeClock};
+    use embedded_mcu_hal::time::{Datetime, DatetimeClock, DatetimeFields, Month};
+    use embedded_services::comms;
     use odp_service_common::runnable_service::ServiceRunner;
 
-    use time_alarm_service_interface::{AcpiDaylightSavingsTimeStatus, AcpiTimeZone, AcpiTimestamp, TimeAlarmService};
+    use time_alarm_service_interface::{
+        AcpiDaylightSavingsTimeStatus, AcpiTimeZone, AcpiTimerId, AcpiTimestamp, AlarmExpiredWakePolicy,
+        AlarmTimerSeconds, PowerSourceChanged, TimeAlarmService,
+    };
 
     use time_alarm_service::mock::*;
+    use time_alarm_service::{AppTimerId, Resources, Runner, Service, TimerStorage, TimerValueMode};
+
+    /// Time that [`paused_clock`] starts at.
+    const TEST_UNIX_TIME: u64 = 1_234_567_890;
+
+    /// Clock and NVRAM storage backing a service under test, with all timers initially disabled.
+    struct Fixture<'hw, C, const N: usize = 0> {
+        resources: Resources<'hw, N>,
+        clock: C,
+        tz_storage: MockNvramStorage<'hw>,
+        ac_exp_storage: MockNvramStorage<'hw>,
+        ac_pol_storage: MockNvramStorage<'hw>,
+        dc_exp_storage: MockNvramStorage<'hw>,
+        dc_pol_storage: MockNvramStorage<'hw>,
+        app_timer_storage: [(MockNvramStorage<'hw>, MockNvramStorage<'hw>); N],
+    }
+
+    impl<'hw, C: DatetimeClock + 'hw> Fixture<'hw, C> {
+        fn new(clock: C) -> Self {
+            Self::with_app_timers(clock)
+        }
+    }
+
+    impl<'hw, C: DatetimeClock + 'hw, const N: usize> Fixture<'hw, C, N> {
+        fn with_app_timers(clock: C) -> Self {
+            Self {
+                resources: Default::default(),
+                clock,
+                tz_storage: MockNvramStorage::new(0),
+                ac_exp_storage: MockNvramStorage::new(AlarmTimerSeconds::DISABLED.0),
+                ac_pol_storage: MockNvramStorage::new(0),
+                dc_exp_storage: MockNvramStorage::new(AlarmTimerSeconds::DISABLED.0),
+                dc_pol_storage: MockNvramStorage::new(0),
+                app_timer_storage: core::array::from_fn(|_| {
+                    (
+                        MockNvramStorage::new(AlarmTimerSeconds::DISABLED.0),
+                        MockNvramStorage::new(0),
+                    )
+                }),
+            }
+        }
+
+        /// Creates the service, borrowing the fixture for the rest of the test.
+        async fn start(&'hw mut self) -> (Service<'hw, N>, Runner<'hw, N>) {
+            Service::new_with_app_timers(
+                &mut self.resources,
+                &mut self.clock,
+                &mut self.tz_storage,
+                &mut self.ac_exp_storage,
+                &mut self.ac_pol_storage,
+                &mut self.dc_exp_storage,
+                &mut self.dc_pol_storage,
+                self.app_timer_storage
+                    .each_mut()
+                    .map(|(expiration, policy)| TimerStorage { expiration, policy }),
+            )
+            .await
+            .unwrap()
+        }
+    }
+
+    /// A paused clock set to [`TEST_UNIX_TIME`].
+    fn paused_clock() -> MockDatetimeClock {
+        let mut clock = MockDatetimeClock::new_paused();
+        clock.set(Datetime::from_unix_timestamp(TEST_UNIX_TIME)).unwrap();
+        clock
+    }
 
     #[tokio::test]
     async fn test_get_time() {
-        let mut tz_storage = MockNvramStorage::new(0);
-        let mut ac_exp_storage = MockNvramStorage::new(0);
-        let mut ac_pol_storage = MockNvramStorage::new(0);
-        let mut dc_exp_storage = MockNvramStorage::new(0);
-        let mut dc_pol_storage = MockNvramStorage::new(0);
-
-        let mut clock = MockDatetimeClock::new_running();
-        let mut storage = Default::default();
-
-        let (service, runner) = time_alarm_service::Service::new(
-            &mut storage,
-            &mut clock,
-            &mut tz_storage,
-            &mut ac_exp_storage,
-            &mut ac_pol_storage,
-            &mut dc_exp_storage,
-            &mut dc_pol_storage,
-        )
-        .await
-        .unwrap();
+        let mut fixture = Fixture::new(MockDatetimeClock::new_running());
+        let (service, runner) = fixture.start().await;
 
         // We need to have the service have non-static lifetime for our test use cases so we can have
         // multiple test cases.  This means we can't spawn tasks that require 'static lifetime.
@@ -59,29 +112,8 @@ mod test {
 
     #[tokio::test]
     async fn test_set_time() {
-        let mut tz_storage = MockNvramStorage::new(0);
-        let mut ac_exp_storage = MockNvramStorage::new(0);
-        let mut ac_pol_storage = MockNvramStorage::new(0);
-        let mut dc_exp_storage = MockNvramStorage::new(0);
-        let mut dc_pol_storage = MockNvramStorage::new(0);
-
-        let mut clock = MockDatetimeClock::new_paused();
-        const TEST_UNIX_TIME: u64 = 1_234_567_890;
-        clock.set(Datetime::from_unix_timestamp(TEST_UNIX_TIME)).unwrap();
-
-        let mut storage = Default::default();
-
-        let (service, runner) = time_alarm_service::Service::new(
-            &mut storage,
-            &mut clock,
-            &mut tz_storage,
-            &mut ac_exp_storage,
-            &mut ac_pol_storage,
-            &mut dc_exp_storage,
-            &mut dc_pol_storage,
-        )
-        .await
-        .unwrap();
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
 
         tokio::select! {
             _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
@@ -103,4 +135,427 @@ mod test {
             } => {}
         }
     }
+
+    #[tokio::test]
+    async fn test_relative_alarm_survives_clock_change() {
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 3600;
+                const CLOCK_DELTA_SECS: u64 = 1000;
+
+                service
+                    .set_timer_value_with_mode(
+                        AcpiTimerId::AcPower,
+                        AlarmTimerSeconds(TIMER_SECS),
+                        TimerValueMode::Relative,
+                    )
+                    .unwrap();
+                service
+                    .set_timer_value(AcpiTimerId::DcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+
+                service
+                    .set_real_time(AcpiTimestamp {
+                        datetime: Datetime::from_unix_timestamp(TEST_UNIX_TIME + CLOCK_DELTA_SECS),
+                        time_zone: AcpiTimeZone::Unknown,
+                        dst_status: AcpiDaylightSavingsTimeStatus::NotObserved,
+                    })
+                    .unwrap();
+
+                // The relative timer keeps its remaining duration, the wall-clock timer keeps its expiration time.
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::AcPower).unwrap(),
+                    AlarmTimerSeconds(TIMER_SECS)
+                );
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::DcPower).unwrap(),
+                    AlarmTimerSeconds(TIMER_SECS - CLOCK_DELTA_SECS as u32)
+                );
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wall_clock_alarm_expires_when_clock_moves_past_it() {
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 10;
+                const CLOCK_DELTA_SECS: u64 = 100;
+
+                service
+                    .set_timer_value_with_mode(
+                        AcpiTimerId::AcPower,
+                        AlarmTimerSeconds(TIMER_SECS),
+                        TimerValueMode::Relative,
+                    )
+                    .unwrap();
+                service
+                    .set_timer_value(AcpiTimerId::DcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+
+                service
+                    .set_real_time(AcpiTimestamp {
+                        datetime: Datetime::from_unix_timestamp(TEST_UNIX_TIME + CLOCK_DELTA_SECS),
+                        time_zone: AcpiTimeZone::Unknown,
+                        dst_status: AcpiDaylightSavingsTimeStatus::NotObserved,
+                    })
+                    .unwrap();
+
+                // Give the service a chance to process the rescheduled timers
+                Timer::after(embassy_time::Duration::from_millis(500)).await;
+
+                // The clock moved past the wall-clock timer so it should have expired immediately
+                assert!(service.get_wake_status(AcpiTimerId::DcPower).timer_expired());
+
+                // The relative timer still has its full duration remaining
+                assert!(!service.get_wake_status(AcpiTimerId::AcPower).timer_expired());
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::AcPower).unwrap(),
+                    AlarmTimerSeconds(TIMER_SECS)
+                );
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_adjust_timer_value() {
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 100;
+
+                service
+                    .set_timer_value(AcpiTimerId::AcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+
+                // Extend the armed timer
+                service.adjust_timer_value(AcpiTimerId::AcPower, 30).unwrap();
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::AcPower).unwrap(),
+                    AlarmTimerSeconds(TIMER_SECS + 30)
+                );
+
+                // Shorten it again
+                service.adjust_timer_value(AcpiTimerId::AcPower, -50).unwrap();
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::AcPower).unwrap(),
+                    AlarmTimerSeconds(TIMER_SECS - 20)
+                );
+
+                // Shortening to before the current time disables the timer without expiring it
+                service.adjust_timer_value(AcpiTimerId::AcPower, -1000).unwrap();
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::AcPower).unwrap(),
+                    AlarmTimerSeconds::DISABLED
+                );
+
+                Timer::after(embassy_time::Duration::from_millis(500)).await;
+                assert!(!service.get_wake_status(AcpiTimerId::AcPower).timer_expired());
+
+                // Adjusting a disabled timer leaves it disabled
+                service.adjust_timer_value(AcpiTimerId::DcPower, 30).unwrap();
+                assert_eq!(
+                    service.get_timer_value(AcpiTimerId::DcPower).unwrap(),
+                    AlarmTimerSeconds::DISABLED
+                );
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clear_all_timers() {
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 10;
+                const CLOCK_DELTA_SECS: u64 = 100;
+
+                for timer_id in [AcpiTimerId::AcPower, AcpiTimerId::DcPower] {
+                    service
+                        .set_timer_value(timer_id, AlarmTimerSeconds(TIMER_SECS))
+                        .unwrap();
+                    service
+                        .set_expired_timer_policy(timer_id, AlarmExpiredWakePolicy::INSTANTLY)
+                        .unwrap();
+                }
+
+                // Clearing twice should be the same as clearing once, both values are read back from NVRAM
+                service.clear_all_timers().unwrap();
+                service.clear_all_timers().unwrap();
+
+                for timer_id in [AcpiTimerId::AcPower, AcpiTimerId::DcPower] {
+                    assert_eq!(service.get_timer_value(timer_id).unwrap(), AlarmTimerSeconds::DISABLED);
+                    assert_eq!(
+                        service.get_expired_timer_policy(timer_id),
+                        AlarmExpiredWakePolicy::default()
+                    );
+                }
+
+                // Move the clock past the original expiration time, neither timer should fire
+                service
+                    .set_real_time(AcpiTimestamp {
+                        datetime: Datetime::from_unix_timestamp(TEST_UNIX_TIME + CLOCK_DELTA_SECS),
+                        time_zone: AcpiTimeZone::Unknown,
+                        dst_status: AcpiDaylightSavingsTimeStatus::NotObserved,
+                    })
+                    .unwrap();
+                Timer::after(embassy_time::Duration::from_millis(500)).await;
+
+                for timer_id in [AcpiTimerId::AcPower, AcpiTimerId::DcPower] {
+                    assert!(!service.get_wake_status(timer_id).timer_expired());
+                }
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_expired_timer_policy() {
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 10;
+                const DEFAULT_POLICY: AlarmExpiredWakePolicy = AlarmExpiredWakePolicy(60);
+
+                service.set_default_expired_timer_policy(Some(DEFAULT_POLICY));
+
+                // Arming a disabled timer picks up the default policy
+                assert_eq!(
+                    service.get_expired_timer_policy(AcpiTimerId::AcPower),
+                    AlarmExpiredWakePolicy::INSTANTLY
+                );
+                service
+                    .set_timer_value(AcpiTimerId::AcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+                assert_eq!(service.get_expired_timer_policy(AcpiTimerId::AcPower), DEFAULT_POLICY);
+
+                // A policy explicitly set by the host takes precedence over the default
+                service
+                    .set_expired_timer_policy(AcpiTimerId::DcPower, AlarmExpiredWakePolicy::INSTANTLY)
+                    .unwrap();
+                service
+                    .set_timer_value(AcpiTimerId::DcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+                assert_eq!(
+                    service.get_expired_timer_policy(AcpiTimerId::DcPower),
+                    AlarmExpiredWakePolicy::INSTANTLY
+                );
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_timer_expiry() {
+        let mut fixture = Fixture::new(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 10;
+
+                // The system starts on AC power, so the AC timer is the active one
+                service
+                    .set_timer_value(AcpiTimerId::AcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+
+                // The mock clock is paused, so move it past the expiration time to expire the timer
+                service
+                    .set_real_time(AcpiTimestamp {
+                        datetime: Datetime::from_unix_timestamp(TEST_UNIX_TIME + u64::from(TIMER_SECS)),
+                        time_zone: AcpiTimeZone::Unknown,
+                        dst_status: AcpiDaylightSavingsTimeStatus::NotObserved,
+                    })
+                    .unwrap();
+
+                let timer_id = embassy_time::with_timeout(
+                    embassy_time::Duration::from_secs(1),
+                    service.wait_timer_expiry(),
+                )
+                .await
+                .expect("Timer expiry was not signaled");
+                assert_eq!(timer_id, AcpiTimerId::AcPower);
+                assert!(service.get_wake_status(AcpiTimerId::AcPower).timer_triggered_wake());
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_power_source_changed_message() {
+        // Registering the endpoint requires the service to live for the rest of the program
+        let fixture = Box::leak(Box::new(Fixture::new(paused_clock())));
+        let (service, runner) = fixture.start().await;
+
+        embedded_services::init().await;
+        service.register_endpoint().await.unwrap();
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 10;
+
+                // Switch to battery power over comms so the DC timer is the active one
+                comms::send(
+                    comms::EndpointID::Internal(comms::Internal::Power),
+                    comms::EndpointID::Internal(comms::Internal::TimeAlarm),
+                    &PowerSourceChanged(AcpiTimerId::DcPower),
+                )
+                .await
+                .unwrap();
+
+                let power_source = embassy_time::with_timeout(
+                    embassy_time::Duration::from_secs(1),
+                    service.wait_power_source_change(),
+                )
+                .await
+                .expect("Power source change was not signaled");
+                assert_eq!(power_source, AcpiTimerId::DcPower);
+
+                service
+                    .set_timer_value(AcpiTimerId::DcPower, AlarmTimerSeconds(TIMER_SECS))
+                    .unwrap();
+
+                // The mock clock is paused, so move it past the expiration time to expire the timer
+                service
+                    .set_real_time(AcpiTimestamp {
+                        datetime: Datetime::from_unix_timestamp(TEST_UNIX_TIME + u64::from(TIMER_SECS)),
+                        time_zone: AcpiTimeZone::Unknown,
+                        dst_status: AcpiDaylightSavingsTimeStatus::NotObserved,
+                    })
+                    .unwrap();
+
+                let timer_id = embassy_time::with_timeout(
+                    embassy_time::Duration::from_secs(1),
+                    service.wait_timer_expiry(),
+                )
+                .await
+                .expect("Timer expiry was not signaled");
+                assert_eq!(timer_id, AcpiTimerId::DcPower);
+                assert!(service.get_wake_status(AcpiTimerId::DcPower).timer_triggered_wake());
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_app_timer_expiry() {
+        let mut fixture = Fixture::<_, 1>::with_app_timers(paused_clock());
+        let (service, runner) = fixture.start().await;
+
+        assert!(service.app_timer(AppTimerId(1)).is_none());
+        let app_timer = service.app_timer(AppTimerId(0)).unwrap();
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                const TIMER_SECS: u32 = 10;
+
+                app_timer
+                    .set_timer_value(AlarmTimerSeconds(TIMER_SECS), TimerValueMode::WallClock)
+                    .unwrap();
+                assert_eq!(app_timer.get_timer_value().unwrap(), AlarmTimerSeconds(TIMER_SECS));
+
+                // The mock clock is paused, so move it past the expiration time to expire the timer
+                service
+                    .set_real_time(AcpiTimestamp {
+                        datetime: Datetime::from_unix_timestamp(TEST_UNIX_TIME + u64::from(TIMER_SECS)),
+                        time_zone: AcpiTimeZone::Unknown,
+                        dst_status: AcpiDaylightSavingsTimeStatus::NotObserved,
+                    })
+                    .unwrap();
+
+                let timer_id = embassy_time::with_timeout(
+                    embassy_time::Duration::from_secs(1),
+                    service.wait_app_timer_expiry(),
+                )
+                .await
+                .expect("App timer expiry was not signaled");
+                assert_eq!(timer_id, AppTimerId(0));
+                assert!(app_timer.get_wake_status().timer_triggered_wake());
+
+                // The ACPI timers are independent and were never armed
+                for timer_id in [AcpiTimerId::AcPower, AcpiTimerId::DcPower] {
+                    assert!(!service.get_wake_status(timer_id).timer_expired());
+                }
+                assert!(
+                    embassy_time::with_timeout(embassy_time::Duration::from_millis(100), service.wait_timer_expiry())
+                        .await
+                        .is_err()
+                );
+
+                app_timer.clear_wake_status();
+                assert!(!app_timer.get_wake_status().timer_triggered_wake());
+            } => {}
+        }
+    }
+
+    /// A time with a non-zero sub-second part, used to check millisecond reporting.
+    fn fractional_datetime() -> Datetime {
+        Datetime::new(DatetimeFields {
+            year: 2024,
+            month: Month::January,
+            day: 10,
+            hour: 12,
+            minute: 0,
+            second: 0,
+            nanosecond: 123_456_789,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_millisecond_real_time() {
+        let mut fixture = Fixture::new(MockMillisecondDatetimeClock::new(fractional_datetime()));
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                assert!(service.get_capabilities().realtime_accuracy_in_milliseconds());
+
+                let timestamp = service.get_real_time().unwrap();
+                assert_eq!(timestamp.datetime, fractional_datetime());
+
+                // Milliseconds are stored little-endian at offset 8 of the ACPI timestamp
+                let bytes = timestamp.as_bytes();
+                assert_eq!(u16::from_le_bytes([bytes[8], bytes[9]]), 123);
+            } => {}
+        }
+    }
+
+    #[tokio::test]
+    async fn test_second_real_time_fallback() {
+        // This clock only has second resolution, so any sub-second part it reports should be dropped
+        let mut clock = MockDatetimeClock::new_paused();
+        clock.set(fractional_datetime()).unwrap();
+
+        let mut fixture = Fixture::new(clock);
+        let (service, runner) = fixture.start().await;
+
+        tokio::select! {
+            _ = runner.run() => unreachable!("time alarm service task finished unexpectedly"),
+            _ = async {
+                assert!(!service.get_capabilities().realtime_accuracy_in_milliseconds());
+
+                let timestamp = service.get_real_time().unwrap();
+                assert_eq!(timestamp.datetime.unix_timestamp(), fractional_datetime().unix_timestamp());
+                assert_eq!(timestamp.datetime.nanoseconds(), 0);
+            } => {}
+        }
+    }
 }
@@ -9,6 +9,7 @@ use type_c_interface::control::{
     pd::PortStatus,
     vdm::{AttnVdm, OtherVdm},
 };
+use type_c_interface::controller::Controller;
 
 pub mod max_sink_voltage;
 pub mod pd;
@@ -19,6 +20,7 @@ pub enum FnCall {
     Pd(pd::FnCall),
     Ucsi(ucsi::FnCall),
     MaxSinkVoltage(max_sink_voltage::FnCall),
+    ResetController,
 }
 
 /// Mock PD controller for use in tests
@@ -26,6 +28,8 @@ pub struct Mock {
     name: &'static str,
     /// Recorded function calls
     pub fn_calls: VecDeque<FnCall>,
+    /// Next results to return for [`Controller::reset_controller`]
+    pub next_result_reset_controller: VecDeque<Result<(), PdError>>,
     /// Next results to return for [`type_c_interface::controller::pd::Pd::get_port_status`]
     pub next_result_get_port_status: VecDeque<Result<PortStatus, PdError>>,
     /// Next results to return for [`type_c_interface::controller::pd::Pd::clear_dead_battery_flag`]
@@ -75,6 +79,7 @@ impl Mock {
         Self {
             fn_calls: VecDeque::new(),
             name,
+            next_result_reset_controller: VecDeque::new(),
             next_result_get_port_status: VecDeque::new(),
             next_result_clear_dead_battery_flag: VecDeque::new(),
             next_result_enable_sink_path: VecDeque::new(),
@@ -103,3 +108,12 @@ impl Named for Mock {
         self.name
     }
 }
+
+impl Controller for Mock {
+    async fn reset_controller(&mut self) -> Result<(), PdError> {
+        self.fn_calls.push_back(FnCall::ResetController);
+        self.next_result_reset_controller
+            .pop_front()
+            .expect("next_result_reset_controller not set")
+    }
+}
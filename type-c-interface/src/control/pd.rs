@@ -34,6 +34,16 @@ pub struct PortStatus {
     pub unconstrained_power: bool,
 }
 
+/// Power capabilities a port can currently sink and source
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RoleCapabilities {
+    /// Capability available as a consumer, if any
+    pub sink: Option<power_policy_interface::capability::ConsumerPowerCapability>,
+    /// Capability available as a provider, if any
+    pub source: Option<power_policy_interface::capability::ProviderPowerCapability>,
+}
+
 impl PortStatus {
     /// Create a new blank port status
     /// Needed because default() is not const
@@ -0,0 +1,209 @@
+//! Facade over two controller types, allowing different PD controllers to be stored and driven together.
+//!
+//! The controller traits return `impl Future` and so can't be used as trait objects. Instead, a board with two
+//! different PD controllers can wrap both in [`EitherController`] to get a single controller type, e.g. for a slice
+//! of controllers processed by one task loop. More than two controller types can be supported by nesting.
+use core::num::NonZeroU8;
+
+use embedded_services::named::Named;
+use embedded_usb_pd::vdm::structured::command::discover_identity::{sop, sop_prime};
+use embedded_usb_pd::{LocalPortId, PdError, ado::Ado, ucsi::lpm};
+
+use crate::control::{
+    dp::{DpConfig, DpStatus},
+    pd::{PdStateMachineConfig, PortStatus},
+    power::SystemPowerState,
+    retimer::RetimerFwUpdateState,
+    svid::DiscoveredSvids,
+    tbt::TbtConfig,
+    type_c::TypeCStateMachineState,
+    usb::UsbControlConfig,
+    vdm::{AttnVdm, OtherVdm, SendVdm},
+};
+use crate::controller::electrical_disconnect::ElectricalDisconnect;
+use crate::controller::max_sink_voltage::MaxSinkVoltage;
+use crate::controller::pd::{self, Pd};
+use crate::controller::power::SystemPowerStateStatus;
+use crate::controller::retimer::Retimer;
+use crate::controller::{Controller, type_c};
+use crate::ucsi::Lpm;
+
+/// One of two controller types
+///
+/// Each controller trait is implemented if both controller types implement it, by forwarding to the contained
+/// controller.
+pub enum EitherController<A, B> {
+    /// First controller type
+    First(A),
+    /// Second controller type
+    Second(B),
+}
+
+/// Forwards a call to the contained controller
+macro_rules! forward {
+    ($self:ident, $controller:ident => $call:expr) => {
+        match $self {
+            EitherController::First($controller) => $call,
+            EitherController::Second($controller) => $call,
+        }
+    };
+}
+
+impl<A: Named, B: Named> Named for EitherController<A, B> {
+    fn name(&self) -> &'static str {
+        forward!(self, controller => controller.name())
+    }
+}
+
+impl<A: Controller, B: Controller> Controller for EitherController<A, B> {
+    async fn reset_controller(&mut self) -> Result<(), PdError> {
+        forward!(self, controller => controller.reset_controller().await)
+    }
+}
+
+impl<A: Pd, B: Pd> Pd for EitherController<A, B> {
+    async fn get_port_status(&mut self, port: LocalPortId) -> Result<PortStatus, PdError> {
+        forward!(self, controller => controller.get_port_status(port).await)
+    }
+
+    async fn clear_dead_battery_flag(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.clear_dead_battery_flag(port).await)
+    }
+
+    async fn enable_sink_path(&mut self, port: LocalPortId, enable: bool) -> Result<(), PdError> {
+        forward!(self, controller => controller.enable_sink_path(port, enable).await)
+    }
+
+    async fn get_pd_alert(&mut self, port: LocalPortId) -> Result<Option<Ado>, PdError> {
+        forward!(self, controller => controller.get_pd_alert(port).await)
+    }
+
+    async fn set_unconstrained_power(&mut self, port: LocalPortId, unconstrained: bool) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_unconstrained_power(port, unconstrained).await)
+    }
+
+    async fn get_other_vdm(&mut self, port: LocalPortId) -> Result<OtherVdm, PdError> {
+        forward!(self, controller => controller.get_other_vdm(port).await)
+    }
+
+    async fn get_attn_vdm(&mut self, port: LocalPortId) -> Result<AttnVdm, PdError> {
+        forward!(self, controller => controller.get_attn_vdm(port).await)
+    }
+
+    async fn send_vdm(&mut self, port: LocalPortId, tx_vdm: SendVdm) -> Result<(), PdError> {
+        forward!(self, controller => controller.send_vdm(port, tx_vdm).await)
+    }
+
+    async fn execute_drst(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.execute_drst(port).await)
+    }
+
+    async fn hard_reset(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.hard_reset(port).await)
+    }
+
+    async fn get_dp_status(&mut self, port: LocalPortId) -> Result<DpStatus, PdError> {
+        forward!(self, controller => controller.get_dp_status(port).await)
+    }
+
+    async fn set_dp_config(&mut self, port: LocalPortId, config: DpConfig) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_dp_config(port, config).await)
+    }
+
+    async fn set_tbt_config(&mut self, port: LocalPortId, config: TbtConfig) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_tbt_config(port, config).await)
+    }
+
+    async fn set_usb_control(&mut self, port: LocalPortId, config: UsbControlConfig) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_usb_control(port, config).await)
+    }
+
+    async fn get_discovered_svids(&mut self, port: LocalPortId) -> Result<DiscoveredSvids, PdError> {
+        forward!(self, controller => controller.get_discovered_svids(port).await)
+    }
+
+    async fn get_discover_identity_sop_response(&mut self, port: LocalPortId) -> Result<sop::ResponseVdos, PdError> {
+        forward!(self, controller => controller.get_discover_identity_sop_response(port).await)
+    }
+
+    async fn get_discover_identity_sop_prime_response(
+        &mut self,
+        port: LocalPortId,
+    ) -> Result<sop_prime::ResponseVdos, PdError> {
+        forward!(self, controller => controller.get_discover_identity_sop_prime_response(port).await)
+    }
+}
+
+impl<A: pd::StateMachine, B: pd::StateMachine> pd::StateMachine for EitherController<A, B> {
+    async fn set_pd_state_machine_config(
+        &mut self,
+        port: LocalPortId,
+        config: PdStateMachineConfig,
+    ) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_pd_state_machine_config(port, config).await)
+    }
+}
+
+impl<A: type_c::StateMachine, B: type_c::StateMachine> type_c::StateMachine for EitherController<A, B> {
+    async fn set_type_c_state_machine_config(
+        &mut self,
+        port: LocalPortId,
+        state: TypeCStateMachineState,
+    ) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_type_c_state_machine_config(port, state).await)
+    }
+}
+
+impl<A: ElectricalDisconnect, B: ElectricalDisconnect> ElectricalDisconnect for EitherController<A, B> {
+    async fn execute_electrical_disconnect(
+        &mut self,
+        port: LocalPortId,
+        reconnect_time_s: Option<NonZeroU8>,
+    ) -> Result<(), PdError> {
+        forward!(self, controller => controller.execute_electrical_disconnect(port, reconnect_time_s).await)
+    }
+}
+
+impl<A: MaxSinkVoltage, B: MaxSinkVoltage> MaxSinkVoltage for EitherController<A, B> {
+    async fn set_max_sink_voltage(&mut self, port: LocalPortId, voltage_mv: Option<u16>) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_max_sink_voltage(port, voltage_mv).await)
+    }
+}
+
+impl<A: SystemPowerStateStatus, B: SystemPowerStateStatus> SystemPowerStateStatus for EitherController<A, B> {
+    async fn set_system_power_state_status(
+        &mut self,
+        port: LocalPortId,
+        state: SystemPowerState,
+    ) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_system_power_state_status(port, state).await)
+    }
+}
+
+impl<A: Retimer, B: Retimer> Retimer for EitherController<A, B> {
+    async fn get_rt_fw_update_status(&mut self, port: LocalPortId) -> Result<RetimerFwUpdateState, PdError> {
+        forward!(self, controller => controller.get_rt_fw_update_status(port).await)
+    }
+
+    async fn set_rt_fw_update_state(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_rt_fw_update_state(port).await)
+    }
+
+    async fn clear_rt_fw_update_state(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.clear_rt_fw_update_state(port).await)
+    }
+
+    async fn set_rt_compliance(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.set_rt_compliance(port).await)
+    }
+
+    async fn reconfigure_retimer(&mut self, port: LocalPortId) -> Result<(), PdError> {
+        forward!(self, controller => controller.reconfigure_retimer(port).await)
+    }
+}
+
+impl<A: Lpm, B: Lpm> Lpm for EitherController<A, B> {
+    async fn execute_lpm_command(&mut self, command: lpm::LocalCommand) -> Result<Option<lpm::ResponseData>, PdError> {
+        forward!(self, controller => controller.execute_lpm_command(command).await)
+    }
+}
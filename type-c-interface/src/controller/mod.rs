@@ -3,6 +3,7 @@
 use embedded_services::named::Named;
 use embedded_usb_pd::PdError;
 
+pub mod either;
 pub mod electrical_disconnect;
 pub mod max_sink_voltage;
 pub mod pd;
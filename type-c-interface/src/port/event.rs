@@ -33,6 +33,8 @@ bitfield! {
     pub u8, alt_mode_entered, set_alt_mode_entered: 7, 7;
     /// PD hard reset
     pub u8, pd_hard_reset, set_pd_hard_reset: 8, 8;
+    /// DP alt-mode entered or exited
+    pub u8, dp_alt_mode_changed, set_dp_alt_mode_changed: 9, 9;
 }
 
 /// Port status change events
@@ -143,6 +145,16 @@ impl PortStatusEventBitfield {
     pub fn set_pd_hard_reset(&mut self, value: bool) {
         self.0.set_pd_hard_reset(value.into());
     }
+
+    /// Returns true if DP alt-mode was entered or exited
+    pub fn dp_alt_mode_changed(self) -> bool {
+        self.0.dp_alt_mode_changed() != 0
+    }
+
+    /// Sets the DP alt-mode changed event
+    pub fn set_dp_alt_mode_changed(&mut self, value: bool) {
+        self.0.set_dp_alt_mode_changed(value.into());
+    }
 }
 
 bitfield! {
@@ -7,10 +7,10 @@ use power_policy_interface::psu::Error as PowerPolicyError;
 pub fn power_capability_try_from_contract(
     contract: Contract,
 ) -> Option<power_policy_interface::capability::PowerCapability> {
-    Some(power_policy_interface::capability::PowerCapability {
-        voltage_mv: contract.pdo.max_voltage_mv(),
-        current_ma: contract.operating_current_ma()?,
-    })
+    power_policy_interface::capability::PowerCapability::new(
+        contract.pdo.max_voltage_mv(),
+        contract.operating_current_ma()?,
+    )
 }
 
 pub fn power_capability_from_current(current: type_c::Current) -> power_policy_interface::capability::PowerCapability {
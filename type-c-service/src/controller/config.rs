@@ -1,10 +1,33 @@
+use embassy_time::Duration;
+
 /// Configuration for Type-C controller wrapper
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[non_exhaustive]
 pub struct Config {
     /// Unconstrained behavior for sink role
     pub unconstrained_sink: UnconstrainedSink,
+    /// Serve UCSI `GET_CONNECTOR_STATUS` from the last controller response until a port event is processed
+    ///
+    /// Avoids a controller transaction for each connector status read from the host while the port is idle.
+    pub cache_ucsi_connector_status: bool,
+    /// Power roles this port is allowed to take on with the power policy
+    pub power_role_policy: PowerRolePolicy,
+    /// Time to wait for a sink ready event after a new consumer contract before treating the sink as ready
+    ///
+    /// If [`None`], twice the spec maximum `tPSTransition` for the contract is used.
+    pub sink_ready_timeout: Option<Duration>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            unconstrained_sink: UnconstrainedSink::default(),
+            cache_ucsi_connector_status: false,
+            power_role_policy: PowerRolePolicy::default(),
+            sink_ready_timeout: None,
+        }
+    }
 }
 
 /// Unconstrained behavior for sink role
@@ -20,3 +43,17 @@ pub enum UnconstrainedSink {
     /// Never signal unconstrained power
     Never,
 }
+
+/// Power roles a port is allowed to take on with the power policy
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum PowerRolePolicy {
+    /// Port only provides power, sink contracts are never reported to the power policy
+    SourceOnly,
+    /// Port only consumes power, source contracts are never reported to the power policy
+    SinkOnly,
+    /// Port can either provide or consume power
+    #[default]
+    DualRole,
+}
@@ -9,6 +9,8 @@ use type_c_interface::port::event::PortEventBitfield;
 pub enum Event {
     /// Port event
     PortEvent(type_c_interface::port::event::PortEvent),
+    /// The controller was reset and the port state must be re-synchronized
+    ControllerReset,
 }
 
 /// Loopback event to allow `sync_state` and similar functions
@@ -1,7 +1,8 @@
 //! This module contains event receiver types for the controller wrapper.
 use core::array;
 use core::future::pending;
-use embassy_futures::select::{Either, select};
+use embassy_futures::select::{Either, Either3, select, select3};
+use embassy_sync::watch;
 use embassy_time::Timer;
 use embedded_services::error;
 use embedded_services::event::{NonBlockingSender, Receiver};
@@ -9,6 +10,7 @@ use embedded_services::sync::Lockable;
 
 use crate::PortEventStreamer;
 use crate::controller::event::{Event, Loopback};
+use crate::controller::recovery::ControllerRecovery;
 use crate::controller::state::SharedState;
 use type_c_interface::port::event::{PortEvent, PortEventBitfield, PortStatusEventBitfield};
 
@@ -48,6 +50,8 @@ pub struct PortEventReceiver<R: Receiver<PortEventBitfield>, LoopbackReceiver: R
     streaming_state: Option<PortEventStreamer<array::IntoIter<PortEventBitfield, 1>>>,
     /// Loopback receiver for software-generated events
     loopback_receiver: LoopbackReceiver,
+    /// True if the current streaming state came from the loopback receiver
+    streaming_loopback: bool,
 }
 
 impl<R: Receiver<PortEventBitfield>, LoopbackReceiver: Receiver<Loopback>> PortEventReceiver<R, LoopbackReceiver> {
@@ -57,6 +61,16 @@ impl<R: Receiver<PortEventBitfield>, LoopbackReceiver: Receiver<Loopback>> PortE
             receiver,
             streaming_state: None,
             loopback_receiver,
+            streaming_loopback: false,
+        }
+    }
+
+    /// Discard any events remaining from the last controller interrupt
+    ///
+    /// Software-generated loopback events are kept.
+    pub fn discard_controller_events(&mut self) {
+        if !self.streaming_loopback {
+            self.streaming_state = None;
         }
     }
 
@@ -68,8 +82,16 @@ impl<R: Receiver<PortEventBitfield>, LoopbackReceiver: Receiver<Loopback>> PortE
                 embassy_futures::yield_now().await;
                 streaming_state
             } else {
-                let (Either::First(Loopback::PortEvent(events)) | Either::Second(events)) =
-                    select(self.loopback_receiver.wait_next(), self.receiver.wait_next()).await;
+                let events = match select(self.loopback_receiver.wait_next(), self.receiver.wait_next()).await {
+                    Either::First(Loopback::PortEvent(events)) => {
+                        self.streaming_loopback = true;
+                        events
+                    }
+                    Either::Second(events) => {
+                        self.streaming_loopback = false;
+                        events
+                    }
+                };
                 self.streaming_state
                     .insert(PortEventStreamer::new([events].into_iter()))
             };
@@ -94,6 +116,10 @@ pub struct EventReceiver<
     port_event_receiver: PortEventReceiver<InterruptReceiver, LoopbackReceiver>,
     /// Shared state
     shared_state: &'a State,
+    /// Last observed [`SharedState::sync_generation`]
+    sync_generation: u8,
+    /// Controller reset notifications, if controller recovery is registered
+    reset_receiver: Option<watch::DynReceiver<'a, ()>>,
 }
 
 impl<
@@ -112,6 +138,18 @@ impl<
         Self {
             shared_state,
             port_event_receiver: PortEventReceiver::new(port_event_receiver, loopback_receiver),
+            sync_generation: 0,
+            reset_receiver: None,
+        }
+    }
+
+    /// Register the recovery state of the controller this port belongs to
+    ///
+    /// [`Event::ControllerReset`] is returned each time the controller is reset.
+    pub fn register_recovery<const N: usize>(&mut self, recovery: &'a ControllerRecovery<N>) {
+        self.reset_receiver = recovery.reset_receiver();
+        if self.reset_receiver.is_none() {
+            error!("No controller reset receivers available");
         }
     }
 
@@ -119,18 +157,40 @@ impl<
     ///
     /// Returns the local port ID and the event bitfield.
     pub async fn wait_event(&mut self) -> Event {
-        let timeout = self.shared_state.lock().await.sink_ready_timeout;
-        match select(self.port_event_receiver.wait_next(), async move {
-            if let Some(timeout) = timeout {
-                Timer::at(timeout).await;
-            } else {
-                pending::<()>().await;
-            }
-        })
+        let (timeout, sync_generation) = {
+            let state = self.shared_state.lock().await;
+            (state.sink_ready_timeout, state.sync_generation)
+        };
+
+        if sync_generation != self.sync_generation {
+            // The port was synced, remaining controller events no longer reflect the controller state
+            self.sync_generation = sync_generation;
+            self.port_event_receiver.discard_controller_events();
+        }
+
+        let reset_receiver = &mut self.reset_receiver;
+        match select3(
+            async move {
+                if let Some(reset_receiver) = reset_receiver {
+                    reset_receiver.changed().await;
+                } else {
+                    pending::<()>().await;
+                }
+            },
+            self.port_event_receiver.wait_next(),
+            async move {
+                if let Some(timeout) = timeout {
+                    Timer::at(timeout).await;
+                } else {
+                    pending::<()>().await;
+                }
+            },
+        )
         .await
         {
-            Either::First(event) => Event::PortEvent(event),
-            Either::Second(_) => {
+            Either3::First(_) => Event::ControllerReset,
+            Either3::Second(event) => Event::PortEvent(event),
+            Either3::Third(_) => {
                 let mut status_event = PortStatusEventBitfield::none();
                 status_event.set_sink_ready(true);
                 self.shared_state.lock().await.sink_ready_timeout = None;
@@ -1,7 +1,8 @@
 //! Struct that manages per-port state, interfacing with a controller object that exposes multiple ports.
 use embedded_services::{debug, error, event::NonBlockingSender, info, named::Named, sync::Lockable};
-use embedded_usb_pd::{LocalPortId, PdError};
+use embedded_usb_pd::{LocalPortId, PdError, ucsi::lpm};
 use power_policy_interface::psu::PsuState;
+use type_c_interface::control::dp::DpStatus;
 use type_c_interface::control::pd::PortStatus;
 use type_c_interface::controller::pd::Pd;
 use type_c_interface::port::event::PortEventBitfield;
@@ -19,7 +20,9 @@ pub mod macros;
 pub mod max_sink_voltage;
 mod pd;
 mod power;
+pub mod recovery;
 pub mod retimer;
+pub mod snapshot;
 pub mod state;
 pub mod type_c;
 pub mod ucsi;
@@ -42,6 +45,10 @@ pub struct Port<
     name: &'static str,
     /// Cached port status
     status: PortStatus,
+    /// Cached DP status
+    dp_status: DpStatus,
+    /// Cached UCSI connector status response for the connector of this port, cleared whenever a port event is processed
+    ucsi_connector_status: Option<lpm::ResponseData>,
     /// Sender for type-c service events
     type_c_sender: TypeCSender,
     /// Sender for power policy events
@@ -52,6 +59,8 @@ pub struct Port<
     shared_state: &'device Shared,
     /// Loopback sender
     loopback_sender: LoopbackSender,
+    /// Recovery state of the controller, shared with the other ports on the controller
+    recovery: Option<recovery::RecoveryHandle<'device>>,
 }
 
 impl<
@@ -81,19 +90,28 @@ impl<
             controller,
             port,
             status: PortStatus::default(),
+            dp_status: DpStatus::default(),
+            ucsi_connector_status: None,
             psu_state: power_policy_interface::psu::State::default(),
             power_policy_sender,
             config,
             shared_state,
             loopback_sender,
             type_c_sender,
+            recovery: None,
         }
     }
 
     /// Top-level processing function
     pub async fn process_event(&mut self, event: Event) -> Result<Option<ServicePortEventData>, PdError> {
+        // Any event can change the connector status
+        self.ucsi_connector_status = None;
         match event {
             Event::PortEvent(port_event) => self.process_port_event(port_event).await,
+            Event::ControllerReset => {
+                info!("({}): Controller reset, re-synchronizing port state", self.name);
+                self.sync_state().await.map(|_| None)
+            }
         }
     }
 
@@ -186,6 +204,7 @@ impl<
         } else {
             info!("Plug removed");
             self.psu_state.detach();
+            self.dp_status = DpStatus::default();
             if self
                 .power_policy_sender
                 .try_send(power_policy_interface::psu::event::EventData::Detached)
@@ -204,7 +223,19 @@ impl<
     }
 
     /// Synchronize the state between the controller and the internal state
+    ///
+    /// Cached state and pending events from before the sync are discarded. Controller events that the event
+    /// receiver has not yet returned are dropped, the events generated here describe the current status instead.
     pub async fn sync_state(&mut self) -> Result<(), PdError> {
+        self.ucsi_connector_status = None;
+        // Alt-mode changes are detected against this, the next DP status update reports the current state
+        self.dp_status = DpStatus::default();
+        {
+            let mut shared_state = self.shared_state.lock().await;
+            shared_state.sync_generation = shared_state.sync_generation.wrapping_add(1);
+            shared_state.sink_ready_timeout = None;
+        }
+
         let status = self.controller.lock().await.get_port_status(self.port).await?;
 
         let mut event = PortEventBitfield::none();
@@ -55,9 +55,26 @@ impl<
     }
 
     /// Process a DisplayPort status update by retrieving the current DP status from the `controller` for the appropriate `port`.
+    ///
+    /// If DP alt-mode was entered or exited since the last update, a status event with
+    /// [`dp_alt_mode_changed`](PortStatusEventBitfield::dp_alt_mode_changed) set is pended through the loopback channel.
     pub(super) async fn process_dp_status_update(&mut self) -> Result<ServicePortEventData, PdError> {
         debug!("({}): Processing DP status update event", self.name);
         let status = self.controller.lock().await.get_dp_status(self.port).await?;
+        if status.alt_mode_entered != self.dp_status.alt_mode_entered {
+            info!(
+                "({}): DP alt-mode {}",
+                self.name,
+                if status.alt_mode_entered { "entered" } else { "exited" }
+            );
+            let mut port_event = PortEventBitfield::none();
+            port_event.status.set_dp_alt_mode_changed(true);
+            if self.loopback_sender.try_send(Loopback::PortEvent(port_event)).is_none() {
+                error!("Failed to send loopback event");
+            }
+        }
+        self.dp_status = status;
+
         let event = ServicePortEventData::DpStatusUpdate(status);
         if self.type_c_sender.try_send(event).is_none() {
             error!("Failed to send DP status update type-C event");
@@ -9,9 +9,10 @@ use power_policy_interface::{
     capability::{ConsumerDisconnect, ConsumerPowerCapability, ProviderPowerCapability, PsuType},
     psu::{Error as PsuError, Psu, State},
 };
+use type_c_interface::control::pd::RoleCapabilities;
 use type_c_interface::controller::power::SystemPowerStateStatus;
 
-use crate::controller::config::UnconstrainedSink;
+use crate::controller::config::{PowerRolePolicy, UnconstrainedSink};
 use type_c_interface::util::power_policy_error_from_pd_error;
 
 use super::*;
@@ -28,18 +29,12 @@ impl<
     /// Handle a new contract as consumer
     pub(super) async fn process_new_consumer_contract(&mut self, new_status: &PortStatus) -> Result<(), PdError> {
         info!("Process new consumer contract");
-        let available_sink_contract = new_status.available_sink_contract.map(|c| {
-            let mut c: ConsumerPowerCapability = c.into();
-            let unconstrained = match self.config.unconstrained_sink {
-                UnconstrainedSink::Auto => new_status.unconstrained_power,
-                UnconstrainedSink::PowerThresholdMilliwatts(threshold) => c.capability.max_power_mw() >= threshold,
-                UnconstrainedSink::Never => false,
-            };
-            c.flags.set_unconstrained_power(unconstrained);
-            c.flags.set_psu_type(PsuType::TypeC);
-            c
-        });
+        if self.config.power_role_policy == PowerRolePolicy::SourceOnly {
+            info!("({}): Source-only port, ignoring consumer contract", self.name);
+            return Ok(());
+        }
 
+        let available_sink_contract = self.consumer_capability(new_status);
         if let Err(e) = self.psu_state.update_consumer_power_capability(available_sink_contract) {
             error!("Failed to update consumer power capability: {:?}", e);
             return Err(PdError::Failed);
@@ -54,14 +49,54 @@ impl<
         Ok(())
     }
 
-    /// Handle a new contract as provider
-    pub(super) async fn process_new_provider_contract(&mut self, new_status: &PortStatus) -> Result<(), PdError> {
-        info!("Process New provider contract");
-        let capability = new_status.available_source_contract.map(|caps| {
+    /// Consumer capability for the sink contract in `status`
+    fn consumer_capability(&self, status: &PortStatus) -> Option<ConsumerPowerCapability> {
+        status.available_sink_contract.map(|c| {
+            let mut c: ConsumerPowerCapability = c.into();
+            let unconstrained = match self.config.unconstrained_sink {
+                UnconstrainedSink::Auto => status.unconstrained_power,
+                UnconstrainedSink::PowerThresholdMilliwatts(threshold) => c.capability.max_power_mw() >= threshold,
+                UnconstrainedSink::Never => false,
+            };
+            c.flags.set_unconstrained_power(unconstrained);
+            c.flags.set_psu_type(PsuType::TypeC);
+            c
+        })
+    }
+
+    /// Provider capability for the source contract in `status`
+    fn provider_capability(status: &PortStatus) -> Option<ProviderPowerCapability> {
+        status.available_source_contract.map(|caps| {
             let mut caps = ProviderPowerCapability::from(caps);
             caps.flags.set_psu_type(PsuType::TypeC);
             caps
-        });
+        })
+    }
+
+    /// Returns what the port can currently sink and source
+    ///
+    /// The status is read from the controller and converted the same way as contracts reported to the power policy.
+    /// A role not permitted by the [`PowerRolePolicy`] is reported as `None`.
+    pub async fn get_role_capabilities(&self) -> Result<RoleCapabilities, PdError> {
+        let status = self.controller.lock().await.get_port_status(self.port).await?;
+        Ok(RoleCapabilities {
+            sink: self
+                .consumer_capability(&status)
+                .filter(|_| self.config.power_role_policy != PowerRolePolicy::SourceOnly),
+            source: Self::provider_capability(&status)
+                .filter(|_| self.config.power_role_policy != PowerRolePolicy::SinkOnly),
+        })
+    }
+
+    /// Handle a new contract as provider
+    pub(super) async fn process_new_provider_contract(&mut self, new_status: &PortStatus) -> Result<(), PdError> {
+        info!("Process New provider contract");
+        if self.config.power_role_policy == PowerRolePolicy::SinkOnly {
+            info!("({}): Sink-only port, ignoring provider contract", self.name);
+            return Ok(());
+        }
+
+        let capability = Self::provider_capability(new_status);
         if let Err(e) = self.psu_state.update_requested_provider_power_capability(capability) {
             error!("Failed to update requested provider power capability: {:?}", e);
             return Err(PdError::Failed);
@@ -146,17 +181,24 @@ impl<
         );
         if new_contract && !sink_ready && contract_changed {
             // Start the timeout
-            // Double the spec maximum transition time to provide a safety margin for hardware/controller delays or out-of-spec controllers.
-            let timeout_ms = if new_status.epr {
-                T_PS_TRANSITION_EPR_MS
-            } else {
-                T_PS_TRANSITION_SPR_MS
-            }
-            .maximum
-            .0 * 2;
-
-            debug!("({}): Sink ready timeout started for {}ms", self.name, timeout_ms);
-            *timeout = Some(Instant::now() + Duration::from_millis(timeout_ms as u64));
+            let duration = self.config.sink_ready_timeout.unwrap_or_else(|| {
+                // Double the spec maximum transition time to provide a safety margin for hardware/controller delays or out-of-spec controllers.
+                let timeout_ms = if new_status.epr {
+                    T_PS_TRANSITION_EPR_MS
+                } else {
+                    T_PS_TRANSITION_SPR_MS
+                }
+                .maximum
+                .0 * 2;
+                Duration::from_millis(timeout_ms as u64)
+            });
+
+            debug!(
+                "({}): Sink ready timeout started for {}ms",
+                self.name,
+                duration.as_millis()
+            );
+            *timeout = Some(Instant::now() + duration);
         } else if timeout.is_some()
             && (!new_status.is_connected() || new_status.available_sink_contract.is_none() || sink_ready)
         {
@@ -202,6 +244,7 @@ impl<
             "({}): Connect as consumer: {:?}, enable input switch",
             self.name, capability
         );
+        self.psu_state.can_connect_consumer(capability)?;
         self.controller
             .lock()
             .await
@@ -0,0 +1,208 @@
+//! Recovery logic for a controller that has stopped responding
+//!
+//! A reset affects every port on a controller, so errors are counted across all of its ports in a shared
+//! [`ControllerRecovery`]. Once the controller has been reset, the event receiver of each port returns
+//! [`Event::ControllerReset`] and the port re-synchronizes its state.
+use embassy_sync::mutex::Mutex;
+use embassy_sync::watch::{self, Watch};
+use embassy_time::{Duration, Instant, Timer};
+use embedded_services::{GlobalRawMutex, event::NonBlockingSender, sync::Lockable};
+use embedded_usb_pd::PdError;
+use type_c_interface::controller::Controller;
+
+use super::*;
+use crate::controller::state::SharedState;
+
+/// Default number of consecutive controller errors before the controller is reset
+pub const DEFAULT_RESET_ERROR_THRESHOLD: u8 = 3;
+
+/// Default base delay between controller reset attempts
+pub const DEFAULT_RESET_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Maximum shift applied to the reset backoff, caps the delay at 128x the base backoff
+const MAX_BACKOFF_SHIFT: u8 = 7;
+
+/// Controller recovery configuration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct Config {
+    /// Number of consecutive errors, across all ports on the controller, before the controller is reset
+    pub reset_error_threshold: u8,
+    /// Base delay between controller reset attempts, doubled after each failed attempt
+    pub reset_backoff: Duration,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            reset_error_threshold: DEFAULT_RESET_ERROR_THRESHOLD,
+            reset_backoff: DEFAULT_RESET_BACKOFF,
+        }
+    }
+}
+
+/// Error counts for a controller
+struct State {
+    /// Number of consecutive controller errors
+    consecutive_errors: u8,
+    /// Number of consecutive controller reset attempts
+    reset_attempts: u8,
+}
+
+/// Recovery state shared by all ports on a controller
+///
+/// `N` is the number of event receivers that can be notified of a reset, usually the number of ports on the
+/// controller.
+pub struct ControllerRecovery<const N: usize> {
+    /// Configuration
+    config: Config,
+    /// Error counts
+    state: Mutex<GlobalRawMutex, State>,
+    /// Held by the port waiting to reset the controller or resetting it
+    resetting: Mutex<GlobalRawMutex, ()>,
+    /// Notifies the event receivers of each port when the controller has been reset
+    reset: Watch<GlobalRawMutex, (), N>,
+}
+
+impl<const N: usize> ControllerRecovery<N> {
+    /// Create a new instance
+    pub const fn new(config: Config) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State {
+                consecutive_errors: 0,
+                reset_attempts: 0,
+            }),
+            resetting: Mutex::new(()),
+            reset: Watch::new(),
+        }
+    }
+
+    /// Returns a receiver notified of controller resets, [`None`] if all `N` receivers have been taken
+    pub(crate) fn reset_receiver(&self) -> Option<watch::DynReceiver<'_, ()>> {
+        self.reset.dyn_receiver()
+    }
+
+    /// Returns a handle for a port on this controller
+    fn handle(&self) -> RecoveryHandle<'_> {
+        RecoveryHandle {
+            config: self.config,
+            state: &self.state,
+            resetting: &self.resetting,
+            reset_sender: self.reset.dyn_sender(),
+        }
+    }
+}
+
+/// Reference to a [`ControllerRecovery`] held by a port, independent of the number of ports
+pub(crate) struct RecoveryHandle<'a> {
+    /// Configuration
+    config: Config,
+    /// Error counts
+    state: &'a Mutex<GlobalRawMutex, State>,
+    /// Held by the port waiting to reset the controller or resetting it
+    resetting: &'a Mutex<GlobalRawMutex, ()>,
+    /// Reset notification sender
+    reset_sender: watch::DynSender<'a, ()>,
+}
+
+impl<
+    'device,
+    C: Lockable<Inner: Pd + Controller>,
+    Shared: Lockable<Inner = SharedState>,
+    TypeCSender: NonBlockingSender<type_c_interface::service::event::PortEventData>,
+    PowerSender: NonBlockingSender<power_policy_interface::psu::event::EventData>,
+    LoopbackSender: NonBlockingSender<event::Loopback>,
+> Port<'device, C, Shared, TypeCSender, PowerSender, LoopbackSender>
+{
+    /// Register the recovery state of the controller this port belongs to
+    ///
+    /// Until registered, [`Self::process_event_with_recovery`] behaves like [`Self::process_event`].
+    pub fn register_recovery<const N: usize>(&mut self, recovery: &'device ControllerRecovery<N>) {
+        self.recovery = Some(recovery.handle());
+    }
+
+    /// Process an event, resetting the controller if it repeatedly fails
+    ///
+    /// Behaves like [`Self::process_event`], but counts consecutive errors across all ports on the controller. Once
+    /// [`reset_error_threshold`](Config::reset_error_threshold) consecutive errors have occurred, the controller is
+    /// reset and every port on it is notified through [`Event::ControllerReset`].
+    pub async fn process_event_with_recovery(&mut self, event: Event) -> Result<Option<ServicePortEventData>, PdError> {
+        let result = self.process_event(event).await;
+        self.track_controller_result(&result).await;
+        result
+    }
+
+    /// Synchronize the port state, resetting the controller if it repeatedly fails
+    pub async fn sync_state_with_recovery(&mut self) -> Result<(), PdError> {
+        let result = self.sync_state().await;
+        self.track_controller_result(&result).await;
+        result
+    }
+
+    /// Update the consecutive error count and reset the controller if the threshold has been reached
+    ///
+    /// Consecutive reset attempts are spaced out by [`reset_backoff`](Config::reset_backoff), doubling after each
+    /// attempt that doesn't recover the controller. Only one port resets the controller at a time, the others keep
+    /// processing events in the meantime.
+    async fn track_controller_result<T>(&mut self, result: &Result<T, PdError>) {
+        let Some(recovery) = &self.recovery else {
+            return;
+        };
+
+        let mut state = recovery.state.lock().await;
+        let Err(e) = result else {
+            state.consecutive_errors = 0;
+            state.reset_attempts = 0;
+            return;
+        };
+
+        state.consecutive_errors = state.consecutive_errors.saturating_add(1);
+        debug!(
+            "({}): Controller error {:?}, consecutive errors: {}",
+            self.name, e, state.consecutive_errors
+        );
+
+        if state.consecutive_errors < recovery.config.reset_error_threshold {
+            return;
+        }
+
+        // Another port is already resetting the controller
+        let Ok(_resetting) = recovery.resetting.try_lock() else {
+            return;
+        };
+
+        let backoff = if state.reset_attempts > 0 {
+            let shift = (state.reset_attempts - 1).min(MAX_BACKOFF_SHIFT);
+            recovery
+                .config
+                .reset_backoff
+                .checked_mul(1u32 << shift)
+                .unwrap_or(Duration::MAX)
+        } else {
+            Duration::from_ticks(0)
+        };
+        state.reset_attempts = state.reset_attempts.saturating_add(1);
+        let attempt = state.reset_attempts;
+        // Not held while waiting so other ports on the controller aren't blocked
+        drop(state);
+
+        if backoff > Duration::from_ticks(0) {
+            debug!("({}): Waiting {:?} before controller reset", self.name, backoff);
+            Timer::at(Instant::now().checked_add(backoff).unwrap_or(Instant::MAX)).await;
+        }
+
+        info!("({}): Resetting controller, attempt {}", self.name, attempt);
+        let reset_result = self.controller.lock().await.reset_controller().await;
+
+        let mut state = recovery.state.lock().await;
+        match reset_result {
+            Ok(()) => {
+                state.consecutive_errors = 0;
+                recovery.reset_sender.send(());
+            }
+            Err(e) => error!("({}): Controller reset failed: {:?}", self.name, e),
+        }
+    }
+}
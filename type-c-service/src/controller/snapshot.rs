@@ -0,0 +1,90 @@
+//! Controller-wide port status snapshots
+use embedded_services::{debug, named::Named, sync::Lockable};
+use embedded_usb_pd::{LocalPortId, PdError, PowerRole};
+use heapless::Vec;
+use type_c_interface::control::pd::PortStatus;
+use type_c_interface::controller::pd::Pd;
+
+/// Returns the status of every port on the controller, indexed by local port ID
+///
+/// `PORT_COUNT` must match the number of ports exposed by the controller. The controller lock is held for the
+/// whole scan so no port can process an event and change controller state between individual queries.
+pub async fn get_all_port_status<C: Lockable<Inner: Pd>, const PORT_COUNT: usize>(
+    controller: &C,
+) -> Result<Vec<PortStatus, PORT_COUNT>, PdError> {
+    query_all_port_status(&mut *controller.lock().await).await
+}
+
+/// Queries the status of every port on an already locked controller
+async fn query_all_port_status<C: Pd, const PORT_COUNT: usize>(
+    controller: &mut C,
+) -> Result<Vec<PortStatus, PORT_COUNT>, PdError> {
+    let mut statuses = Vec::new();
+    for port in 0..PORT_COUNT {
+        let status = controller.get_port_status(LocalPortId(port as u8)).await?;
+        debug!("({}) port {} status: {:#?}", controller.name(), port, status);
+        // Push will never fail since we push at most PORT_COUNT entries
+        let _ = statuses.push(status);
+    }
+    Ok(statuses)
+}
+
+/// Compact summary of a single port's state
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PortSummary {
+    /// Whether a partner is connected
+    pub connected: bool,
+    /// Current power role
+    pub power_role: PowerRole,
+    /// Power of the contract in the current power role in mW, `None` if there is no contract
+    pub contract_mw: Option<u32>,
+}
+
+impl From<&PortStatus> for PortSummary {
+    fn from(status: &PortStatus) -> Self {
+        let contract = match status.power_role {
+            PowerRole::Sink => status.available_sink_contract,
+            PowerRole::Source => status.available_source_contract,
+        };
+
+        Self {
+            connected: status.is_connected(),
+            power_role: status.power_role,
+            contract_mw: contract.filter(|_| status.is_connected()).map(|c| c.max_power_mw()),
+        }
+    }
+}
+
+/// Status summary of a controller and its ports
+///
+/// The controller itself is only described by its name, the [`Controller`](type_c_interface::controller::Controller)
+/// trait exposes no controller-wide status to report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ControllerSummary<const PORT_COUNT: usize> {
+    /// Name of the controller
+    pub name: &'static str,
+    /// Summary of each port, indexed by local port ID
+    pub ports: Vec<PortSummary, PORT_COUNT>,
+}
+
+impl<const PORT_COUNT: usize> ControllerSummary<PORT_COUNT> {
+    /// Returns the number of ports with a connected partner
+    pub fn connected_ports(&self) -> usize {
+        self.ports.iter().filter(|port| port.connected).count()
+    }
+}
+
+/// Returns a summary of the controller and every port on it
+///
+/// Like [`get_all_port_status`], the controller lock is held for the whole scan so the summary is consistent.
+pub async fn get_controller_summary<C: Lockable<Inner: Pd>, const PORT_COUNT: usize>(
+    controller: &C,
+) -> Result<ControllerSummary<PORT_COUNT>, PdError> {
+    let mut controller = controller.lock().await;
+    let statuses: Vec<PortStatus, PORT_COUNT> = query_all_port_status(&mut *controller).await?;
+    Ok(ControllerSummary {
+        name: controller.name(),
+        ports: statuses.iter().map(PortSummary::from).collect(),
+    })
+}
@@ -5,6 +5,8 @@ use embassy_time::Instant;
 pub struct SharedState {
     /// Sink ready timeout
     pub(crate) sink_ready_timeout: Option<Instant>,
+    /// Incremented each time the port state is synced, used to discard controller events from before the sync
+    pub(crate) sync_generation: u8,
 }
 
 impl SharedState {
@@ -12,6 +14,7 @@ impl SharedState {
     pub fn new() -> Self {
         Self {
             sink_ready_timeout: None,
+            sync_generation: 0,
         }
     }
 
@@ -16,6 +16,21 @@ impl<
 > type_c_interface::ucsi::Lpm for Port<'device, C, Shared, TypeCSender, PowerSender, LoopbackSender>
 {
     async fn execute_lpm_command(&mut self, command: lpm::LocalCommand) -> Result<Option<lpm::ResponseData>, PdError> {
-        self.controller.lock().await.execute_lpm_command(command).await
+        let is_connector_status = matches!(command.operation(), lpm::CommandData::GetConnectorStatus);
+        // Only events on this port invalidate the cache, so only the status of this port's own connector is cached
+        let cacheable = self.config.cache_ucsi_connector_status && is_connector_status && command.port() == self.port;
+        if cacheable && let Some(response) = self.ucsi_connector_status {
+            debug!("({}): Using cached UCSI connector status", self.name);
+            return Ok(Some(response));
+        }
+
+        let response = self.controller.lock().await.execute_lpm_command(command).await;
+        if cacheable {
+            self.ucsi_connector_status = response.as_ref().ok().copied().flatten();
+        } else if !is_connector_status {
+            // Other commands may change the connector state
+            self.ucsi_connector_status = None;
+        }
+        response
     }
 }
@@ -9,6 +9,7 @@ use embedded_usb_pd::GlobalPortId;
 use embedded_usb_pd::PdError as Error;
 use power_policy_interface::service::event::EventData as PowerPolicyEventData;
 use type_c_interface::control::pd::PortStatus;
+use type_c_interface::controller::ControllerId;
 use type_c_interface::port::pd::Pd;
 use type_c_interface::service::event::{DebugAccessoryData, EventData, PortEvent, PortEventData};
 
@@ -74,6 +75,14 @@ impl<'port, Reg: Registration<'port>> Service<'port, Reg> {
             .copied()
     }
 
+    /// Iterate over the registered controllers, yielding each controller's ID and number of registered ports
+    pub fn controllers(&self) -> impl Iterator<Item = (ControllerId, usize)> + '_ {
+        self.registration
+            .controllers()
+            .iter()
+            .map(|controller| (controller.id, controller.port_count))
+    }
+
     /// Send an event to all registered listeners
     fn broadcast_event(&mut self, event: ServiceEvent<'port, Reg::Port>) {
         for sender in self.registration.event_senders() {
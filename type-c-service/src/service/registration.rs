@@ -2,6 +2,7 @@
 
 use embedded_services::{event::NonBlockingSender, sync::Lockable};
 use embedded_usb_pd::{GlobalPortId, LocalPortId};
+use type_c_interface::controller::ControllerId;
 use type_c_interface::port::pd::Pd;
 use type_c_interface::service::event::Event as ServiceEvent;
 use type_c_interface::ucsi::Lpm as UcsiLpm;
@@ -17,6 +18,8 @@ pub trait Registration<'port> {
     fn event_senders(&mut self) -> &mut [Self::ServiceSender];
     /// Returns the ucsi local port ID for a given global port
     fn ucsi_local_port_id(&self, global_port: GlobalPortId) -> Option<LocalPortId>;
+    /// Returns a slice to access registered controllers
+    fn controllers(&self) -> &[ControllerData];
 }
 
 pub struct PortData {
@@ -24,11 +27,23 @@ pub struct PortData {
     pub local_port: Option<LocalPortId>,
 }
 
+/// Data for a registered controller
+pub struct ControllerData {
+    /// Controller ID
+    pub id: ControllerId,
+    /// Number of ports on this controller
+    pub port_count: usize,
+}
+
 /// A registration implementation based around arrays
+///
+/// Ports are registered in controller order: the first `controllers[0].port_count` ports belong to the first
+/// controller, the following ports to the second controller and so on.
 pub struct ArrayRegistration<
     'port,
     Port: Lockable<Inner: Pd + UcsiLpm> + 'port,
     const PORT_COUNT: usize,
+    const CONTROLLER_COUNT: usize,
     ServiceSender: NonBlockingSender<ServiceEvent<'port, Port>>,
     const SERVICE_SENDER_COUNT: usize,
 > {
@@ -36,6 +51,8 @@ pub struct ArrayRegistration<
     pub ports: [&'port Port; PORT_COUNT],
     /// Array of local port data
     pub port_data: [PortData; PORT_COUNT],
+    /// Array of registered controllers
+    pub controllers: [ControllerData; CONTROLLER_COUNT],
     /// Array of service event senders
     pub service_senders: [ServiceSender; SERVICE_SENDER_COUNT],
 }
@@ -44,9 +61,11 @@ impl<
     'port,
     Port: Lockable<Inner: Pd + UcsiLpm> + 'port,
     const PORT_COUNT: usize,
+    const CONTROLLER_COUNT: usize,
     ServiceSender: NonBlockingSender<ServiceEvent<'port, Port>>,
     const SERVICE_SENDER_COUNT: usize,
-> Registration<'port> for ArrayRegistration<'port, Port, PORT_COUNT, ServiceSender, SERVICE_SENDER_COUNT>
+> Registration<'port>
+    for ArrayRegistration<'port, Port, PORT_COUNT, CONTROLLER_COUNT, ServiceSender, SERVICE_SENDER_COUNT>
 {
     type Port = Port;
     type ServiceSender = ServiceSender;
@@ -64,4 +83,8 @@ impl<
             .get(global_port.0 as usize)
             .and_then(|data| data.local_port)
     }
+
+    fn controllers(&self) -> &[ControllerData] {
+        &self.controllers
+    }
 }
@@ -1,3 +1,5 @@
+#![allow(clippy::unwrap_used)]
+#![allow(dead_code)]
 use std::mem::ManuallyDrop;
 
 use embassy_futures::{
@@ -11,11 +13,12 @@ use embassy_sync::{
     watch,
 };
 use embassy_time::{Duration, with_timeout};
-use embedded_services::{GlobalRawMutex, event::NonBlockingSender};
+use embedded_services::{GlobalRawMutex, event::NonBlockingSender, sync::Lockable};
 use embedded_usb_pd::LocalPortId;
 use paste::paste;
 use power_policy_interface::charger::mock::NoopCharger;
-use type_c_service::service::registration::PortData;
+use type_c_interface::controller::{ControllerId, pd::Pd};
+use type_c_service::service::registration::{ControllerData, PortData};
 
 pub const DEFAULT_TEST_DURATION: Duration = Duration::from_secs(5);
 
@@ -113,6 +116,8 @@ pub type TypeCRegistrationType<'port, 'ch> = type_c_service::service::registrati
     PortMutexType<'port, 'ch>,
     // Number of type-C ports
     TYPE_C_PORT_COUNT,
+    // Number of controllers, each port has its own controller
+    TYPE_C_PORT_COUNT,
     // Senders for events broadcast by the service
     TypeCServiceSender<'port, 'ch>,
     // Number of registered service event senders
@@ -125,6 +130,58 @@ pub type TypeCServiceMutexType<'port, 'ch> =
 /// Default channel size to use
 pub const CHANNEL_SIZE: usize = 4;
 
+/// Port over an arbitrary controller type, for tests that drive ports directly rather than through [`run_test`]
+pub type ControllerPortMutexType<'a, C> = Mutex<
+    GlobalRawMutex,
+    type_c_service::controller::Port<
+        'a,
+        C,
+        PortSharedState,
+        PortTypeCSender<'a>,
+        PortPowerSender<'a>,
+        PortLoopbackSender<'a>,
+    >,
+>;
+
+/// Channels used by a single port created with [`create_port`]
+pub struct PortChannels {
+    pub type_c: Channel<GlobalRawMutex, type_c_interface::service::event::PortEventData, CHANNEL_SIZE>,
+    pub power_policy: Channel<GlobalRawMutex, power_policy_interface::psu::event::EventData, CHANNEL_SIZE>,
+    pub loopback: Channel<GlobalRawMutex, type_c_service::controller::event::Loopback, CHANNEL_SIZE>,
+    pub interrupt: Channel<GlobalRawMutex, type_c_interface::port::event::PortEventBitfield, CHANNEL_SIZE>,
+}
+
+impl PortChannels {
+    pub fn new() -> Self {
+        Self {
+            type_c: Channel::new(),
+            power_policy: Channel::new(),
+            loopback: Channel::new(),
+            interrupt: Channel::new(),
+        }
+    }
+}
+
+/// Create a port on `controller` with the default config
+pub fn create_port<'a, C: Lockable<Inner: Pd>>(
+    name: &'static str,
+    local_port: LocalPortId,
+    controller: &'a C,
+    shared_state: &'a PortSharedState,
+    channels: &'a PortChannels,
+) -> ControllerPortMutexType<'a, C> {
+    Mutex::new(type_c_service::controller::Port::new(
+        name,
+        Default::default(),
+        local_port,
+        controller,
+        shared_state,
+        channels.type_c.dyn_sender(),
+        channels.power_policy.dyn_sender(),
+        channels.loopback.dyn_sender(),
+    ))
+}
+
 /// Struct to pass port components to a test implementation.
 pub struct TestPort<'port, 'ch> {
     /// Port logic
@@ -143,6 +200,11 @@ pub struct TestPort<'port, 'ch> {
 ///
 /// Directly taking async closures is messy and requires an intermediate trait anyway
 pub trait Test {
+    /// Configure the type-C service before the test runs
+    fn setup<'port, 'ch>(&mut self, _type_c_service: &TypeCServiceMutexType<'port, 'ch>) -> impl Future<Output = ()> {
+        async {}
+    }
+
     /// Run the test
     fn run<'port, 'ch>(
         &mut self,
@@ -353,6 +415,20 @@ pub async fn run_test(
                     local_port: Some(LocalPortId(0)),
                 },
             ],
+            controllers: [
+                ControllerData {
+                    id: ControllerId(0),
+                    port_count: 1,
+                },
+                ControllerData {
+                    id: ControllerId(1),
+                    port_count: 1,
+                },
+                ControllerData {
+                    id: ControllerId(2),
+                    port_count: 1,
+                },
+            ],
             service_senders: [type_c_service_sender],
         },
     ));
@@ -418,6 +494,7 @@ pub async fn run_test(
                 type_c_service_event_receivers,
             ),
             async {
+                test.setup(&type_c_service).await;
                 test.run(
                     type_c_service_receiver,
                     power_policy_service_receiver,
@@ -0,0 +1,90 @@
+use embassy_sync::channel::Channel;
+use embassy_sync::mutex::Mutex;
+use embedded_services::GlobalRawMutex;
+use embedded_usb_pd::LocalPortId;
+use type_c_interface::controller::ControllerId;
+use type_c_interface_test_mocks::controller::Mock;
+use type_c_service::controller::state::SharedState;
+use type_c_service::service::Service;
+use type_c_service::service::registration::{ArrayRegistration, ControllerData, PortData};
+
+mod common;
+
+use common::{CHANNEL_SIZE, ControllerMockMutexType, ControllerPortMutexType, PortChannels, create_port};
+
+/// Test that the service enumerates every registered controller along with its number of ports
+#[tokio::test]
+async fn test_controllers() {
+    let controller0 = Mutex::new(Mock::new("mock0"));
+    let controller1 = Mutex::new(Mock::new("mock1"));
+
+    // Controller 0 has two ports, controller 1 has a single port
+    let port0_shared_state = Mutex::new(SharedState::new());
+    let port0_channels = PortChannels::new();
+    let port0 = create_port(
+        "port0",
+        LocalPortId(0),
+        &controller0,
+        &port0_shared_state,
+        &port0_channels,
+    );
+
+    let port1_shared_state = Mutex::new(SharedState::new());
+    let port1_channels = PortChannels::new();
+    let port1 = create_port(
+        "port1",
+        LocalPortId(1),
+        &controller0,
+        &port1_shared_state,
+        &port1_channels,
+    );
+
+    let port2_shared_state = Mutex::new(SharedState::new());
+    let port2_channels = PortChannels::new();
+    let port2 = create_port(
+        "port2",
+        LocalPortId(0),
+        &controller1,
+        &port2_shared_state,
+        &port2_channels,
+    );
+
+    let service_channel: Channel<
+        GlobalRawMutex,
+        type_c_interface::service::event::Event<'_, ControllerPortMutexType<'_, ControllerMockMutexType>>,
+        CHANNEL_SIZE,
+    > = Channel::new();
+    let service = Service::new(
+        Default::default(),
+        ArrayRegistration {
+            ports: [&port0, &port1, &port2],
+            port_data: [
+                PortData {
+                    local_port: Some(LocalPortId(0)),
+                },
+                PortData {
+                    local_port: Some(LocalPortId(1)),
+                },
+                PortData {
+                    local_port: Some(LocalPortId(0)),
+                },
+            ],
+            controllers: [
+                ControllerData {
+                    id: ControllerId(0),
+                    port_count: 2,
+                },
+                ControllerData {
+                    id: ControllerId(1),
+                    port_count: 1,
+                },
+            ],
+            service_senders: [service_channel.dyn_sender()],
+        },
+    );
+
+    assert_eq!(
+        service.controllers().collect::<Vec<_>>(),
+        [(ControllerId(0), 2), (ControllerId(1), 1)]
+    );
+}
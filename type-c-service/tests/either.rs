@@ -0,0 +1,182 @@
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+use embassy_sync::mutex::Mutex;
+use embedded_services::GlobalRawMutex;
+use embedded_services::named::Named;
+use embedded_usb_pd::vdm::structured::command::discover_identity::{sop, sop_prime};
+use embedded_usb_pd::{LocalPortId, PdError, ado::Ado, type_c::ConnectionState};
+use power_policy_interface::psu::event::EventData;
+use type_c_interface::control::{
+    dp::{DpConfig, DpStatus},
+    pd::PortStatus,
+    svid::DiscoveredSvids,
+    tbt::TbtConfig,
+    usb::UsbControlConfig,
+    vdm::{AttnVdm, OtherVdm, SendVdm},
+};
+use type_c_interface::controller::either::EitherController;
+use type_c_interface::controller::pd::Pd;
+use type_c_interface::port::event::{PortEvent, PortStatusEventBitfield};
+use type_c_interface::service::event::PortEventData;
+use type_c_interface_test_mocks::controller::{FnCall as ControllerFnCall, Mock, pd::FnCall as PdFnCall};
+use type_c_service::controller::{event::Event, snapshot::get_all_port_status, state::SharedState};
+
+mod common;
+
+use common::{PortChannels, create_port};
+
+/// Controller type distinct from [`Mock`], reports an attached partner on every port and supports nothing else
+struct AttachedController;
+
+impl Named for AttachedController {
+    fn name(&self) -> &'static str {
+        "attached"
+    }
+}
+
+impl Pd for AttachedController {
+    async fn get_port_status(&mut self, _port: LocalPortId) -> Result<PortStatus, PdError> {
+        Ok(PortStatus {
+            connection_state: Some(ConnectionState::Attached),
+            ..Default::default()
+        })
+    }
+
+    async fn clear_dead_battery_flag(&mut self, _port: LocalPortId) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn enable_sink_path(&mut self, _port: LocalPortId, _enable: bool) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_pd_alert(&mut self, _port: LocalPortId) -> Result<Option<Ado>, PdError> {
+        Ok(None)
+    }
+
+    async fn set_unconstrained_power(&mut self, _port: LocalPortId, _unconstrained: bool) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_other_vdm(&mut self, _port: LocalPortId) -> Result<OtherVdm, PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_attn_vdm(&mut self, _port: LocalPortId) -> Result<AttnVdm, PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn send_vdm(&mut self, _port: LocalPortId, _tx_vdm: SendVdm) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn execute_drst(&mut self, _port: LocalPortId) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn hard_reset(&mut self, _port: LocalPortId) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_dp_status(&mut self, _port: LocalPortId) -> Result<DpStatus, PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn set_dp_config(&mut self, _port: LocalPortId, _config: DpConfig) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn set_tbt_config(&mut self, _port: LocalPortId, _config: TbtConfig) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn set_usb_control(&mut self, _port: LocalPortId, _config: UsbControlConfig) -> Result<(), PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_discovered_svids(&mut self, _port: LocalPortId) -> Result<DiscoveredSvids, PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_discover_identity_sop_response(&mut self, _port: LocalPortId) -> Result<sop::ResponseVdos, PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+
+    async fn get_discover_identity_sop_prime_response(
+        &mut self,
+        _port: LocalPortId,
+    ) -> Result<sop_prime::ResponseVdos, PdError> {
+        Err(PdError::UnrecognizedCommand)
+    }
+}
+
+type ControllerType = Mutex<GlobalRawMutex, EitherController<Mock, AttachedController>>;
+
+/// Test that two distinct controller types stored together are each driven by the same loop and port logic
+#[tokio::test]
+async fn test_mixed_controllers() {
+    let mut mock = Mock::new("mock0");
+    mock.next_result_get_pd_alert.push_back(Ok(None));
+    mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+    mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+    let controllers: [ControllerType; 2] = [
+        Mutex::new(EitherController::First(mock)),
+        Mutex::new(EitherController::Second(AttachedController)),
+    ];
+
+    let mut connected = [false; 2];
+    for (controller, is_connected) in controllers.iter().zip(connected.iter_mut()) {
+        let alert = controller.lock().await.get_pd_alert(LocalPortId(0)).await.unwrap();
+        assert!(alert.is_none());
+        let statuses = get_all_port_status::<_, 1>(controller).await.unwrap();
+        *is_connected = statuses.iter().any(PortStatus::is_connected);
+    }
+    assert_eq!(connected, [false, true]);
+
+    let [first, second] = &controllers;
+    assert_eq!(second.lock().await.name(), "attached");
+
+    // A port on each controller processes a plug event
+    let port0_shared_state = Mutex::new(SharedState::new());
+    let port0_channels = PortChannels::new();
+    let port0 = create_port("port0", LocalPortId(0), first, &port0_shared_state, &port0_channels);
+    let port1_shared_state = Mutex::new(SharedState::new());
+    let port1_channels = PortChannels::new();
+    let port1 = create_port("port1", LocalPortId(0), second, &port1_shared_state, &port1_channels);
+
+    let mut status_event = PortStatusEventBitfield::none();
+    status_event.set_plug_inserted_or_removed(true);
+    for (port, channels, expected_connected, expected_event) in [
+        (&port0, &port0_channels, false, EventData::Detached),
+        (&port1, &port1_channels, true, EventData::Attached),
+    ] {
+        let event = port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(status_event)))
+            .await
+            .unwrap();
+        let Some(PortEventData::StatusChanged(data)) = event else {
+            panic!("Expected status changed event, got {event:?}");
+        };
+        assert_eq!(data.current_status.is_connected(), expected_connected);
+        assert_eq!(channels.power_policy.try_receive().unwrap(), expected_event);
+    }
+
+    let mut controller = first.lock().await;
+    assert_eq!(controller.name(), "mock0");
+    let EitherController::First(mock) = &mut *controller else {
+        panic!("Expected mock controller");
+    };
+    assert!(matches!(
+        mock.fn_calls.pop_front().unwrap(),
+        ControllerFnCall::Pd(PdFnCall::GetPdAlert(LocalPortId(0)))
+    ));
+    for _ in 0..2 {
+        assert!(matches!(
+            mock.fn_calls.pop_front().unwrap(),
+            ControllerFnCall::Pd(PdFnCall::GetPortStatus(LocalPortId(0)))
+        ));
+    }
+    assert!(mock.fn_calls.is_empty());
+}
@@ -319,6 +319,162 @@ impl Test for TestDpStatus {
     }
 }
 
+/// Test DP alt-mode change detection.
+///
+/// A DP status update that transitions from no DP to DP alt-mode should pend a single
+/// status-changed event with the `dp_alt_mode_changed` bit set. Repeating the same status must not
+/// pend another event.
+struct TestDpAltModeChanged;
+
+impl Test for TestDpAltModeChanged {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let mut event_receiver = port0.event_receiver;
+        let dp_4_lane = DpStatus {
+            alt_mode_entered: true,
+            dfp_d_pin_cfg: DpPinConfig {
+                pin_c: true,
+                pin_d: false,
+                pin_e: false,
+            },
+        };
+
+        {
+            let mut mock0 = port0.mock.lock().await;
+            mock0.next_result_get_dp_status.push_back(Ok(dp_4_lane));
+            mock0.next_result_get_dp_status.push_back(Ok(dp_4_lane));
+        }
+
+        // Transition from no DP to DP 4-lane
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::DpStatusUpdate))
+            .await
+            .unwrap();
+
+        match with_timeout(DEFAULT_PER_CALL_TIMEOUT, event_receiver.wait_event()).await {
+            Ok(Event::PortEvent(PortEvent::StatusChanged(status_event))) => {
+                assert!(
+                    status_event.dp_alt_mode_changed(),
+                    "DP alt-mode changed flag should be set"
+                );
+            }
+            other => panic!("Expected PortEvent::StatusChanged, got {other:?}"),
+        }
+
+        // Same status again, nothing should be pended
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::DpStatusUpdate))
+            .await
+            .unwrap();
+
+        assert!(
+            with_timeout(DEFAULT_PER_CALL_TIMEOUT, event_receiver.wait_event())
+                .await
+                .is_err(),
+            "unexpected DP alt-mode changed event"
+        );
+
+        {
+            let mut mock0 = port0.mock.lock().await;
+            for _ in 0..2 {
+                assert!(matches!(
+                    mock0.fn_calls.pop_front(),
+                    Some(ControllerFnCall::Pd(PdFnCall::GetDpStatus(_)))
+                ));
+            }
+            assert!(mock0.fn_calls.is_empty());
+        }
+    }
+}
+
+/// Test DP alt-mode change detection after the port state is synchronized.
+///
+/// Synchronizing, e.g. after a controller reset, discards the cached DP status. Entering DP alt-mode again afterwards
+/// must pend a status-changed event rather than being compared against the status from before the sync.
+struct TestDpAltModeChangedAfterSync;
+
+impl Test for TestDpAltModeChangedAfterSync {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let mut event_receiver = port0.event_receiver;
+        let dp_4_lane = DpStatus {
+            alt_mode_entered: true,
+            dfp_d_pin_cfg: DpPinConfig {
+                pin_c: true,
+                pin_d: false,
+                pin_e: false,
+            },
+        };
+
+        {
+            let mut mock0 = port0.mock.lock().await;
+            mock0.next_result_get_dp_status.push_back(Ok(dp_4_lane));
+            mock0.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+            mock0.next_result_get_dp_status.push_back(Ok(dp_4_lane));
+        }
+
+        for sync in [false, true] {
+            if sync {
+                // The port status is unchanged, so the sync itself doesn't pend anything
+                port0.port.lock().await.sync_state().await.unwrap();
+            }
+
+            port0
+                .port
+                .lock()
+                .await
+                .process_event(Event::PortEvent(PortEvent::DpStatusUpdate))
+                .await
+                .unwrap();
+
+            match with_timeout(DEFAULT_PER_CALL_TIMEOUT, event_receiver.wait_event()).await {
+                Ok(Event::PortEvent(PortEvent::StatusChanged(status_event))) => {
+                    assert!(
+                        status_event.dp_alt_mode_changed(),
+                        "DP alt-mode changed flag should be set"
+                    );
+                }
+                other => panic!("Expected PortEvent::StatusChanged, got {other:?}"),
+            }
+        }
+
+        {
+            let mut mock0 = port0.mock.lock().await;
+            assert!(matches!(
+                mock0.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetDpStatus(_)))
+            ));
+            assert!(matches!(
+                mock0.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(_)))
+            ));
+            assert!(matches!(
+                mock0.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetDpStatus(_)))
+            ));
+            assert!(mock0.fn_calls.is_empty());
+        }
+    }
+}
+
 /// Test the PD hard reset flow.
 ///
 /// A hard reset arrives as a status-changed event with the `pd_hard_reset` bit set. The port
@@ -409,6 +565,28 @@ async fn test_dp_status() {
     .await;
 }
 
+#[tokio::test]
+async fn test_dp_alt_mode_changed() {
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        Default::default(),
+        TestDpAltModeChanged,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_dp_alt_mode_changed_after_sync() {
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        Default::default(),
+        TestDpAltModeChangedAfterSync,
+    )
+    .await;
+}
+
 #[tokio::test]
 async fn test_hard_reset() {
     common::run_test(
@@ -13,15 +13,18 @@ use power_policy_interface::{
     service::event::Event as PowerPolicyEvent,
 };
 use type_c_interface::{
-    control::pd::PortStatus,
+    control::pd::{PortStatus, RoleCapabilities},
     port::event::{PortEvent, PortEventBitfield, PortStatusEventBitfield},
     port::max_sink_voltage::MaxSinkVoltage,
-    util::POWER_CAPABILITY_5V_1A5,
+    util::{POWER_CAPABILITY_5V_1A5, POWER_CAPABILITY_5V_3A0},
 };
 use type_c_interface_test_mocks::controller::{
     FnCall as ControllerFnCall, max_sink_voltage::FnCall as MaxSinkVoltageFnCall, pd::FnCall as PdFnCall,
 };
-use type_c_service::controller::event::Event;
+use type_c_service::controller::{
+    config::{Config, PowerRolePolicy},
+    event::Event,
+};
 
 use crate::common::{
     DEFAULT_PER_CALL_TIMEOUT, DEFAULT_TEST_DURATION, PowerPolicyServiceReceiver, Test, TestPort, TypeCServiceReceiver,
@@ -241,7 +244,7 @@ impl Test for TestBasicProviderFlow {
         assert_eq!(type_c_result.err(), Some(TimeoutError));
         // Power policy service should broadcast a provider disconnect event
         match power_policy_result {
-            Ok(PowerPolicyEvent::ProviderDisconnected(psu)) => {
+            Ok(PowerPolicyEvent::ProviderDisconnected(psu, _)) => {
                 assert!(ptr::eq(psu, port0.port));
             }
             _ => panic!("Did not receive provider disconnected event"),
@@ -394,6 +397,71 @@ impl Test for TestConsumerFlowTimerSinkReady {
     }
 }
 
+/// Sink ready timeout configured for port0 in [`TestSinkReadyTimeoutOverride`]
+const SINK_READY_TIMEOUT_OVERRIDE: Duration = Duration::from_millis(2000);
+
+/// Test that each port arms its sink-ready timer with its own configured timeout, falling back to
+/// twice the spec maximum transition time when no override is configured.
+struct TestSinkReadyTimeoutOverride;
+
+impl Test for TestSinkReadyTimeoutOverride {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        mut port0: TestPort<'port, 'ch>,
+        mut port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let default_timeout = Duration::from_millis(T_PS_TRANSITION_SPR_MS.maximum.0 as u64 * 2);
+
+        // Plug in a sink on both ports with a new consumer contract but no hardware sink-ready event.
+        let start = Instant::now();
+        for test_port in [&port0, &port1] {
+            test_port
+                .mock
+                .lock()
+                .await
+                .next_result_get_port_status
+                .push_back(Ok(PortStatus {
+                    available_sink_contract: Some(POWER_CAPABILITY_5V_1A5),
+                    connection_state: Some(ConnectionState::Attached),
+                    power_role: PowerRole::Sink,
+                    ..Default::default()
+                }));
+
+            let mut port_event = PortStatusEventBitfield::none();
+            port_event.set_plug_inserted_or_removed(true);
+            port_event.set_new_power_contract_as_consumer(true);
+            test_port
+                .port
+                .lock()
+                .await
+                .process_event(Event::PortEvent(PortEvent::StatusChanged(port_event)))
+                .await
+                .unwrap();
+        }
+        let armed = Instant::now();
+
+        // Each deadline is based on the port's own timeout
+        for (test_port, timeout) in [(&port0, SINK_READY_TIMEOUT_OVERRIDE), (&port1, default_timeout)] {
+            let deadline = test_port.shared_state.lock().await.sink_ready_timeout().unwrap();
+            assert!(deadline >= start + timeout);
+            assert!(deadline <= armed + timeout);
+        }
+
+        // The default timer fires first, the overridden one only once its longer timeout has elapsed
+        port1.event_receiver.wait_event().await;
+        let elapsed = start.elapsed();
+        assert!(elapsed >= default_timeout);
+        assert!(elapsed < SINK_READY_TIMEOUT_OVERRIDE);
+        assert!(port0.shared_state.lock().await.sink_ready_timeout().is_some());
+
+        port0.event_receiver.wait_event().await;
+        assert!(start.elapsed() >= SINK_READY_TIMEOUT_OVERRIDE);
+    }
+}
+
 /// Test that changing the max sink voltage while a consumer is connected disables the sink path and
 /// notifies the power policy, which broadcasts a `ConsumerDisconnected` event with the renegotiation
 /// flag set. Setting the same voltage should do neither.
@@ -751,7 +819,7 @@ impl Test for TestProviderToConsumerRoleSwap {
 
         // The provider should disconnect as soon as the swap completes.
         match with_timeout(DEFAULT_PER_CALL_TIMEOUT, power_policy_receiver.receive()).await {
-            Ok(PowerPolicyEvent::ProviderDisconnected(psu)) => {
+            Ok(PowerPolicyEvent::ProviderDisconnected(psu, _)) => {
                 assert!(ptr::eq(psu, port0.port));
             }
             _ => panic!("Did not receive provider disconnected event on role swap"),
@@ -807,6 +875,195 @@ impl Test for TestProviderToConsumerRoleSwap {
     }
 }
 
+/// Test that a port's power role policy only lets permitted contracts through to the power policy.
+struct TestPowerRolePolicy {
+    policy: PowerRolePolicy,
+}
+
+impl Test for TestPowerRolePolicy {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let consumer_allowed = self.policy != PowerRolePolicy::SourceOnly;
+        let provider_allowed = self.policy != PowerRolePolicy::SinkOnly;
+
+        {
+            // Report a sink connection
+            let mut mock0 = port0.mock.lock().await;
+            mock0.next_result_get_port_status.push_back(Ok(PortStatus {
+                available_sink_contract: Some(POWER_CAPABILITY_5V_1A5),
+                connection_state: Some(ConnectionState::Attached),
+                power_role: PowerRole::Sink,
+                ..Default::default()
+            }));
+            if consumer_allowed {
+                mock0.next_result_enable_sink_path.push_back(Ok(()));
+            }
+        }
+
+        let mut port_event = PortStatusEventBitfield::none();
+        port_event.set_plug_inserted_or_removed(true);
+        port_event.set_new_power_contract_as_consumer(true);
+        port_event.set_sink_ready(true);
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(port_event)))
+            .await
+            .unwrap();
+
+        let power_policy_result = with_timeout(DEFAULT_PER_CALL_TIMEOUT, power_policy_receiver.receive()).await;
+        if consumer_allowed {
+            assert!(matches!(
+                power_policy_result,
+                Ok(PowerPolicyEvent::ConsumerConnected(psu, _)) if ptr::eq(psu, port0.port)
+            ));
+            assert!(matches!(
+                port0.port.lock().await.state().psu_state,
+                PsuState::ConnectedConsumer(_)
+            ));
+        } else {
+            assert_eq!(power_policy_result.err(), Some(TimeoutError));
+            assert_eq!(port0.port.lock().await.state().psu_state, PsuState::Idle);
+            // The sink path should never have been touched
+            assert!(
+                port0
+                    .mock
+                    .lock()
+                    .await
+                    .fn_calls
+                    .iter()
+                    .all(|call| !matches!(call, ControllerFnCall::Pd(PdFnCall::EnableSinkPath(..))))
+            );
+        }
+
+        {
+            // Unplug
+            let mut mock0 = port0.mock.lock().await;
+            mock0.next_result_get_port_status.push_back(Ok(Default::default()));
+        }
+
+        let mut port_event = PortStatusEventBitfield::none();
+        port_event.set_plug_inserted_or_removed(true);
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(port_event)))
+            .await
+            .unwrap();
+
+        if consumer_allowed {
+            assert!(matches!(
+                with_timeout(DEFAULT_PER_CALL_TIMEOUT, power_policy_receiver.receive()).await,
+                Ok(PowerPolicyEvent::ConsumerDisconnected(..))
+            ));
+        }
+
+        {
+            // Report a source connection
+            let mut mock0 = port0.mock.lock().await;
+            mock0.next_result_get_port_status.push_back(Ok(PortStatus {
+                available_source_contract: Some(POWER_CAPABILITY_5V_1A5),
+                connection_state: Some(ConnectionState::Attached),
+                power_role: PowerRole::Source,
+                ..Default::default()
+            }));
+        }
+
+        let mut port_event = PortStatusEventBitfield::none();
+        port_event.set_plug_inserted_or_removed(true);
+        port_event.set_new_power_contract_as_provider(true);
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(port_event)))
+            .await
+            .unwrap();
+
+        let power_policy_result = with_timeout(DEFAULT_PER_CALL_TIMEOUT, power_policy_receiver.receive()).await;
+        if provider_allowed {
+            assert!(matches!(
+                power_policy_result,
+                Ok(PowerPolicyEvent::ProviderConnected(psu, _)) if ptr::eq(psu, port0.port)
+            ));
+            assert!(matches!(
+                port0.port.lock().await.state().psu_state,
+                PsuState::ConnectedProvider(_)
+            ));
+        } else {
+            assert_eq!(power_policy_result.err(), Some(TimeoutError));
+            assert_eq!(port0.port.lock().await.state().psu_state, PsuState::Idle);
+        }
+    }
+}
+
+/// Test that a dual-role port reports both its sink and source capabilities, filtered by its power role policy.
+///
+/// Port0 is dual-role, port1 is sink-only.
+struct TestRoleCapabilities;
+
+impl Test for TestRoleCapabilities {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let status = PortStatus {
+            available_sink_contract: Some(POWER_CAPABILITY_5V_1A5),
+            available_source_contract: Some(POWER_CAPABILITY_5V_3A0),
+            connection_state: Some(ConnectionState::Attached),
+            dual_power: true,
+            ..Default::default()
+        };
+        let sink = ConsumerPowerCapability {
+            capability: POWER_CAPABILITY_5V_1A5,
+            flags: ConsumerFlags::none().with_psu_type(PsuType::TypeC),
+        };
+        let source = ProviderPowerCapability {
+            capability: POWER_CAPABILITY_5V_3A0,
+            flags: ProviderFlags::none().with_psu_type(PsuType::TypeC),
+        };
+
+        for (port, expected) in [
+            (
+                &port0,
+                RoleCapabilities {
+                    sink: Some(sink),
+                    source: Some(source),
+                },
+            ),
+            (
+                &port1,
+                RoleCapabilities {
+                    sink: Some(sink),
+                    source: None,
+                },
+            ),
+        ] {
+            port.mock.lock().await.next_result_get_port_status.push_back(Ok(status));
+            assert_eq!(port.port.lock().await.get_role_capabilities().await.unwrap(), expected);
+
+            let mut mock = port.mock.lock().await;
+            assert!(matches!(
+                mock.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(_)))
+            ));
+            assert!(mock.fn_calls.is_empty());
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_basic_consumer_flow() {
     common::run_test(
@@ -872,3 +1129,56 @@ async fn test_provider_to_consumer_role_swap() {
     )
     .await;
 }
+
+#[tokio::test]
+async fn test_sink_ready_timeout_override() {
+    let mut config = Config::default();
+    config.sink_ready_timeout = Some(SINK_READY_TIMEOUT_OVERRIDE);
+    common::run_test(
+        Duration::from_secs(10),
+        Default::default(),
+        [config, Default::default(), Default::default()],
+        TestSinkReadyTimeoutOverride,
+    )
+    .await;
+}
+
+async fn run_power_role_policy_test(policy: PowerRolePolicy) {
+    let mut config = Config::default();
+    config.power_role_policy = policy;
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        [config, Default::default(), Default::default()],
+        TestPowerRolePolicy { policy },
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_power_role_policy_source_only() {
+    run_power_role_policy_test(PowerRolePolicy::SourceOnly).await;
+}
+
+#[tokio::test]
+async fn test_power_role_policy_sink_only() {
+    run_power_role_policy_test(PowerRolePolicy::SinkOnly).await;
+}
+
+#[tokio::test]
+async fn test_role_capabilities() {
+    let mut sink_only = Config::default();
+    sink_only.power_role_policy = PowerRolePolicy::SinkOnly;
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        [Default::default(), sink_only, Default::default()],
+        TestRoleCapabilities,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_power_role_policy_dual_role() {
+    run_power_role_policy_test(PowerRolePolicy::DualRole).await;
+}
@@ -0,0 +1,229 @@
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+use embassy_futures::select::{Either, select};
+use embassy_sync::channel::DynamicReceiver;
+use embassy_sync::mutex::Mutex;
+use embassy_time::{Duration, with_timeout};
+use embedded_usb_pd::{LocalPortId, PdError};
+use type_c_interface::{
+    control::pd::PortStatus,
+    port::event::{PortEvent, PortEventBitfield, PortStatusEventBitfield},
+};
+use type_c_interface_test_mocks::controller::{FnCall as ControllerFnCall, Mock, pd::FnCall as PdFnCall};
+use type_c_service::controller::{
+    event::{Event, Loopback},
+    event_receiver::EventReceiver,
+    recovery::{self, ControllerRecovery},
+    state::SharedState,
+};
+
+mod common;
+
+use common::{ControllerMockMutexType, ControllerPortMutexType, PortChannels, PortSharedState, create_port};
+
+/// Number of ports on the mock controller
+const PORT_COUNT: usize = 2;
+
+/// Number of consecutive errors before the controller is reset
+const RESET_ERROR_THRESHOLD: u8 = 3;
+
+/// Timeout for a single event
+const EVENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+type PortType<'a> = ControllerPortMutexType<'a, ControllerMockMutexType>;
+type EventReceiverType<'a> =
+    EventReceiver<'a, PortSharedState, DynamicReceiver<'a, PortEventBitfield>, DynamicReceiver<'a, Loopback>>;
+
+/// Create a port and its event receiver, both registered with the controller recovery state
+async fn create_recovery_port<'a>(
+    name: &'static str,
+    local_port: LocalPortId,
+    controller: &'a ControllerMockMutexType,
+    shared_state: &'a PortSharedState,
+    channels: &'a PortChannels,
+    recovery: &'a ControllerRecovery<PORT_COUNT>,
+) -> (PortType<'a>, EventReceiverType<'a>) {
+    let port = create_port(name, local_port, controller, shared_state, channels);
+    port.lock().await.register_recovery(recovery);
+
+    let mut event_receiver = EventReceiver::new(
+        shared_state,
+        channels.interrupt.dyn_receiver(),
+        channels.loopback.dyn_receiver(),
+    );
+    event_receiver.register_recovery(recovery);
+
+    (port, event_receiver)
+}
+
+/// Process a status changed event on `port`
+async fn process_status_changed(port: &PortType<'_>) -> Result<(), PdError> {
+    port.lock()
+        .await
+        .process_event_with_recovery(Event::PortEvent(PortEvent::StatusChanged(
+            PortStatusEventBitfield::none(),
+        )))
+        .await
+        .map(|_| ())
+}
+
+/// Test that errors on different ports of a controller count towards a single reset, after which every port on the
+/// controller re-synchronizes.
+#[tokio::test]
+async fn test_reset_and_recover() {
+    let controller = Mutex::new(Mock::new("mock0"));
+    let mut config = recovery::Config::default();
+    config.reset_error_threshold = RESET_ERROR_THRESHOLD;
+    let recovery = ControllerRecovery::<PORT_COUNT>::new(config);
+
+    let port0_shared_state = Mutex::new(SharedState::new());
+    let port0_channels = PortChannels::new();
+    let (port0, mut port0_event_receiver) = create_recovery_port(
+        "port0",
+        LocalPortId(0),
+        &controller,
+        &port0_shared_state,
+        &port0_channels,
+        &recovery,
+    )
+    .await;
+
+    let port1_shared_state = Mutex::new(SharedState::new());
+    let port1_channels = PortChannels::new();
+    let (port1, mut port1_event_receiver) = create_recovery_port(
+        "port1",
+        LocalPortId(1),
+        &controller,
+        &port1_shared_state,
+        &port1_channels,
+        &recovery,
+    )
+    .await;
+
+    {
+        let mut mock = controller.lock().await;
+        for _ in 0..RESET_ERROR_THRESHOLD {
+            mock.next_result_get_port_status.push_back(Err(PdError::Failed));
+        }
+        mock.next_result_reset_controller.push_back(Ok(()));
+    }
+
+    // Errors alternate between ports, the controller is reset once the threshold is reached across both
+    assert_eq!(process_status_changed(&port0).await, Err(PdError::Failed));
+    assert_eq!(process_status_changed(&port1).await, Err(PdError::Failed));
+    assert_eq!(process_status_changed(&port0).await, Err(PdError::Failed));
+
+    {
+        let mut mock = controller.lock().await;
+        for port in [0, 1, 0] {
+            assert!(matches!(
+                mock.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(LocalPortId(p)))) if p == port
+            ));
+        }
+        assert!(matches!(
+            mock.fn_calls.pop_front(),
+            Some(ControllerFnCall::ResetController)
+        ));
+        assert!(mock.fn_calls.is_empty());
+
+        mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+        mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+    }
+
+    // Both ports are notified of the reset and re-synchronize
+    for (port, event_receiver) in [(&port0, &mut port0_event_receiver), (&port1, &mut port1_event_receiver)] {
+        let event = with_timeout(EVENT_TIMEOUT, event_receiver.wait_event()).await.unwrap();
+        assert!(matches!(event, Event::ControllerReset));
+        port.lock().await.process_event_with_recovery(event).await.unwrap();
+    }
+
+    {
+        let mut mock = controller.lock().await;
+        for port in [0, 1] {
+            assert!(matches!(
+                mock.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(LocalPortId(p)))) if p == port
+            ));
+        }
+        assert!(mock.fn_calls.is_empty());
+
+        mock.next_result_get_port_status.push_back(Err(PdError::Failed));
+    }
+
+    // The error count was cleared by the reset, a single error doesn't reset the controller again
+    assert_eq!(process_status_changed(&port1).await, Err(PdError::Failed));
+
+    let mut mock = controller.lock().await;
+    assert!(matches!(
+        mock.fn_calls.pop_front(),
+        Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(LocalPortId(1))))
+    ));
+    assert!(mock.fn_calls.is_empty());
+}
+
+/// Test that a port waiting to retry a controller reset doesn't block other ports on the controller.
+#[tokio::test]
+async fn test_backoff_does_not_block() {
+    let controller = Mutex::new(Mock::new("mock0"));
+    let mut config = recovery::Config::default();
+    config.reset_error_threshold = 1;
+    // Long enough that the test would time out if the other port had to wait for it
+    config.reset_backoff = Duration::from_secs(3600);
+    let recovery = ControllerRecovery::<PORT_COUNT>::new(config);
+
+    let port0_shared_state = Mutex::new(SharedState::new());
+    let port0_channels = PortChannels::new();
+    let (port0, _port0_event_receiver) = create_recovery_port(
+        "port0",
+        LocalPortId(0),
+        &controller,
+        &port0_shared_state,
+        &port0_channels,
+        &recovery,
+    )
+    .await;
+
+    let port1_shared_state = Mutex::new(SharedState::new());
+    let port1_channels = PortChannels::new();
+    let (port1, _port1_event_receiver) = create_recovery_port(
+        "port1",
+        LocalPortId(1),
+        &controller,
+        &port1_shared_state,
+        &port1_channels,
+        &recovery,
+    )
+    .await;
+
+    {
+        let mut mock = controller.lock().await;
+        mock.next_result_get_port_status.push_back(Err(PdError::Failed));
+        mock.next_result_get_port_status.push_back(Err(PdError::Failed));
+        mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+        mock.next_result_reset_controller.push_back(Err(PdError::Failed));
+    }
+
+    // The first reset attempt is made right away and fails
+    assert_eq!(process_status_changed(&port0).await, Err(PdError::Failed));
+
+    // The second waits out the backoff, while port1 keeps processing events
+    match select(
+        process_status_changed(&port0),
+        with_timeout(EVENT_TIMEOUT, process_status_changed(&port1)),
+    )
+    .await
+    {
+        Either::First(_) => panic!("Controller reset didn't wait for the backoff"),
+        Either::Second(result) => assert_eq!(result, Ok(Ok(()))),
+    }
+
+    let mock = controller.lock().await;
+    assert_eq!(
+        mock.fn_calls
+            .iter()
+            .filter(|call| matches!(call, ControllerFnCall::ResetController))
+            .count(),
+        1
+    );
+}
@@ -0,0 +1,97 @@
+#![allow(clippy::unwrap_used)]
+use embassy_sync::mutex::Mutex;
+use embedded_services::GlobalRawMutex;
+use embedded_usb_pd::{LocalPortId, PdError, PowerRole, type_c::ConnectionState};
+use power_policy_interface::capability::PowerCapability;
+use type_c_interface::control::pd::PortStatus;
+use type_c_interface_test_mocks::controller::{FnCall as ControllerFnCall, Mock, pd::FnCall as PdFnCall};
+use type_c_service::controller::snapshot::{PortSummary, get_all_port_status, get_controller_summary};
+
+/// Number of ports on the mock controller
+const PORT_COUNT: usize = 2;
+
+/// Test that the status of every port on a two-port controller is returned from a single call
+#[tokio::test]
+async fn test_get_all_port_status() {
+    let port1_status = PortStatus {
+        connection_state: Some(ConnectionState::Attached),
+        ..Default::default()
+    };
+
+    let controller = Mutex::<GlobalRawMutex, _>::new(Mock::new("mock0"));
+    {
+        let mut mock = controller.lock().await;
+        mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+        mock.next_result_get_port_status.push_back(Ok(port1_status));
+    }
+
+    let statuses = get_all_port_status::<_, PORT_COUNT>(&controller).await.unwrap();
+    assert_eq!(statuses.as_slice(), &[PortStatus::default(), port1_status]);
+
+    let mut mock = controller.lock().await;
+    assert!(matches!(
+        mock.fn_calls.pop_front(),
+        Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(LocalPortId(0))))
+    ));
+    assert!(matches!(
+        mock.fn_calls.pop_front(),
+        Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(LocalPortId(1))))
+    ));
+    assert!(mock.fn_calls.is_empty());
+}
+
+/// Test that an error querying any port fails the whole snapshot
+#[tokio::test]
+async fn test_get_all_port_status_error() {
+    let controller = Mutex::<GlobalRawMutex, _>::new(Mock::new("mock0"));
+    {
+        let mut mock = controller.lock().await;
+        mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+        mock.next_result_get_port_status.push_back(Err(PdError::Failed));
+    }
+
+    assert_eq!(
+        get_all_port_status::<_, PORT_COUNT>(&controller).await,
+        Err(PdError::Failed)
+    );
+}
+
+/// Test that the summary of a two-port controller reflects a connected consumer and a disconnected port
+#[tokio::test]
+async fn test_get_controller_summary() {
+    let port0_status = PortStatus {
+        connection_state: Some(ConnectionState::Attached),
+        power_role: PowerRole::Sink,
+        available_sink_contract: Some(PowerCapability {
+            voltage_mv: 20000,
+            current_ma: 3000,
+        }),
+        ..Default::default()
+    };
+
+    let controller = Mutex::<GlobalRawMutex, _>::new(Mock::new("mock0"));
+    {
+        let mut mock = controller.lock().await;
+        mock.next_result_get_port_status.push_back(Ok(port0_status));
+        mock.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+    }
+
+    let summary = get_controller_summary::<_, PORT_COUNT>(&controller).await.unwrap();
+    assert_eq!(summary.name, "mock0");
+    assert_eq!(
+        summary.ports.as_slice(),
+        &[
+            PortSummary {
+                connected: true,
+                power_role: PowerRole::Sink,
+                contract_mw: Some(60000),
+            },
+            PortSummary {
+                connected: false,
+                power_role: PowerRole::Sink,
+                contract_mw: None,
+            },
+        ]
+    );
+    assert_eq!(summary.connected_ports(), 1);
+}
@@ -0,0 +1,93 @@
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+use embassy_time::with_timeout;
+use embedded_usb_pd::{PowerRole, type_c::ConnectionState};
+use type_c_interface::{
+    control::pd::PortStatus,
+    port::event::{PortEvent, PortEventBitfield},
+    util::POWER_CAPABILITY_5V_1A5,
+};
+use type_c_interface_test_mocks::controller::{FnCall as ControllerFnCall, pd::FnCall as PdFnCall};
+use type_c_service::controller::event::Event;
+
+use crate::common::{
+    DEFAULT_PER_CALL_TIMEOUT, DEFAULT_TEST_DURATION, PowerPolicyServiceReceiver, Test, TestPort, TypeCServiceReceiver,
+};
+
+mod common;
+
+/// Test that syncing a port discards controller events received before the sync and re-reads the port status.
+struct TestSyncDiscardsPendingEvents;
+
+impl Test for TestSyncDiscardsPendingEvents {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let TestPort {
+            port,
+            mock,
+            interrupt_sender,
+            mut event_receiver,
+            ..
+        } = port0;
+
+        // Raise an interrupt with two events, only the first is returned before the sync
+        let mut interrupt = PortEventBitfield::none();
+        interrupt.notification.set_alert(true);
+        interrupt.notification.set_dp_status_update(true);
+        interrupt_sender.send(interrupt).await;
+
+        let event = event_receiver.wait_event().await;
+        assert!(matches!(event, Event::PortEvent(PortEvent::Alert)));
+
+        // The controller now reports a connected sink
+        mock.lock().await.next_result_get_port_status.push_back(Ok(PortStatus {
+            available_sink_contract: Some(POWER_CAPABILITY_5V_1A5),
+            connection_state: Some(ConnectionState::Attached),
+            power_role: PowerRole::Sink,
+            ..Default::default()
+        }));
+        port.lock().await.sync_state().await.unwrap();
+
+        {
+            let mut mock0 = mock.lock().await;
+            assert!(matches!(
+                mock0.fn_calls.pop_front(),
+                Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(_)))
+            ));
+            assert!(mock0.fn_calls.is_empty());
+        }
+
+        // The stale DP status update is dropped in favor of the events generated by the sync
+        let event = with_timeout(DEFAULT_PER_CALL_TIMEOUT, event_receiver.wait_event())
+            .await
+            .unwrap();
+        let Event::PortEvent(PortEvent::StatusChanged(status_event)) = event else {
+            panic!("Expected status changed event, got {event:?}");
+        };
+        assert!(status_event.plug_inserted_or_removed());
+        assert!(status_event.new_power_contract_as_consumer());
+
+        assert!(
+            with_timeout(DEFAULT_PER_CALL_TIMEOUT, event_receiver.wait_event())
+                .await
+                .is_err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_sync_discards_pending_events() {
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        Default::default(),
+        TestSyncDiscardsPendingEvents,
+    )
+    .await;
+}
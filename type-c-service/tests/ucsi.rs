@@ -0,0 +1,287 @@
+#![allow(clippy::unwrap_used)]
+#![allow(clippy::panic)]
+use embassy_time::with_timeout;
+use embedded_usb_pd::type_c::ConnectionState;
+use embedded_usb_pd::ucsi::ppm::set_notification_enable::{self, NotificationEnable};
+use embedded_usb_pd::ucsi::{GlobalCommand, lpm, ppm};
+use embedded_usb_pd::{GlobalPortId, LocalPortId, PowerRole};
+use type_c_interface::{
+    control::pd::PortStatus,
+    port::event::{PortEvent, PortStatusEventBitfield},
+    service::event::{EventData, UsciChangeIndicatorData},
+    ucsi::Lpm as _,
+};
+use type_c_interface_test_mocks::controller::{FnCall as ControllerFnCall, pd::FnCall as PdFnCall, ucsi::FnCall};
+use type_c_service::controller::{config::Config, event::Event};
+
+use crate::common::{
+    DEFAULT_PER_CALL_TIMEOUT, DEFAULT_TEST_DURATION, PowerPolicyServiceReceiver, Test, TestPort, TypeCServiceMutexType,
+    TypeCServiceReceiver,
+};
+
+mod common;
+
+/// Connector status command for `port`
+fn get_connector_status(port: LocalPortId) -> lpm::LocalCommand {
+    lpm::LocalCommand::new(port, lpm::CommandData::GetConnectorStatus)
+}
+
+/// Test that a cached UCSI connector status is served without a controller call until a port event is processed, and
+/// is never served for a different connector.
+struct TestCachedConnectorStatus;
+
+impl Test for TestCachedConnectorStatus {
+    async fn run<'port, 'ch>(
+        &mut self,
+        _type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        let response = lpm::ResponseData::GetConnectorStatus(lpm::get_connector_status::ResponseData::default());
+        port0
+            .mock
+            .lock()
+            .await
+            .next_result_execute_lpm_command
+            .push_back(Ok(Some(response)));
+
+        // The first read goes to the controller, the second is served from the cache
+        for _ in 0..2 {
+            let result = port0
+                .port
+                .lock()
+                .await
+                .execute_lpm_command(get_connector_status(LocalPortId(0)))
+                .await
+                .unwrap();
+            assert!(matches!(result, Some(lpm::ResponseData::GetConnectorStatus(_))));
+        }
+
+        // A read for another connector always goes to the controller and leaves the cached status in place
+        port0
+            .mock
+            .lock()
+            .await
+            .next_result_execute_lpm_command
+            .push_back(Ok(Some(response)));
+        port0
+            .port
+            .lock()
+            .await
+            .execute_lpm_command(get_connector_status(LocalPortId(1)))
+            .await
+            .unwrap();
+        port0
+            .port
+            .lock()
+            .await
+            .execute_lpm_command(get_connector_status(LocalPortId(0)))
+            .await
+            .unwrap();
+
+        {
+            let mut mock0 = port0.mock.lock().await;
+            for port in [LocalPortId(0), LocalPortId(1)] {
+                assert!(matches!(
+                    mock0.fn_calls.pop_front(),
+                    Some(ControllerFnCall::Ucsi(FnCall::ExecuteLpm(command))) if command.port() == port
+                ));
+            }
+            assert!(mock0.fn_calls.is_empty());
+
+            mock0.next_result_get_port_status.push_back(Ok(PortStatus::default()));
+            mock0.next_result_execute_lpm_command.push_back(Ok(Some(response)));
+        }
+
+        // A port event invalidates the cache
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(
+                PortStatusEventBitfield::none(),
+            )))
+            .await
+            .unwrap();
+
+        port0
+            .port
+            .lock()
+            .await
+            .execute_lpm_command(get_connector_status(LocalPortId(0)))
+            .await
+            .unwrap();
+
+        let mut mock0 = port0.mock.lock().await;
+        assert!(matches!(
+            mock0.fn_calls.pop_front(),
+            Some(ControllerFnCall::Pd(PdFnCall::GetPortStatus(_)))
+        ));
+        assert!(matches!(
+            mock0.fn_calls.pop_front(),
+            Some(ControllerFnCall::Ucsi(FnCall::ExecuteLpm(_)))
+        ));
+        assert!(mock0.fn_calls.is_empty());
+    }
+}
+
+/// Test that a plug event produces a UCSI connector change notification once the OPM has enabled them.
+struct TestConnectorChangeNotification;
+
+impl Test for TestConnectorChangeNotification {
+    async fn setup<'port, 'ch>(&mut self, type_c_service: &TypeCServiceMutexType<'port, 'ch>) {
+        let mut notification_enable = NotificationEnable::default();
+        notification_enable.set_cmd_complete(true);
+        notification_enable.set_connect_change(true);
+
+        let response = type_c_service
+            .lock()
+            .await
+            .process_ucsi_command(&GlobalCommand::PpmCommand(ppm::Command::SetNotificationEnable(
+                set_notification_enable::Args { notification_enable },
+            )))
+            .await;
+        assert!(response.cci.cmd_complete());
+        assert!(response.data.is_ok());
+    }
+
+    async fn run<'port, 'ch>(
+        &mut self,
+        type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        port0
+            .mock
+            .lock()
+            .await
+            .next_result_get_port_status
+            .push_back(Ok(PortStatus {
+                connection_state: Some(ConnectionState::Attached),
+                power_role: PowerRole::Sink,
+                ..Default::default()
+            }));
+
+        let mut port_event = PortStatusEventBitfield::none();
+        port_event.set_plug_inserted_or_removed(true);
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(port_event)))
+            .await
+            .unwrap();
+
+        let event = with_timeout(DEFAULT_PER_CALL_TIMEOUT, type_c_receiver.receive())
+            .await
+            .unwrap();
+        let EventData::UsciChangeIndicator(data) = event.event else {
+            panic!("Expected UsciChangeIndicator event");
+        };
+        // No other connector changes are pending, so the OPM should be notified
+        assert_eq!(
+            data,
+            UsciChangeIndicatorData {
+                port: GlobalPortId(0),
+                notify_opm: true,
+            }
+        );
+    }
+}
+
+/// Test that a plug event produces no UCSI notification once the OPM has disabled connector change notifications.
+struct TestConnectorChangeNotificationDisabled;
+
+impl Test for TestConnectorChangeNotificationDisabled {
+    async fn setup<'port, 'ch>(&mut self, type_c_service: &TypeCServiceMutexType<'port, 'ch>) {
+        // Only command completion notifications are enabled
+        let mut notification_enable = NotificationEnable::default();
+        notification_enable.set_cmd_complete(true);
+
+        let response = type_c_service
+            .lock()
+            .await
+            .process_ucsi_command(&GlobalCommand::PpmCommand(ppm::Command::SetNotificationEnable(
+                set_notification_enable::Args { notification_enable },
+            )))
+            .await;
+        assert!(response.cci.cmd_complete());
+        assert!(response.data.is_ok());
+    }
+
+    async fn run<'port, 'ch>(
+        &mut self,
+        type_c_receiver: TypeCServiceReceiver<'port, 'ch>,
+        _power_policy_receiver: PowerPolicyServiceReceiver<'port, 'ch>,
+        port0: TestPort<'port, 'ch>,
+        _port1: TestPort<'port, 'ch>,
+        _port2: TestPort<'port, 'ch>,
+    ) {
+        port0
+            .mock
+            .lock()
+            .await
+            .next_result_get_port_status
+            .push_back(Ok(PortStatus {
+                connection_state: Some(ConnectionState::Attached),
+                power_role: PowerRole::Sink,
+                ..Default::default()
+            }));
+
+        let mut port_event = PortStatusEventBitfield::none();
+        port_event.set_plug_inserted_or_removed(true);
+        port0
+            .port
+            .lock()
+            .await
+            .process_event(Event::PortEvent(PortEvent::StatusChanged(port_event)))
+            .await
+            .unwrap();
+
+        assert!(
+            with_timeout(DEFAULT_PER_CALL_TIMEOUT, type_c_receiver.receive())
+                .await
+                .is_err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_cached_connector_status() {
+    let mut config = Config::default();
+    config.cache_ucsi_connector_status = true;
+
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        [config, Default::default(), Default::default()],
+        TestCachedConnectorStatus,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_connector_change_notification() {
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        Default::default(),
+        TestConnectorChangeNotification,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_connector_change_notification_disabled() {
+    common::run_test(
+        DEFAULT_TEST_DURATION,
+        Default::default(),
+        Default::default(),
+        TestConnectorChangeNotificationDisabled,
+    )
+    .await;
+}